@@ -0,0 +1,200 @@
+use derivative::Derivative;
+use serde::{Deserialize, Serialize};
+use serde_alias::serde_alias;
+
+use super::block::{BlockReader, BlockWriter};
+
+/// Decoded `Behavior::data`.
+///
+/// Only the leading fields of each behavior are decoded, anything after those is kept in `trailing`
+/// and written back verbatim, so decoding is always lossless.
+/// The field layout hasn't been checked against behavior data from a real game yet.
+///
+/// There is no way to create new behavior data: the defaults of the decoded structs leave `trailing` empty,
+/// which is not a complete behavior. New behaviors copy the data of an existing one instead.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum BehaviorData {
+	Platform(PlatformBehaviorData),
+	Sine(SineBehaviorData),
+	CustomMovement(CustomMovementBehaviorData),
+	Unknown(Vec<u8>),
+}
+impl Default for BehaviorData {
+	fn default() -> Self {
+		BehaviorData::Unknown(Vec::new())
+	}
+}
+
+impl BehaviorData {
+	/// Decode structured behavior data based on the name of the behavior's plugin
+	pub fn decode(data: Vec<u8>, plugin_name: &str) -> Self {
+		use BehaviorData as E;
+		match plugin_name {
+			"Platform" => PlatformBehaviorData::from_bin(&data).map_or(E::Unknown(data), E::Platform),
+			"Sine" => SineBehaviorData::from_bin(&data).map_or(E::Unknown(data), E::Sine),
+			"Custom Movement" => CustomMovementBehaviorData::from_bin(&data).map_or(E::Unknown(data), E::CustomMovement),
+			_ => E::Unknown(data),
+		}
+	}
+	pub fn encode(self) -> Vec<u8> {
+		match self {
+			BehaviorData::Platform(data) => data.to_bin(),
+			BehaviorData::Sine(data) => data.to_bin(),
+			BehaviorData::CustomMovement(data) => data.to_bin(),
+			BehaviorData::Unknown(data) => data,
+		}
+	}
+	/// Whether this is the variant that [`BehaviorData::decode`] produces for the plugin's data
+	pub fn is_for_plugin(&self, plugin_name: &str) -> bool {
+		use BehaviorData as E;
+		match plugin_name {
+			"Platform" => matches!(self, E::Platform(_)),
+			"Sine" => matches!(self, E::Sine(_)),
+			"Custom Movement" => matches!(self, E::CustomMovement(_)),
+			_ => matches!(self, E::Unknown(_)),
+		}
+	}
+}
+
+#[serde_alias(SnakeCase)]
+#[derive(Derivative, Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[derivative(Default)]
+pub struct PlatformBehaviorData {
+	#[derivative(Default(value = "1"))]
+	pub version: i32,
+	/// Pixels per second
+	#[derivative(Default(value = "330.0"))]
+	pub max_speed: f32,
+	/// Pixels per second per second
+	#[derivative(Default(value = "1500.0"))]
+	pub acceleration: f32,
+	/// Pixels per second per second
+	#[derivative(Default(value = "1500.0"))]
+	pub deceleration: f32,
+	/// Initial upward speed of a jump, in pixels per second
+	#[derivative(Default(value = "650.0"))]
+	pub jump_strength: f32,
+	/// Pixels per second per second
+	#[derivative(Default(value = "1500.0"))]
+	pub gravity: f32,
+	/// Pixels per second
+	#[derivative(Default(value = "1000.0"))]
+	pub max_fall_speed: f32,
+	pub trailing: Vec<u8>,
+}
+impl PlatformBehaviorData {
+	/// Returns `None` if the data is too short to contain the known fields
+	pub fn from_bin(bin: &[u8]) -> Option<Self> {
+		if bin.len() < 28 { return None }
+		let mut bin = BlockReader::new(bin);
+		Some(PlatformBehaviorData {
+			version: bin.read_i32(),
+			max_speed: bin.read_f32(),
+			acceleration: bin.read_f32(),
+			deceleration: bin.read_f32(),
+			jump_strength: bin.read_f32(),
+			gravity: bin.read_f32(),
+			max_fall_speed: bin.read_f32(),
+			trailing: bin.read_remaining(),
+		})
+	}
+	pub fn to_bin(&self) -> Vec<u8> {
+		let mut bin = BlockWriter::new();
+		bin.write_i32(self.version);
+		bin.write_f32(self.max_speed);
+		bin.write_f32(self.acceleration);
+		bin.write_f32(self.deceleration);
+		bin.write_f32(self.jump_strength);
+		bin.write_f32(self.gravity);
+		bin.write_f32(self.max_fall_speed);
+		bin.write_bytes(&self.trailing);
+		bin.buffer
+	}
+}
+
+#[serde_alias(SnakeCase)]
+#[derive(Derivative, Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[derivative(Default)]
+pub struct SineBehaviorData {
+	#[derivative(Default(value = "1"))]
+	pub version: i32,
+	/// 0 = horizontal, 1 = vertical, 2 = forwards/backwards, 3 = size, 4 = angle, 5 = opacity
+	pub movement: i32,
+	/// 0 = sine, 1 = triangle, 2 = sawtooth, 3 = reverse sawtooth, 4 = square
+	pub wave: i32,
+	/// Seconds
+	#[derivative(Default(value = "4.0"))]
+	pub period: f32,
+	pub period_random: f32,
+	#[derivative(Default(value = "50.0"))]
+	pub magnitude: f32,
+	pub magnitude_random: f32,
+	pub trailing: Vec<u8>,
+}
+impl SineBehaviorData {
+	/// Returns `None` if the data is too short to contain the known fields
+	pub fn from_bin(bin: &[u8]) -> Option<Self> {
+		if bin.len() < 28 { return None }
+		let mut bin = BlockReader::new(bin);
+		Some(SineBehaviorData {
+			version: bin.read_i32(),
+			movement: bin.read_i32(),
+			wave: bin.read_i32(),
+			period: bin.read_f32(),
+			period_random: bin.read_f32(),
+			magnitude: bin.read_f32(),
+			magnitude_random: bin.read_f32(),
+			trailing: bin.read_remaining(),
+		})
+	}
+	pub fn to_bin(&self) -> Vec<u8> {
+		let mut bin = BlockWriter::new();
+		bin.write_i32(self.version);
+		bin.write_i32(self.movement);
+		bin.write_i32(self.wave);
+		bin.write_f32(self.period);
+		bin.write_f32(self.period_random);
+		bin.write_f32(self.magnitude);
+		bin.write_f32(self.magnitude_random);
+		bin.write_bytes(&self.trailing);
+		bin.buffer
+	}
+}
+
+#[serde_alias(SnakeCase)]
+#[derive(Derivative, Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[derivative(Default)]
+pub struct CustomMovementBehaviorData {
+	#[derivative(Default(value = "1"))]
+	pub version: i32,
+	/// 0 = none, 1 = linear, 2 = circular
+	pub stepping_mode: i32,
+	#[derivative(Default(value = "5"))]
+	pub pixels_per_step: i32,
+	pub trailing: Vec<u8>,
+}
+impl CustomMovementBehaviorData {
+	/// Returns `None` if the data is too short to contain the known fields
+	pub fn from_bin(bin: &[u8]) -> Option<Self> {
+		if bin.len() < 12 { return None }
+		let mut bin = BlockReader::new(bin);
+		Some(CustomMovementBehaviorData {
+			version: bin.read_i32(),
+			stepping_mode: bin.read_i32(),
+			pixels_per_step: bin.read_i32(),
+			trailing: bin.read_remaining(),
+		})
+	}
+	pub fn to_bin(&self) -> Vec<u8> {
+		let mut bin = BlockWriter::new();
+		bin.write_i32(self.version);
+		bin.write_i32(self.stepping_mode);
+		bin.write_i32(self.pixels_per_step);
+		bin.write_bytes(&self.trailing);
+		bin.buffer
+	}
+}
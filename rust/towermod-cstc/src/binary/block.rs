@@ -19,6 +19,10 @@ impl<'a> BlockReader<'a> {
 		self.buffer[self.pos-amount..self.pos].to_owned()
 	}
 
+	pub fn read_remaining(&mut self) -> Vec<u8> {
+		self.read_bytes(self.buffer.len() - self.pos)
+	}
+
 	pub fn read_string(&mut self) -> String {
 		let len = self.read_u32() as usize;
 		assert_eq!(self.buffer[self.pos+len-1], 0); // all strings end with a NUL
@@ -78,6 +82,46 @@ impl<'a> BlockReader<'a> {
 		f64::from_le_bytes(self.buffer[self.pos-8..self.pos].try_into().unwrap())
 	}
 
+	// Checked variants of the readers above, for data that may be truncated or corrupt.
+	// They return `None` instead of panicking when the buffer ends early.
+
+	pub fn try_read_bytes(&mut self, amount: usize) -> Option<&'a [u8]> {
+		let bytes = self.buffer.get(self.pos..self.pos.checked_add(amount)?)?;
+		self.pos += amount;
+		Some(bytes)
+	}
+
+	pub fn try_read_u8(&mut self) -> Option<u8> {
+		Some(self.try_read_bytes(1)?[0])
+	}
+
+	pub fn try_read_i32(&mut self) -> Option<i32> {
+		Some(i32::from_le_bytes(self.try_read_bytes(4)?.try_into().unwrap()))
+	}
+
+	pub fn try_read_u32(&mut self) -> Option<u32> {
+		Some(u32::from_le_bytes(self.try_read_bytes(4)?.try_into().unwrap()))
+	}
+
+	pub fn try_read_f32(&mut self) -> Option<f32> {
+		Some(f32::from_le_bytes(self.try_read_bytes(4)?.try_into().unwrap()))
+	}
+
+	pub fn try_read_i64(&mut self) -> Option<i64> {
+		Some(i64::from_le_bytes(self.try_read_bytes(8)?.try_into().unwrap()))
+	}
+
+	pub fn try_read_f64(&mut self) -> Option<f64> {
+		Some(f64::from_le_bytes(self.try_read_bytes(8)?.try_into().unwrap()))
+	}
+
+	pub fn try_read_string(&mut self) -> Option<String> {
+		let len = self.try_read_u32()? as usize;
+		let (nul, slice) = self.try_read_bytes(len)?.split_last()?;
+		if *nul != 0 { return None } // all strings end with a NUL
+		Some(String::from_utf8_lossy(slice).to_string())
+	}
+
 	pub fn read_data_key(&mut self) -> DataKey {
 		let key = self.read_string();
 		let i_type = self.read_i32();
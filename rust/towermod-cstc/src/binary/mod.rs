@@ -4,6 +4,7 @@ mod eventblock;
 mod appblock;
 mod levelblock;
 mod pluginobject;
+mod behaviordata;
 mod system_plugin_object;
 
 pub use imageblock::*;
@@ -11,4 +12,5 @@ pub use eventblock::*;
 pub use appblock::*;
 pub use levelblock::*;
 pub use pluginobject::*;
+pub use behaviordata::*;
 pub use system_plugin_object::*;
@@ -28,6 +28,10 @@ pub struct ObjHeader {
 pub enum ObjectData {
 	Text(TextObjectData),
 	Sprite(SpriteObjectData),
+	TiledBackground(TiledBackgroundObjectData),
+	Panel(PanelObjectData),
+	Gradient(GradientObjectData),
+	Canvas(CanvasObjectData),
 	Unknown(Vec<u8>),
 }
 impl Default for ObjectData {
@@ -42,7 +46,7 @@ impl ObjectData {
 		use ObjectData as E;
 		match plugin_name {
 			"XAudio2" => E::Unknown(data),
-			"Canvas" => E::Unknown(data),
+			"Canvas" => CanvasObjectData::from_bin(&data).map_or(E::Unknown(data), E::Canvas),
 			"Mouse & Keyboard" => E::Unknown(data),
 			"Custom Movement" => E::Unknown(data),
 			"Clipboard" => E::Unknown(data),
@@ -50,15 +54,15 @@ impl ObjectData {
 			"Window" => E::Unknown(data),
 			"Input System" => E::Unknown(data),
 			"Sprite" => E::Sprite(SpriteObjectData::from_bin(&data)),
-			"Tiled Background" => E::Unknown(data),
+			"Tiled Background" => TiledBackgroundObjectData::from_bin(&data).map_or(E::Unknown(data), E::TiledBackground),
 			"Image manipulator" => E::Unknown(data),
 			"Platform" => E::Unknown(data),
 			"Common Dialog" => E::Unknown(data),
 			"Sine" => E::Unknown(data),
 			"INI" => E::Unknown(data),
-			"Gradient" => E::Unknown(data),
-			"Panel" => E::Unknown(data),
-			"Text" => E::Text(TextObjectData::from_bin(&data)),
+			"Gradient" => GradientObjectData::from_bin(&data).map_or(E::Unknown(data), E::Gradient),
+			"Panel" => PanelObjectData::from_bin(&data).map_or(E::Unknown(data), E::Panel),
+			"Text" => TextObjectData::from_bin(&data).map_or(E::Unknown(data), E::Text),
 			"HTTP" => E::Unknown(data),
 			"Array" => E::Unknown(data),
 			"CRC32" => E::Unknown(data),
@@ -73,9 +77,16 @@ impl ObjectData {
 		match self {
 			ObjectData::Text(data) => data.to_bin(),
 			ObjectData::Sprite(data) => data.to_bin(),
+			ObjectData::TiledBackground(data) => data.to_bin(),
+			ObjectData::Panel(data) => data.to_bin(),
+			ObjectData::Gradient(data) => data.to_bin(),
+			ObjectData::Canvas(data) => data.to_bin(),
 			ObjectData::Unknown(data) => data,
 		}
 	}
+	/// Data for a new instance of a plugin.
+	///
+	/// Plugins whose data isn't decoded, or only partially decoded (see [`ObjectData::has_default`]), get empty `Unknown` data.
 	pub fn new(plugin_name: &str) -> Self {
 		use ObjectData as E;
 		match plugin_name {
@@ -107,6 +118,14 @@ impl ObjectData {
 			_ => E::Unknown(Default::default()),
 		}
 	}
+	/// Whether [`ObjectData::new`] returns data the runtime can load.
+	///
+	/// Only the leading fields of Tiled Background, Panel, Gradient and Canvas data are decoded, and the rest of their layout
+	/// hasn't been checked against data from a real game, so there is no known default for it.
+	/// New instances of those have to copy the data of an existing instance.
+	pub fn has_default(plugin_name: &str) -> bool {
+		!matches!(plugin_name, "Tiled Background" | "Panel" | "Gradient" | "Canvas")
+	}
 }
 
 
@@ -123,13 +142,24 @@ pub enum ExpStore {
 }
 
 
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, PartialEq)]
 pub struct Color {
 	pub a: f32,
 	pub r: f32,
 	pub g: f32,
 	pub b: f32,
 }
+impl Color {
+	/// Win32 COLORREF (0x00BBGGRR), ignoring alpha
+	pub fn to_colorref(&self) -> u32 {
+		let [r, g, b] = [self.r, self.g, self.b].map(|c| (c.clamp(0.0, 1.0) * 255.0).round() as u32);
+		r | (g << 8) | (b << 16)
+	}
+	pub fn from_colorref(color: u32, a: f32) -> Self {
+		let [r, g, b, _] = color.to_le_bytes().map(|c| c as f32 / 255.0);
+		Color { a, r, g, b }
+	}
+}
 
 #[serde_alias(SnakeCase)]
 #[derive(Derivative, Debug, Clone, Serialize, Deserialize)]
@@ -152,99 +182,181 @@ pub struct TextObjectData {
 	pub horiz_align: i32,
 	pub vert_align: i32,
 	pub hide_at_start: bool,
+	/// Parts of version 1 data that have no equivalent in version 2
+	#[serde(default)]
+	pub legacy: Option<TextObjectLegacyData>,
 }
 impl TextObjectData {
-	pub fn from_bin(bin: &[u8]) -> Self {
+	/// Returns `None` if the data is of an unknown version, or truncated or corrupt
+	pub fn from_bin(bin: &[u8]) -> Option<Self> {
+		let version = i32::from_le_bytes(bin.get(0..4)?.try_into().unwrap());
 		let mut bin = BlockReader::new(bin);
-		bin.read_text_obj()
+		match version {
+			1 => bin.read_text_obj_v1(),
+			2 => bin.read_text_obj(),
+			_ => None,
+		}
 	}
 	pub fn to_bin(&self) -> Vec<u8> {
 		let mut bin = BlockWriter::new();
-		bin.write_text_obj(self);
+		match &self.legacy {
+			Some(legacy) if self.version == 1 => bin.write_text_obj_v1(self, legacy),
+			_ => bin.write_text_obj(self),
+		}
 		bin.buffer
 	}
+	/// Convert version 1 data to version 2, which is written from then on.
+	///
+	/// Version 2 has no place for the [`TextObjectLegacyData`], so it is discarded:
+	/// the embedded object header and private variables are superseded by the instance's own position, size, filter and private variables,
+	/// and of the `DrawText` flags only the alignment (`horiz_align`, `vert_align`) is kept.
+	pub fn upgrade(&mut self) {
+		if self.version != 1 { return }
+		self.version = 2;
+		self.legacy = None;
+	}
+}
+
+/// Version 1 Text data embeds a full object header and private variables, which are kept as-is
+#[serde_alias(SnakeCase)]
+#[derive(Default, Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TextObjectLegacyData {
+	/// Serialized `ObjHeader`
+	pub header: Vec<u8>,
+	/// Win32 `DrawText` flags. The alignment bits are taken from `horiz_align` and `vert_align` when writing.
+	pub flags: u32,
+	/// Serialized private variable values
+	pub private_variables: Vec<u8>,
+	pub trailing: Vec<u8>,
 }
 
+// Text data is read with the checked readers, so truncated or corrupt data is left undecoded rather than crashing
 impl BlockReader<'_> {
-	pub fn read_obj_header(&mut self) -> ObjHeader {
-		let x = self.read_f32();
-		let y = self.read_f32();
-		let w = self.read_f32();
-		let h = self.read_f32();
-		let angle = self.read_f32();
-		let uid = self.read_i32();
-		let filter_deprecated = self.read_u32();
-		let global = self.read_u8() == 1;
-		let visible = self.read_i32();
-		let display_angle = self.read_f32();
-		let is_mirrored = self.read_u8() == 1;
-		let is_flipped = self.read_u8() == 1;
-		let filter = self.read_color();
-		ObjHeader { x, y, w, h, angle, uid, filter_deprecated, global, visible, display_angle, is_mirrored, is_flipped, filter }
-	}
-
-	pub fn read_exp_store(&mut self) -> ExpStore {
-		match self.read_i32() {
+	pub fn read_obj_header(&mut self) -> Option<ObjHeader> {
+		let x = self.try_read_f32()?;
+		let y = self.try_read_f32()?;
+		let w = self.try_read_f32()?;
+		let h = self.try_read_f32()?;
+		let angle = self.try_read_f32()?;
+		let uid = self.try_read_i32()?;
+		let filter_deprecated = self.try_read_u32()?;
+		let global = self.try_read_u8()? == 1;
+		let visible = self.try_read_i32()?;
+		let display_angle = self.try_read_f32()?;
+		let is_mirrored = self.try_read_u8()? == 1;
+		let is_flipped = self.try_read_u8()? == 1;
+		let filter = self.read_color()?;
+		Some(ObjHeader { x, y, w, h, angle, uid, filter_deprecated, global, visible, display_angle, is_mirrored, is_flipped, filter })
+	}
+
+	pub fn read_exp_store(&mut self) -> Option<ExpStore> {
+		Some(match self.try_read_i32()? {
 			EXPTYPE_INTEGER => {
-				ExpStore::Integer(self.read_i64())
+				ExpStore::Integer(self.try_read_i64()?)
 			},
 			EXPTYPE_FLOAT => {
-				ExpStore::Float(self.read_f64())
+				ExpStore::Float(self.try_read_f64()?)
 			},
 			EXPTYPE_STRING => {
-				ExpStore::String(self.read_string())
+				ExpStore::String(self.try_read_string()?)
 			},
 			EXPTYPE_ARRAY => {
-				ExpStore::Array(self.read_collection(|this| {
-					this.read_exp_store()
-				}))
+				ExpStore::Array(self.read_exp_stores()?)
 			},
-			_ => panic!(),
-		}
+			_ => return None,
+		})
 	}
 
-	pub fn read_color(&mut self) -> Color {
-		let a = self.read_f32();
-		let r = self.read_f32();
-		let g = self.read_f32();
-		let b = self.read_f32();
-		Color { a, r, g, b }
+	/// A collection of [`ExpStore`]s, e.g. private variable values
+	pub fn read_exp_stores(&mut self) -> Option<Vec<ExpStore>> {
+		let count = self.try_read_u32()?;
+		// the count isn't trusted for preallocating, since the data may be corrupt
+		(0..count).map(|_| self.read_exp_store()).collect()
 	}
 
-	pub fn read_text_obj(&mut self) -> TextObjectData {
-		let version = self.read_i32();
-		assert_eq!(version, 2);
-		let text = self.read_string();
-		let font_face = self.read_string();
-		let px_size = self.read_i32();
-		let italics = self.read_i32();
-		let bold = self.read_i32();
-		let color = self.read_u32();
-		let opacity = self.read_f32();
-		let horiz_align = self.read_i32();
-		let vert_align = self.read_i32();
-		let hide_at_start = self.read_u8() == 1;
-		TextObjectData { version, text, font_face, px_size, italics, bold, color, opacity, horiz_align, vert_align, hide_at_start }
-	}
-
-	pub fn read_text_obj_v1(&mut self) -> TextObjectData {
-		let version = self.read_i32();
-		assert_eq!(version, 1);
-		let _info = self.read_obj_header();
-		let _text = self.read_string();
-		let _font_face = self.read_string();
-		let _px_size = self.read_i32();
-		let _bold = self.read_i32();
-		let _italics = self.read_i32();
-		let _dw_flags = self.read_u32();
-		let _private_vars = self.read_collection(|this| {
-			this.read_exp_store()
-		});
-		todo!();
+	pub fn read_color(&mut self) -> Option<Color> {
+		let a = self.try_read_f32()?;
+		let r = self.try_read_f32()?;
+		let g = self.try_read_f32()?;
+		let b = self.try_read_f32()?;
+		Some(Color { a, r, g, b })
+	}
+
+	pub fn read_text_obj(&mut self) -> Option<TextObjectData> {
+		let version = self.try_read_i32()?;
+		if version != 2 { return None }
+		let text = self.try_read_string()?;
+		let font_face = self.try_read_string()?;
+		let px_size = self.try_read_i32()?;
+		let italics = self.try_read_i32()?;
+		let bold = self.try_read_i32()?;
+		let color = self.try_read_u32()?;
+		let opacity = self.try_read_f32()?;
+		let horiz_align = self.try_read_i32()?;
+		let vert_align = self.try_read_i32()?;
+		let hide_at_start = self.try_read_u8()? == 1;
+		Some(TextObjectData { version, text, font_face, px_size, italics, bold, color, opacity, horiz_align, vert_align, hide_at_start, legacy: None })
+	}
+
+	pub fn read_text_obj_v1(&mut self) -> Option<TextObjectData> {
+		let version = self.try_read_i32()?;
+		if version != 1 { return None }
+		let header_start = self.pos;
+		let info = self.read_obj_header()?;
+		let header = self.buffer[header_start..self.pos].to_owned();
+		let text = self.try_read_string()?;
+		let font_face = self.try_read_string()?;
+		let px_size = self.try_read_i32()?;
+		let bold = self.try_read_i32()?;
+		let italics = self.try_read_i32()?;
+		let flags = self.try_read_u32()?;
+		let private_variables_start = self.pos;
+		let _private_vars = self.read_exp_stores()?;
+		let private_variables = self.buffer[private_variables_start..self.pos].to_owned();
+		let trailing = self.read_remaining();
+		Some(TextObjectData {
+			version,
+			text,
+			font_face,
+			px_size,
+			italics,
+			bold,
+			// version 1 has no text color, the object's filter is used instead
+			color: info.filter.to_colorref(),
+			opacity: info.filter.a,
+			horiz_align: (flags & 0b11) as i32,
+			vert_align: ((flags >> 2) & 0b11) as i32,
+			hide_at_start: info.visible == 0,
+			legacy: Some(TextObjectLegacyData { header, flags, private_variables, trailing }),
+		})
 	}
 }
 
 impl BlockWriter {
+	pub fn write_obj_header(&mut self, info: &ObjHeader) {
+		self.write_f32(info.x);
+		self.write_f32(info.y);
+		self.write_f32(info.w);
+		self.write_f32(info.h);
+		self.write_f32(info.angle);
+		self.write_i32(info.uid);
+		self.write_u32(info.filter_deprecated);
+		self.write_u8(info.global as u8);
+		self.write_i32(info.visible);
+		self.write_f32(info.display_angle);
+		self.write_u8(info.is_mirrored as u8);
+		self.write_u8(info.is_flipped as u8);
+		self.write_color(&info.filter);
+	}
+
+	pub fn write_color(&mut self, color: &Color) {
+		self.write_f32(color.a);
+		self.write_f32(color.r);
+		self.write_f32(color.g);
+		self.write_f32(color.b);
+	}
+
 	pub fn write_text_obj(&mut self, text: &TextObjectData) {
 		self.write_i32(text.version);
 		self.write_string(&text.text);
@@ -258,6 +370,40 @@ impl BlockWriter {
 		self.write_i32(text.vert_align);
 		self.write_u8(text.hide_at_start as u8)
 	}
+
+	pub fn write_text_obj_v1(&mut self, text: &TextObjectData, legacy: &TextObjectLegacyData) {
+		self.write_i32(1);
+		// Only re-write the parts of the header that were edited, so untouched data round-trips exactly.
+		// A header that can't be read (e.g. edited through JSON) can only be written back as-is.
+		let Some(mut info) = BlockReader::new(&legacy.header).read_obj_header() else {
+			self.write_bytes(&legacy.header);
+			return self.write_text_obj_v1_body(text, legacy);
+		};
+		let original = info.clone();
+		if (info.visible == 0) != text.hide_at_start {
+			info.visible = (!text.hide_at_start) as i32;
+		}
+		if info.filter.to_colorref() != text.color || info.filter.a != text.opacity {
+			info.filter = Color::from_colorref(text.color, text.opacity);
+		}
+		if info.visible == original.visible && info.filter == original.filter {
+			self.write_bytes(&legacy.header);
+		} else {
+			self.write_obj_header(&info);
+		}
+		self.write_text_obj_v1_body(text, legacy);
+	}
+
+	fn write_text_obj_v1_body(&mut self, text: &TextObjectData, legacy: &TextObjectLegacyData) {
+		self.write_string(&text.text);
+		self.write_string(&text.font_face);
+		self.write_i32(text.px_size);
+		self.write_i32(text.bold);
+		self.write_i32(text.italics);
+		self.write_u32((legacy.flags & !0b1111) | (text.horiz_align as u32 & 0b11) | ((text.vert_align as u32 & 0b11) << 2));
+		self.write_bytes(&legacy.private_variables);
+		self.write_bytes(&legacy.trailing);
+	}
 }
 
 #[serde_alias(SnakeCase)]
@@ -336,3 +482,136 @@ impl BlockWriter {
 		self.write_i32(obj.start_frame);
 	}
 }
+
+// The following plugins only have their leading fields decoded.
+// Anything after those is kept in `trailing` and written back verbatim, so decoding is always lossless.
+// Field names are kept distinct between these structs so the untagged `ObjectData` deserializes to the right variant.
+
+#[serde_alias(SnakeCase)]
+#[derive(Derivative, Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[derivative(Default)]
+pub struct TiledBackgroundObjectData {
+	#[derivative(Default(value = "1"))]
+	pub version: i32,
+	/// An ID in the image block
+	pub texture_id: i32,
+	pub trailing: Vec<u8>,
+}
+impl TiledBackgroundObjectData {
+	/// Returns `None` if the data is too short to contain the known fields
+	pub fn from_bin(bin: &[u8]) -> Option<Self> {
+		if bin.len() < 8 { return None }
+		let mut bin = BlockReader::new(bin);
+		let version = bin.read_i32();
+		let texture_id = bin.read_i32();
+		let trailing = bin.read_remaining();
+		Some(TiledBackgroundObjectData { version, texture_id, trailing })
+	}
+	pub fn to_bin(&self) -> Vec<u8> {
+		let mut bin = BlockWriter::new();
+		bin.write_i32(self.version);
+		bin.write_i32(self.texture_id);
+		bin.write_bytes(&self.trailing);
+		bin.buffer
+	}
+}
+
+#[serde_alias(SnakeCase)]
+#[derive(Derivative, Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[derivative(Default)]
+pub struct PanelObjectData {
+	#[derivative(Default(value = "1"))]
+	pub version: i32,
+	/// Texture, an ID in the image block
+	pub image_id: i32,
+	pub trailing: Vec<u8>,
+}
+impl PanelObjectData {
+	/// Returns `None` if the data is too short to contain the known fields
+	pub fn from_bin(bin: &[u8]) -> Option<Self> {
+		if bin.len() < 8 { return None }
+		let mut bin = BlockReader::new(bin);
+		let version = bin.read_i32();
+		let image_id = bin.read_i32();
+		let trailing = bin.read_remaining();
+		Some(PanelObjectData { version, image_id, trailing })
+	}
+	pub fn to_bin(&self) -> Vec<u8> {
+		let mut bin = BlockWriter::new();
+		bin.write_i32(self.version);
+		bin.write_i32(self.image_id);
+		bin.write_bytes(&self.trailing);
+		bin.buffer
+	}
+}
+
+#[serde_alias(SnakeCase)]
+#[derive(Derivative, Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[derivative(Default)]
+pub struct GradientObjectData {
+	#[derivative(Default(value = "1"))]
+	pub version: i32,
+	/// COLORREF (0x00BBGGRR)
+	#[derivative(Default(value = "16777215"))]
+	pub color1: u32,
+	/// COLORREF (0x00BBGGRR)
+	pub color2: u32,
+	/// 0 = top to bottom, 1 = bottom to top, 2 = left to right, 3 = right to left
+	pub direction: i32,
+	pub trailing: Vec<u8>,
+}
+impl GradientObjectData {
+	/// Returns `None` if the data is too short to contain the known fields
+	pub fn from_bin(bin: &[u8]) -> Option<Self> {
+		if bin.len() < 16 { return None }
+		let mut bin = BlockReader::new(bin);
+		let version = bin.read_i32();
+		let color1 = bin.read_u32();
+		let color2 = bin.read_u32();
+		let direction = bin.read_i32();
+		let trailing = bin.read_remaining();
+		Some(GradientObjectData { version, color1, color2, direction, trailing })
+	}
+	pub fn to_bin(&self) -> Vec<u8> {
+		let mut bin = BlockWriter::new();
+		bin.write_i32(self.version);
+		bin.write_u32(self.color1);
+		bin.write_u32(self.color2);
+		bin.write_i32(self.direction);
+		bin.write_bytes(&self.trailing);
+		bin.buffer
+	}
+}
+
+#[serde_alias(SnakeCase)]
+#[derive(Derivative, Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[derivative(Default)]
+pub struct CanvasObjectData {
+	#[derivative(Default(value = "1"))]
+	pub version: i32,
+	/// Initial image, an ID in the image block
+	pub initial_image_id: i32,
+	pub trailing: Vec<u8>,
+}
+impl CanvasObjectData {
+	/// Returns `None` if the data is too short to contain the known fields
+	pub fn from_bin(bin: &[u8]) -> Option<Self> {
+		if bin.len() < 8 { return None }
+		let mut bin = BlockReader::new(bin);
+		let version = bin.read_i32();
+		let initial_image_id = bin.read_i32();
+		let trailing = bin.read_remaining();
+		Some(CanvasObjectData { version, initial_image_id, trailing })
+	}
+	pub fn to_bin(&self) -> Vec<u8> {
+		let mut bin = BlockWriter::new();
+		bin.write_i32(self.version);
+		bin.write_i32(self.initial_image_id);
+		bin.write_bytes(&self.trailing);
+		bin.buffer
+	}
+}
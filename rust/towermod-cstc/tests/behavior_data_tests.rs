@@ -0,0 +1,51 @@
+use towermod_cstc::{BehaviorData, PlatformBehaviorData};
+
+const PLUGINS: [&str; 3] = ["Platform", "Sine", "Custom Movement"];
+
+fn f32s(values: &[f32]) -> Vec<u8> {
+	values.iter().flat_map(|v| v.to_le_bytes()).collect()
+}
+
+#[test]
+fn behavior_data_preserves_trailing_bytes() {
+	// written out by hand rather than by the encoders, little-endian
+	let bins: [Vec<u8>; 3] = [
+		// version 1, max speed, acceleration, deceleration, jump strength, gravity, max fall speed
+		[&[1, 0, 0, 0][..], &f32s(&[330.0, 1500.0, 1500.0, 725.5, 1800.0, 1000.0]), &[1, 0, 0, 0, 0, 0, 200, 66]].concat(),
+		// version 1, vertical, sine wave, period, period random, magnitude, magnitude random
+		[&[1, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0][..], &f32s(&[2.5, 0.0, 50.0, 0.0]), &[7; 9]].concat(),
+		// version 1, circular stepping, 5 pixels per step
+		vec![1, 0, 0, 0, 2, 0, 0, 0, 5, 0, 0, 0, 0, 1],
+	];
+	for (plugin_name, bin) in PLUGINS.into_iter().zip(bins.clone()) {
+		assert_eq!(BehaviorData::decode(bin.clone(), plugin_name).encode(), bin, "{plugin_name}");
+	}
+
+	let BehaviorData::Platform(decoded) = BehaviorData::decode(bins[0].clone(), "Platform") else { panic!() };
+	assert_eq!((decoded.max_speed, decoded.jump_strength, decoded.gravity), (330.0, 725.5, 1800.0));
+	assert_eq!(decoded.trailing, [1, 0, 0, 0, 0, 0, 200, 66]);
+	let BehaviorData::Sine(decoded) = BehaviorData::decode(bins[1].clone(), "Sine") else { panic!() };
+	assert_eq!((decoded.movement, decoded.wave, decoded.period, decoded.magnitude, decoded.trailing.len()), (1, 0, 2.5, 50.0, 9));
+	let BehaviorData::CustomMovement(decoded) = BehaviorData::decode(bins[2].clone(), "Custom Movement") else { panic!() };
+	assert_eq!((decoded.stepping_mode, decoded.pixels_per_step, decoded.trailing.as_slice()), (2, 5, &[0, 1][..]));
+}
+
+#[test]
+fn behavior_data_for_plugin() {
+	let platform = BehaviorData::Platform(PlatformBehaviorData::default());
+	assert!(platform.is_for_plugin("Platform"));
+	assert!(!platform.is_for_plugin("Sine"));
+	assert!(!platform.is_for_plugin("8 Direction"));
+	assert!(BehaviorData::Unknown(vec![]).is_for_plugin("8 Direction"));
+	assert!(!BehaviorData::Unknown(vec![]).is_for_plugin("Platform"));
+}
+
+#[test]
+fn unknown_behavior_data() {
+	let bin = vec![1, 2, 3];
+	for plugin_name in PLUGINS.into_iter().chain(["8 Direction"]) {
+		let decoded = BehaviorData::decode(bin.clone(), plugin_name);
+		assert!(matches!(decoded, BehaviorData::Unknown(_)), "{plugin_name}");
+		assert_eq!(decoded.encode(), bin);
+	}
+}
@@ -0,0 +1,161 @@
+use towermod_cstc::{CanvasObjectData, ObjectData};
+
+const PLUGINS: [&str; 4] = ["Tiled Background", "Panel", "Gradient", "Canvas"];
+
+#[test]
+fn new_object_data() {
+	// the trailing fields of these plugins have no known defaults
+	for plugin_name in PLUGINS {
+		assert!(!ObjectData::has_default(plugin_name), "{plugin_name}");
+		assert!(matches!(ObjectData::new(plugin_name), ObjectData::Unknown(data) if data.is_empty()), "{plugin_name}");
+	}
+	assert!(matches!(ObjectData::new("Sprite"), ObjectData::Sprite(_)));
+	assert!(matches!(ObjectData::new("Text"), ObjectData::Text(_)));
+	assert!(matches!(ObjectData::new("Array"), ObjectData::Unknown(_)));
+}
+
+#[test]
+fn object_data_preserves_trailing_bytes() {
+	// written out by hand rather than by the encoders, little-endian
+	let bins: [Vec<u8>; 4] = [
+		// version 1, texture 42
+		[&[1, 0, 0, 0, 42, 0, 0, 0][..], &[1, 2, 3, 4, 5]].concat(),
+		// version 2, image 7
+		[&[2, 0, 0, 0, 7, 0, 0, 0][..], &[0xff; 13]].concat(),
+		// version 1, colors 0x00112233 and 0x00445566, direction 2
+		vec![1, 0, 0, 0, 0x33, 0x22, 0x11, 0, 0x66, 0x55, 0x44, 0, 2, 0, 0, 0, 9],
+		// version 1, no initial image
+		vec![1, 0, 0, 0, 0xff, 0xff, 0xff, 0xff, 0, 0, 128, 63],
+	];
+	for (plugin_name, bin) in PLUGINS.into_iter().zip(bins.clone()) {
+		let decoded = ObjectData::decode(bin.clone(), plugin_name);
+		assert_eq!(decoded.encode(), bin, "{plugin_name}");
+	}
+
+	let ObjectData::TiledBackground(decoded) = ObjectData::decode(bins[0].clone(), "Tiled Background") else { panic!() };
+	assert_eq!((decoded.version, decoded.texture_id, decoded.trailing.as_slice()), (1, 42, &[1, 2, 3, 4, 5][..]));
+	let ObjectData::Panel(decoded) = ObjectData::decode(bins[1].clone(), "Panel") else { panic!() };
+	assert_eq!((decoded.version, decoded.image_id, decoded.trailing.len()), (2, 7, 13));
+	let ObjectData::Gradient(decoded) = ObjectData::decode(bins[2].clone(), "Gradient") else { panic!() };
+	assert_eq!((decoded.color1, decoded.color2, decoded.direction), (0x00112233, 0x00445566, 2));
+	let ObjectData::Canvas(decoded) = ObjectData::decode(bins[3].clone(), "Canvas") else { panic!() };
+	assert_eq!(decoded.initial_image_id, -1);
+	assert_eq!(CanvasObjectData { trailing: vec![], ..decoded }.to_bin(), [1, 0, 0, 0, 0xff, 0xff, 0xff, 0xff]);
+}
+
+#[test]
+fn truncated_object_data_is_unknown() {
+	for plugin_name in PLUGINS {
+		let bin = vec![1, 0, 0, 0];
+		let decoded = ObjectData::decode(bin.clone(), plugin_name);
+		assert!(matches!(decoded, ObjectData::Unknown(_)), "{plugin_name}");
+		assert_eq!(decoded.encode(), bin);
+	}
+}
+
+#[test]
+fn object_data_json_variants() {
+	let variants = [
+		ObjectData::TiledBackground(Default::default()),
+		ObjectData::Panel(Default::default()),
+		ObjectData::Gradient(Default::default()),
+		ObjectData::Canvas(Default::default()),
+	];
+	for (plugin_name, data) in PLUGINS.into_iter().zip(variants) {
+		let json = serde_json::to_string(&data).unwrap();
+		let deserialized: ObjectData = serde_json::from_str(&json).unwrap();
+		assert_eq!(std::mem::discriminant(&deserialized), std::mem::discriminant(&data), "{plugin_name}");
+	}
+}
+
+fn v1_text_object(visible: i32, flags: u32) -> Vec<u8> {
+	let mut bin = Vec::new();
+	let string = |bin: &mut Vec<u8>, s: &str| {
+		bin.extend((s.len() as u32 + 1).to_le_bytes());
+		bin.extend(s.as_bytes());
+		bin.push(0);
+	};
+	bin.extend(1i32.to_le_bytes());
+	// object header
+	for f in [32.0f32, 48.0, 200.0, 30.0, 0.0] { bin.extend(f.to_le_bytes()) }
+	bin.extend(7i32.to_le_bytes());
+	bin.extend(0xffffffffu32.to_le_bytes());
+	bin.push(0);
+	bin.extend(visible.to_le_bytes());
+	bin.extend(0f32.to_le_bytes());
+	bin.extend([0, 0]);
+	for f in [1.0f32, 1.0, 1.0, 0.0] { bin.extend(f.to_le_bytes()) }
+
+	string(&mut bin, "Hello");
+	string(&mut bin, "Tahoma");
+	bin.extend(12i32.to_le_bytes());
+	bin.extend(1i32.to_le_bytes());
+	bin.extend(0i32.to_le_bytes());
+	bin.extend(flags.to_le_bytes());
+	// private variables: one integer and one string
+	bin.extend(2u32.to_le_bytes());
+	bin.extend(0i32.to_le_bytes());
+	bin.extend(5i64.to_le_bytes());
+	bin.extend(2i32.to_le_bytes());
+	string(&mut bin, "x");
+	bin
+}
+
+#[test]
+fn v1_text_object_data() {
+	// DT_CENTER | DT_VCENTER | DT_WORDBREAK
+	let bin = v1_text_object(1, 0x1 | 0x4 | 0x10);
+	let ObjectData::Text(text) = ObjectData::decode(bin.clone(), "Text") else { panic!() };
+	assert_eq!((text.version, text.text.as_str(), text.font_face.as_str(), text.px_size, text.bold), (1, "Hello", "Tahoma", 12, 1));
+	assert_eq!((text.horiz_align, text.vert_align, text.hide_at_start), (1, 1, false));
+	assert_eq!(text.color, 0x0000ffff);
+	assert_eq!(text.to_bin(), bin);
+
+	// edits are written back in the version 1 format
+	let mut edited = text.clone();
+	edited.text = "Bye".to_string();
+	edited.horiz_align = 2;
+	edited.hide_at_start = true;
+	let ObjectData::Text(decoded) = ObjectData::decode(edited.to_bin(), "Text") else { panic!() };
+	assert_eq!((decoded.version, decoded.text.as_str(), decoded.horiz_align, decoded.vert_align, decoded.hide_at_start), (1, "Bye", 2, 1, true));
+	assert_eq!(decoded.to_bin(), edited.to_bin());
+	assert_eq!(decoded.legacy.unwrap().flags, 0x2 | 0x4 | 0x10);
+
+}
+
+#[test]
+fn upgrade_v1_text_object_data() {
+	// DT_RIGHT | DT_BOTTOM | DT_WORDBREAK, hidden at start
+	let ObjectData::Text(text) = ObjectData::decode(v1_text_object(0, 0x2 | 0x8 | 0x10), "Text") else { panic!() };
+	let mut upgraded = text.clone();
+	upgraded.upgrade();
+	let ObjectData::Text(decoded) = ObjectData::decode(upgraded.to_bin(), "Text") else { panic!() };
+	assert_eq!((decoded.version, decoded.text.as_str(), decoded.font_face.as_str(), decoded.px_size, decoded.bold), (2, "Hello", "Tahoma", 12, 1));
+	assert_eq!((decoded.color, decoded.opacity, decoded.horiz_align, decoded.vert_align, decoded.hide_at_start), (0x0000ffff, 1.0, 2, 2, true));
+	// the embedded header, private variables and remaining DrawText flags are dropped
+	assert!(decoded.legacy.is_none());
+	assert_eq!(decoded.to_bin(), upgraded.to_bin());
+
+	// version 2 data is left alone
+	let mut again = decoded.clone();
+	again.upgrade();
+	assert_eq!(again.to_bin(), decoded.to_bin());
+}
+
+#[test]
+fn truncated_text_object_data_is_unknown() {
+	let v1 = v1_text_object(1, 0);
+	let mut corrupt_string = v1.clone();
+	// length of the text, past the end of the data
+	corrupt_string[59..63].copy_from_slice(&1000u32.to_le_bytes());
+	let mut corrupt_variable = v1.clone();
+	// type of the string private variable
+	let len = corrupt_variable.len();
+	corrupt_variable[len - 10..len - 6].copy_from_slice(&9i32.to_le_bytes());
+	let v2 = towermod_cstc::TextObjectData::default().to_bin();
+	for bin in [vec![], vec![2, 0], vec![3, 0, 0, 0], v1[..40].to_vec(), v1[..v1.len() - 1].to_vec(), corrupt_string, corrupt_variable, v2[..v2.len() - 1].to_vec()] {
+		let decoded = ObjectData::decode(bin.clone(), "Text");
+		assert!(matches!(decoded, ObjectData::Unknown(_)), "{bin:?}");
+		assert_eq!(decoded.encode(), bin);
+	}
+}
@@ -0,0 +1,77 @@
+//! Writing the data blocks to a game executable, one resource update per block vs. a single [`PeResourceSet`].
+//!
+//! Needs a large Construct Classic executable (e.g. TowerClimb):
+//! `TOWERMOD_GAME_PATH=path/to/game.exe cargo bench -p towermod-shared`
+//!
+//! The executable's size is printed along with the results, report both when comparing numbers.
+#![feature(test)]
+extern crate test;
+
+use std::path::{Path, PathBuf};
+use test::Bencher;
+use towermod_shared::{PeResource, PeResourceSet};
+use towermod_shared::towermod_cstc as cstc;
+
+struct Blocks {
+	image_block: Vec<u8>,
+	app_block: Vec<u8>,
+	event_block: Vec<u8>,
+	level_block: Vec<u8>,
+}
+
+fn exe_path() -> Option<PathBuf> {
+	let path = std::env::var_os("TOWERMOD_GAME_PATH").map(PathBuf::from);
+	match &path {
+		Some(path) => eprintln!("{}: {} bytes", path.display(), std::fs::metadata(path).unwrap().len()),
+		None => eprintln!("TOWERMOD_GAME_PATH not set, skipping"),
+	}
+	path
+}
+
+fn read_blocks(path: &Path) -> Blocks {
+	Blocks {
+		image_block: cstc::ImageBlock::read_bin(path).unwrap(),
+		app_block: cstc::AppBlock::read_bin(path).unwrap(),
+		event_block: cstc::EventBlock::read_bin(path).unwrap(),
+		level_block: cstc::LevelBlock::read_bin(path).unwrap(),
+	}
+}
+
+#[bench]
+fn write_blocks_separately(b: &mut Bencher) {
+	let Some(path) = exe_path() else { return };
+	let blocks = read_blocks(&path);
+	let temp = tempfile::NamedTempFile::new().unwrap();
+	b.iter(|| {
+		std::fs::copy(&path, temp.path()).unwrap();
+		cstc::ImageBlock::write_bin(temp.path(), &blocks.image_block).unwrap();
+		cstc::AppBlock::write_bin(temp.path(), &blocks.app_block).unwrap();
+		cstc::EventBlock::write_bin(temp.path(), &blocks.event_block).unwrap();
+		cstc::LevelBlock::write_bin(temp.path(), &blocks.level_block).unwrap();
+	});
+}
+
+#[bench]
+fn write_blocks_in_one_pass(b: &mut Bencher) {
+	let Some(path) = exe_path() else { return };
+	let blocks = read_blocks(&path);
+	let temp = tempfile::NamedTempFile::new().unwrap();
+	b.iter(|| {
+		std::fs::copy(&path, temp.path()).unwrap();
+		let mut resources = PeResourceSet::new();
+		resources
+			.stage_block_bin::<cstc::ImageBlock>(blocks.image_block.clone())
+			.stage_block_bin::<cstc::AppBlock>(blocks.app_block.clone())
+			.stage_block_bin::<cstc::EventBlock>(blocks.event_block.clone())
+			.stage_block_bin::<cstc::LevelBlock>(blocks.level_block.clone());
+		resources.commit(temp.path()).unwrap();
+	});
+}
+
+#[bench]
+fn copy_executable(b: &mut Bencher) {
+	// baseline for the copy included in the other benchmarks
+	let Some(path) = exe_path() else { return };
+	let temp = tempfile::NamedTempFile::new().unwrap();
+	b.iter(|| std::fs::copy(&path, temp.path()).unwrap());
+}
@@ -3,7 +3,7 @@
 use std::{collections::HashMap};
 use crate::{app::state::{app_state::State, select}, cstc_editing::{EdContainer, EdFamily, EdLayout, EdLayoutLayer, EdObjectInstance, EdObjectType}, select, serde};
 use ::serde::{Deserialize, Serialize};
-use towermod_cstc::{plugin::PluginData, Animation, Behavior, Container, Family, ImageMetadata, ObjectData, ObjectTrait, ObjectType};
+use towermod_cstc::{plugin::PluginData, Animation, Behavior, BehaviorData, Container, Family, ImageMetadata, ObjectData, ObjectTrait, ObjectType};
 
 
 pub fn select_editor_plugin(plugin_id: i32) -> impl Fn(&State) -> Option<&PluginData> {
@@ -116,6 +116,16 @@ pub fn select_object_type_instance_ids(object_type_id: i32) -> impl Fn(&State) -
 		object_instances.into_iter().filter_map(|o| if o.object_type_id == object_type_id { Some(o.id) } else { None }).collect()
 	}
 }
+pub fn select_object_instances_in_rect(layout_layer_id: i32, x: f32, y: f32, width: f32, height: f32) -> impl Fn(&State) -> Vec<i32> {
+	move |s| s.spatial_index.query_rect(layout_layer_id, x, y, width, height)
+}
+/// Topmost first
+pub fn select_object_instances_at_point(layout_layer_id: i32, x: f32, y: f32) -> impl Fn(&State) -> Vec<i32> {
+	move |s| s.spatial_index.query_point(layout_layer_id, x, y)
+}
+pub fn select_nearest_object_instance(layout_layer_id: i32, x: f32, y: f32) -> impl Fn(&State) -> Option<i32> {
+	move |s| s.spatial_index.query_nearest(layout_layer_id, x, y)
+}
 pub fn select_object_type_first_instance(object_type_id: i32) -> impl Fn(&State) -> Option<&EdObjectInstance> {
 	move |s| { select_all_object_instances(s).find(|o| o.object_type_id == object_type_id) }
 }
@@ -231,6 +241,13 @@ pub fn select_layout_layers(layout_name: String) -> impl Fn(&State) -> Vec<i32>
 		return Vec::new();
 	}
 }
+pub fn select_new_layout_layer_id() -> impl Fn(&State) -> i32 {
+	|s| {
+		s.data.layouts.iter()
+			.flat_map(|l| l.layers.iter().map(|l| l.id))
+			.max().unwrap_or(0) + 1
+	}
+}
 pub fn select_layout_layer(layer_id: i32) -> impl Fn(&State) -> Option<&EdLayoutLayer> {
 	move |s| {
 		for layout in &s.data.layouts {
@@ -427,6 +444,12 @@ pub fn select_behavior(object_type_id: i32, mov_index: i32) -> impl Fn(&State) -
 pub fn select_behavior_mut(object_type_id: i32, mov_index: i32) -> impl Fn(&mut State) -> Option<&mut Behavior> {
 	move |s| s.data.behaviors.iter_mut().find(|b| b.object_type_id == object_type_id && b.mov_index == mov_index)
 }
+pub fn select_behavior_data(object_type_id: i32, mov_index: i32) -> impl Fn(&State) -> Option<BehaviorData> {
+	move |s| {
+		let behavior = select_behavior(object_type_id, mov_index)(s)?;
+		Some(s.data.decode_behavior_data(behavior))
+	}
+}
 
 pub fn select_containers() -> impl Fn(&State) -> Vec<i32> {
 	move |s| s.data.containers.iter().map(|(k, _)| *k).collect()
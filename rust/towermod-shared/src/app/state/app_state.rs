@@ -1,7 +1,7 @@
 use lazy_static::lazy_static;
 use serde::{Deserialize, Serialize};
 use redux_rs::{Selector, Store};
-use crate::{Game, Project};
+use crate::{cstc_editing::SpatialIndex, Game, Project};
 use super::data_state as data;
 use super::config_state as config;
 
@@ -19,12 +19,22 @@ pub struct State {
 	pub data: data::State,
 	pub project: Option<Project>,
 	pub game: Option<Game>,
+	/// Derived from `data`, kept up to date by the reducer
+	#[serde(skip)]
+	pub spatial_index: SpatialIndex,
 }
 
 pub fn reducer(mut state: State, action: Action) -> State {
 	match action {
 		Action::Data(action) => {
+			let scope = action.spatial_index_scope();
 			state = data::reducer(state, action);
+			match scope {
+				data::SpatialIndexScope::None => {},
+				data::SpatialIndexScope::Instances(ids) => state.spatial_index.update_instances(&state.data, &ids),
+				data::SpatialIndexScope::Layers(ids) => state.spatial_index.update_layers(&state.data, &ids),
+				data::SpatialIndexScope::All => state.spatial_index.rebuild(&state.data),
+			}
 		}
 		Action::Config(action) => {
 			state.config = config::reducer(state.config, action);
@@ -35,6 +45,7 @@ pub fn reducer(mut state: State, action: Action) -> State {
 		Action::SetProject(None) => {
 			state.project = None;
 			state.data = data::State::default();
+			state.spatial_index = SpatialIndex::default();
 		}
 		Action::EditProjectInfo(project) => {
 			state.project = Some(project);
@@ -43,6 +54,7 @@ pub fn reducer(mut state: State, action: Action) -> State {
 			state.game = game;
 			state.project = None;
 			state.data = data::State::default();
+			state.spatial_index = SpatialIndex::default();
 		}
 	}
 	state
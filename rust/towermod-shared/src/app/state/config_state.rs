@@ -1,11 +1,152 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
+use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
+use crate::{GameType, LauncherConfig, PathOverrides};
 
-#[derive(Default, Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct TowermodConfig {
+	/// Format version of the config file, see [`TowermodConfig::from_toml`]
+	#[serde(default)]
+	pub version: u32,
+	/// Path of the currently selected game
 	pub game_path: Option<PathBuf>,
+	/// Overrides the directory where cached data is stored
+	#[serde(default)]
+	pub cache_dir: Option<PathBuf>,
+	/// Every game that has been selected, in the order they were added
+	#[serde(default)]
+	pub profiles: Vec<GameProfile>,
+}
+
+/// Settings for one game executable
+#[derive(Default, Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GameProfile {
+	pub path: PathBuf,
+	/// Shown when switching games, defaults to the file name
+	#[serde(default)]
+	pub name: String,
+	#[serde(default)]
+	pub game_type: GameType,
+	/// `Game.data_hash` of the executable when it was last selected
+	#[serde(default)]
+	pub data_hash: Option<String>,
+	/// Overrides the AppData directory where the game stores its savedata and settings
+	#[serde(default)]
+	pub appdata_dir: Option<PathBuf>,
+	#[serde(default)]
+	pub launcher: LauncherConfig,
+	/// Manifests of the projects opened for this game, most recent first
+	#[serde(default)]
+	pub recent_projects: Vec<PathBuf>,
+}
+
+impl GameProfile {
+	pub const MAX_RECENT_PROJECTS: usize = 10;
+
+	pub fn new(path: PathBuf) -> Self {
+		let name = path.file_stem().map(|s| s.to_string_lossy().into_owned()).unwrap_or_default();
+		Self { path, name, ..Default::default() }
+	}
+
+	/// Move a project to the front of the recent projects
+	pub fn add_recent_project(&mut self, manifest_path: PathBuf) {
+		self.recent_projects.retain(|p| *p != manifest_path);
+		self.recent_projects.insert(0, manifest_path);
+		self.recent_projects.truncate(Self::MAX_RECENT_PROJECTS);
+	}
+}
+
+impl Default for TowermodConfig {
+	fn default() -> Self {
+		Self { version: Self::VERSION, game_path: None, cache_dir: None, profiles: Vec::new() }
+	}
+}
+
+impl TowermodConfig {
+	pub const VERSION: u32 = 2;
+
+	/// Parse a config file written by any version of towermod.
+	/// Returns whether the config had to be migrated from an older version.
+	pub fn from_toml(s: &str) -> Result<(Self, bool)> {
+		let mut table: toml::Table = toml::from_str(s).context("Failed to parse config")?;
+		let version = match table.get("version") {
+			Some(version) => version.as_integer().context("Invalid config version")? as u32,
+			None => 0,
+		};
+		if version > Self::VERSION {
+			anyhow::bail!("Config version {version} is newer than this version of towermod supports ({})", Self::VERSION);
+		}
+		for version in version..Self::VERSION {
+			MIGRATIONS[version as usize](&mut table);
+		}
+		table.insert("version".to_string(), toml::Value::Integer(Self::VERSION as i64));
+		let config = toml::Value::Table(table).try_into().context("Failed to parse config")?;
+		Ok((config, version != Self::VERSION))
+	}
+
+	pub fn profile(&self, game_path: &Path) -> Option<&GameProfile> {
+		self.profiles.iter().find(|p| p.path == game_path)
+	}
+
+	/// Profile of a game, added if it does not exist yet
+	pub fn profile_mut(&mut self, game_path: &Path) -> &mut GameProfile {
+		match self.profiles.iter().position(|p| p.path == game_path) {
+			Some(i) => &mut self.profiles[i],
+			None => {
+				self.profiles.push(GameProfile::new(game_path.to_path_buf()));
+				self.profiles.last_mut().unwrap()
+			},
+		}
+	}
+
+	/// Profile of the currently selected game
+	pub fn current_profile(&self) -> Option<&GameProfile> {
+		self.profile(self.game_path.as_deref()?)
+	}
+
+	pub fn path_overrides(&self) -> PathOverrides {
+		PathOverrides {
+			cache_dir: self.cache_dir.clone(),
+			towerclimb_appdata_dir: self.current_profile().and_then(|p| p.appdata_dir.clone()),
+		}
+	}
+}
+
+/// Each migration upgrades a config from the version matching its index to the next
+const MIGRATIONS: [fn(&mut toml::Table); TowermodConfig::VERSION as usize] = [
+	migrate_v0,
+	migrate_v1,
+];
+
+/// v0 had one `launcher` for all games
+fn migrate_v0(table: &mut toml::Table) {
+	let launcher = table.remove("launcher");
+	let game_path = table.get("gamePath").and_then(|p| p.as_str()).map(|p| p.to_string());
+	if let (Some(launcher), Some(game_path)) = (launcher, game_path) {
+		let mut settings = toml::Table::new();
+		settings.insert("launcher".to_string(), launcher);
+		let mut games = toml::Table::new();
+		games.insert(game_path, toml::Value::Table(settings));
+		table.insert("games".to_string(), toml::Value::Table(games));
+	}
+}
+
+/// v1 kept the settings of each game in a `games` table keyed by path
+fn migrate_v1(table: &mut toml::Table) {
+	let Some(toml::Value::Table(games)) = table.remove("games") else { return };
+	let profiles = games.into_iter()
+		.filter_map(|(path, settings)| {
+			let toml::Value::Table(mut profile) = settings else { return None };
+			let name = Path::new(&path).file_stem().map(|s| s.to_string_lossy().into_owned()).unwrap_or_default();
+			profile.insert("name".to_string(), toml::Value::String(name));
+			profile.insert("path".to_string(), toml::Value::String(path));
+			Some(toml::Value::Table(profile))
+		})
+		.collect();
+	table.insert("profiles".to_string(), toml::Value::Array(profiles));
 }
 
 pub type State = TowermodConfig;
@@ -25,4 +166,3 @@ pub fn reducer(_state: State, action: Action) -> State {
 		Action::SetConfig(state) => state,
 	}
 }
-
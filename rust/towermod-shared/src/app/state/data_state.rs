@@ -2,7 +2,8 @@ use serde::{Deserialize, Serialize};
 use towermod_cstc::stable::*;
 use super::super::selectors;
 
-use crate::cstc_editing::{CstcData, EdAppBlock, EdContainer, EdFamily, EdLayout, EdLayoutLayer, EdObjectInstance, EdObjectType, VariableType, VariableValue};
+use crate::tiled::TiledImportChanges;
+use crate::cstc_editing::{CstcData, EdAppBlock, EdContainer, EdFamily, EdLayout, EdLayoutLayer, EdObjectInstance, EdObjectType, LayoutImageIdsChange, Prefab, VariableType, VariableValue};
 
 pub type State = CstcData;
 type RootState = super::app_state::State;
@@ -10,25 +11,54 @@ type RootState = super::app_state::State;
 pub enum Action {
 	SetData(CstcData),
 	SetImageMetadata (ImageMetadata),
+	/// Make the editor data of a plugin added to the project's DLLBLOCK available
+	AddEditorPlugin { id: i32, plugin: towermod_cstc::plugin::PluginData },
 
 	UpdateObjectType(EdObjectType),
 	CreateObjectType { id: i32, plugin_id: i32 },
 	DeleteObjectType(i32),
 	ObjectTypeAddVariable { id: i32, name: String, value: VariableValue },
 	ObjectTypeDeleteVariable { id: i32, name: String },
+	/// Merge a prefab that has already been remapped onto this project's ids
+	ImportPrefab { prefab: Prefab, layout_layer_id: i32 },
 
 	UpdateObjectInstance(EdObjectInstance),
 	CreateObjectInstance { id: i32, object_type_id: i32, layout_layer_id: i32 },
 	DeleteObjectInstance(i32),
+	/// Move instances to the end of another layer, keeping their relative order
+	MoveObjectInstances { ids: Vec<i32>, layout_layer_id: i32 },
+	/// Append fully-formed instances (e.g. pasted from the clipboard) to a layer
+	InsertObjectInstances { instances: Vec<EdObjectInstance>, layout_layer_id: i32 },
+	/// Apply the instances imported from a Tiled map
+	ImportTiledMap(TiledImportChanges),
+	/// Convert all version 1 Text object data to version 2
+	UpgradeLegacyTextObjects,
 
 	UpdateLayout(EdLayout),
+	/// Remove and append image ids of layouts, e.g. from [`CstcData::compute_layout_image_ids`]
+	UpdateLayoutImageIds(Vec<LayoutImageIdsChange>),
 
 	UpdateLayoutLayer(EdLayoutLayer),
+	/// Insert a new layer into a layout at `index`, or append it after the last layer if `None`.
+	/// Layers are drawn in list order, so the last layer is the topmost one.
+	CreateLayoutLayer { id: i32, layout_name: String, name: String, index: Option<usize> },
+	/// Remove a layer that has no instances
+	DeleteLayoutLayer(i32),
+	/// Move a layer to a new position within its layout
+	MoveLayoutLayer { id: i32, index: usize },
 
 	UpdateAnimation(Animation),
 	CreateAnimation { id: i32, object_type_id: i32 },
 
 	UpdateBehavior(Behavior),
+	/// Replace the data of a behavior with its encoded form. The data must be of the behavior's plugin.
+	UpdateBehaviorData { object_type_id: i32, mov_index: i32, data: towermod_cstc::BehaviorData },
+	/// Append a behavior with the data of an existing behavior of the same movement plugin
+	CreateBehavior { object_type_id: i32, plugin_id: i32, name: String },
+	/// Remove a behavior that isn't used by any events
+	DeleteBehavior { object_type_id: i32, mov_index: i32 },
+	/// Move a behavior to another position among its object type's behaviors
+	MoveBehavior { object_type_id: i32, mov_index: i32, index: usize },
 
 	UpdateContainer(EdContainer),
 	CreateContainer(i32),
@@ -53,6 +83,38 @@ impl From<Action> for super::app_state::Action {
 	}
 }
 
+/// Which part of the spatial index needs to be refreshed after an action
+pub enum SpatialIndexScope {
+	None,
+	Instances(Vec<i32>),
+	Layers(Vec<i32>),
+	All,
+}
+
+impl Action {
+	pub fn spatial_index_scope(&self) -> SpatialIndexScope {
+		use SpatialIndexScope as S;
+		match self {
+			// instances added or removed, or sprite hotspots changed
+			Action::SetData(_) | Action::SetImageMetadata(_) | Action::CreateObjectType { .. } | Action::DeleteObjectType(_) | Action::ImportPrefab { .. } | Action::UpdateAnimation(_) | Action::CreateAnimation { .. } => S::All,
+			Action::UpdateObjectInstance(obj) => S::Instances(vec![obj.id]),
+			Action::CreateObjectInstance { id, .. } | Action::DeleteObjectInstance(id) => S::Instances(vec![*id]),
+			Action::MoveObjectInstances { ids, .. } => S::Instances(ids.clone()),
+			Action::InsertObjectInstances { instances, .. } => S::Instances(instances.iter().map(|o| o.id).collect()),
+			Action::ImportTiledMap(changes) => S::Instances(changes.layers.values().flatten().map(|o| o.id).collect()),
+			Action::CreateLayoutLayer { id, .. } | Action::DeleteLayoutLayer(id) => S::Layers(vec![*id]),
+			// layout and layer updates keep their instances, and layer order doesn't affect the index
+			Action::UpdateLayout(_) | Action::UpdateLayoutLayer(_) | Action::MoveLayoutLayer { .. } | Action::UpdateLayoutImageIds(_) => S::None,
+			// text upgrades don't change the size or position of instances
+			Action::AddEditorPlugin { .. } | Action::UpdateObjectType(_) | Action::ObjectTypeAddVariable { .. } | Action::ObjectTypeDeleteVariable { .. } | Action::UpgradeLegacyTextObjects => S::None,
+			Action::UpdateBehavior(_) | Action::UpdateBehaviorData { .. } | Action::CreateBehavior { .. } | Action::DeleteBehavior { .. } | Action::MoveBehavior { .. } => S::None,
+			Action::UpdateContainer(_) | Action::CreateContainer(_) | Action::DeleteContainer(_) => S::None,
+			Action::CreateFamily { .. } | Action::DeleteFamily { .. } | Action::FamilyAddObject { .. } | Action::FamilyRemoveObject { .. } | Action::FamilyAddVariable { .. } | Action::FamilyDeleteVariable { .. } => S::None,
+			Action::UpdateTrait(_) | Action::CreateTrait(_) | Action::DeleteTrait(_) | Action::UpdateAppBlock(_) => S::None,
+		}
+	}
+}
+
 pub fn reducer(mut s: RootState, action: Action) -> RootState {
 
 	match (action) {
@@ -65,6 +127,9 @@ pub fn reducer(mut s: RootState, action: Action) -> RootState {
 				s.data.image_block.push(metadata);
 			}
 		},
+		Action::AddEditorPlugin { id, plugin } => {
+			s.data.editor_plugins.insert(id, plugin);
+		},
 
 		Action::UpdateObjectType(mut obj) => {
 			if let Some(original_obj) = selectors::select_object_type_mut(obj.id)(&mut s) {
@@ -117,6 +182,9 @@ pub fn reducer(mut s: RootState, action: Action) -> RootState {
 				instance.private_variables.remove(&name);
 			}
 		}
+		Action::ImportPrefab { prefab, layout_layer_id } => {
+			prefab.merge_into(&mut s.data, layout_layer_id);
+		},
 
 		Action::UpdateObjectInstance(obj) => {
 			if let Some(original_obj) = selectors::select_object_instance_mut(obj.id)(&mut s) {
@@ -133,6 +201,38 @@ pub fn reducer(mut s: RootState, action: Action) -> RootState {
 				}
 			}
 		},
+		Action::MoveObjectInstances { ids, layout_layer_id } => {
+			if selectors::select_layout_layer(layout_layer_id)(&s).is_none() { return s };
+			let mut moved = Vec::with_capacity(ids.len());
+			for layout in &mut s.data.layouts {
+				for layer in &mut layout.layers {
+					if layer.id == layout_layer_id { continue }
+					let (taken, kept): (Vec<_>, Vec<_>) = std::mem::take(&mut layer.objects).into_iter()
+						.partition(|o| ids.contains(&o.id));
+					layer.objects = kept;
+					moved.extend(taken);
+				}
+			}
+			if let Some(layer) = selectors::select_layout_layer_mut(layout_layer_id)(&mut s) {
+				layer.objects.extend(moved);
+			}
+		},
+		Action::InsertObjectInstances { instances, layout_layer_id } => {
+			// instance ids must be unique across all layouts
+			if instances.iter().any(|o| selectors::select_object_instance(o.id)(&s).is_some()) { return s };
+			let Some(layer) = selectors::select_layout_layer_mut(layout_layer_id)(&mut s) else { return s };
+			layer.objects.extend(instances);
+		},
+		Action::ImportTiledMap(changes) => {
+			changes.apply(&mut s.data);
+		},
+		Action::UpgradeLegacyTextObjects => {
+			for obj in s.data.layouts.iter_mut().flat_map(|l| l.layers.iter_mut()).flat_map(|l| l.objects.iter_mut()) {
+				if let towermod_cstc::ObjectData::Text(text) = &mut obj.data {
+					text.upgrade();
+				}
+			}
+		},
 
 		Action::UpdateLayout(mut layout) => {
 			if let Some(original_layout) = selectors::select_layout_mut(layout.name.clone())(&mut s) {
@@ -141,12 +241,45 @@ pub fn reducer(mut s: RootState, action: Action) -> RootState {
 			}
 		},
 
+		Action::UpdateLayoutImageIds(changes) => {
+			for change in changes {
+				if let Some(layout) = selectors::select_layout_mut(change.layout_name)(&mut s) {
+					layout.image_ids.retain(|id| !change.removed.contains(id));
+					for id in change.added {
+						if !layout.image_ids.contains(&id) { layout.image_ids.push(id) }
+					}
+				}
+			}
+		},
+
 		Action::UpdateLayoutLayer(mut layer) => {
 			if let Some(original_layer) = selectors::select_layout_layer_mut(layer.id)(&mut s) {
 				std::mem::swap(&mut layer.objects, &mut original_layer.objects);
 				*original_layer = layer;
 			}
 		},
+		Action::CreateLayoutLayer { id, layout_name, name, index } => {
+			// layer ids must be unique across all layouts
+			if selectors::select_layout_layer(id)(&s).is_some() { return s };
+			let Some(layout) = selectors::select_layout_mut(layout_name)(&mut s) else { return s };
+			let index = index.unwrap_or(layout.layers.len()).min(layout.layers.len());
+			layout.layers.insert(index, EdLayoutLayer::new(id, name));
+		},
+		Action::DeleteLayoutLayer(id) => {
+			for layout in &mut s.data.layouts {
+				layout.layers.retain(|l| l.id != id || !l.objects.is_empty());
+			}
+		},
+		Action::MoveLayoutLayer { id, index } => {
+			for layout in &mut s.data.layouts {
+				if let Some(old_index) = layout.layers.iter().position(|l| l.id == id) {
+					let layer = layout.layers.remove(old_index);
+					let index = index.min(layout.layers.len());
+					layout.layers.insert(index, layer);
+					break;
+				}
+			}
+		},
 
 		Action::UpdateAnimation(mut anim) => {
 			if let Some(original_anim) = selectors::select_animation_mut(anim.id)(&mut s) {
@@ -163,6 +296,22 @@ pub fn reducer(mut s: RootState, action: Action) -> RootState {
 				*original_behavior = behavior;
 			}
 		},
+		Action::UpdateBehaviorData { object_type_id, mov_index, data } => {
+			let Some(behavior) = selectors::select_behavior(object_type_id, mov_index)(&s) else { return s };
+			if !s.data.behavior_data_matches(behavior, &data) { return s };
+			if let Some(behavior) = selectors::select_behavior_mut(object_type_id, mov_index)(&mut s) {
+				behavior.data = data.encode();
+			}
+		},
+		Action::CreateBehavior { object_type_id, plugin_id, name } => {
+			s.data.add_behavior(object_type_id, plugin_id, name);
+		},
+		Action::DeleteBehavior { object_type_id, mov_index } => {
+			s.data.remove_behavior(object_type_id, mov_index);
+		},
+		Action::MoveBehavior { object_type_id, mov_index, index } => {
+			s.data.move_behavior(object_type_id, mov_index, index);
+		},
 
 		Action::UpdateContainer(container) => {
 			if container.object_ids.len() == 0 { return s }
@@ -236,7 +385,7 @@ pub fn reducer(mut s: RootState, action: Action) -> RootState {
 }
 
 fn create_object_instance(s: &mut RootState, id: i32, object_type_id: i32, layout_layer_id: i32) -> Option<&mut EdObjectInstance> {
-	let Some(plugin_name) = selectors::select_object_type_plugin_name(object_type_id)(&s).cloned() else { return None };
+	let data = s.data.new_object_data(object_type_id)?;
 	let root_animation_id = selectors::select_object_type_animation(object_type_id)(&s).map(|a| a.id);
 
 	for layout in &mut s.data.layouts {
@@ -245,7 +394,7 @@ fn create_object_instance(s: &mut RootState, id: i32, object_type_id: i32, layou
 				let mut obj = EdObjectInstance {
 					id,
 					object_type_id,
-					data: towermod_cstc::ObjectData::new(&plugin_name),
+					data,
 					..Default::default()
 				};
 				// set animation ID for new sprite instances
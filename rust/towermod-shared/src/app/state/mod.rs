@@ -12,5 +12,5 @@ pub mod data_state;
 pub mod config_state;
 
 pub use data_state::{Action as DataAction};
-pub use config_state::{TowermodConfig, Action as ConfigAction};
+pub use config_state::{TowermodConfig, GameProfile, Action as ConfigAction};
 pub use app_state::{STORE, State, select, dispatch, Action as AppAction};
@@ -1,5 +1,5 @@
-use std::{collections::HashMap, io::{Cursor, Read, Write}, path::PathBuf, sync::Mutex};
-use crate::{app::state::{AppAction, ConfigAction, DataAction, TowermodConfig, STORE}, convert_to_release_build, cstc_editing::CstcData, first_time_setup, get_towerclimb_appdata_dir_path, get_mods_dir_path, Game, GameType, ModInfo, ModType, PeResource, Project, ProjectType };
+use std::{collections::HashMap, io::{Cursor, Read, Write}, path::{Path, PathBuf}, sync::Mutex};
+use crate::{app::state::{AppAction, DataAction, STORE}, convert_to_release_build, cstc_editing::{CstcData, LayoutImageIdsChange}, first_time_setup, get_towerclimb_appdata_dir_path, get_mods_dir_path, Game, GameType, ModInfo, ModType, PeResource, PeResourceSet, Project, ProjectType };
 use anyhow::Result;
 use async_scoped::TokioScope;
 use anyhow::{Context};
@@ -74,7 +74,9 @@ pub async fn install_mod(resource: &str) -> Result<ModInfo> {
 	}
 }
 
+/// Mods installed for the current game. Mods that could not be read are always included
 pub async fn get_installed_mods() -> Result<Vec<ModInfo>> {
+	let data_hash = selectors::get_game().await.and_then(|g| g.data_hash);
 	let mut stream = fs::read_dir(get_mods_dir_path()).await?;
 	let mut mods: Vec<ModInfo> = Vec::new();
 	while let Some(entry) = stream.next_entry().await? {
@@ -110,7 +112,9 @@ pub async fn get_installed_mods() -> Result<Vec<ModInfo>> {
 			mod_info.file_path = Some(path);
 			if let Err(e) = result {
 				mod_info.error = Some(format!("{e}"));
-			};
+			} else if data_hash.is_some() && mod_info.game.data_hash.is_some() && mod_info.game.data_hash != data_hash {
+				continue
+			}
 			mods.push(mod_info);
 		}
 	}
@@ -131,7 +135,11 @@ pub async fn play_mod(zip_path: PathBuf) -> Result<u32> {
 	};
 
 	if mod_info.game.data_hash != game.data_hash {
-		anyhow::bail!("mod and currently selected game do not match");
+		let known_games = crate::load_known_games().await;
+		anyhow::bail!(
+			"This mod was made for {}, but the currently selected game is {}",
+			known_games.describe(&mod_info.game), known_games.describe(&game),
+		);
 	}
 	mod_info.game = game.clone();
 
@@ -193,6 +201,7 @@ pub async fn play_mod(zip_path: PathBuf) -> Result<u32> {
 		let (images, mut metadatas) = images::split_imageblock(image_block);
 		let src_exe_path = mod_info.game.get_release_build().await?;
 		fs::copy(&src_exe_path, &output_exe_path).await?;
+		let mut resources = PeResourceSet::new();
 
 		if mod_info.mod_type == ModType::BinaryPatch {
 			let level_block_patch: Mutex<Option<Vec<u8>>> = Mutex::new(None);
@@ -228,25 +237,47 @@ pub async fn play_mod(zip_path: PathBuf) -> Result<u32> {
 			if let Some(level_block_patch) = level_block_patch.into_inner().unwrap() {
 				let level_block_bin = cstc::LevelBlock::read_bin(&game_path)?;
 				let buf = towermod_util::apply_patch(&*level_block_bin, &*level_block_patch)?;
-				cstc::LevelBlock::write_bin(&output_exe_path, &buf)?;
+				resources.stage_block_bin::<cstc::LevelBlock>(buf);
 			}
 			if let Some(event_block_patch) = event_block_patch.into_inner().unwrap() {
 				let event_block_bin = cstc::EventBlock::read_bin(&game_path)?;
 				let buf = towermod_util::apply_patch(&*event_block_bin, &*event_block_patch)?;
-				cstc::EventBlock::write_bin(&output_exe_path, &buf)?;
+				resources.stage_block_bin::<cstc::EventBlock>(buf);
 			}
 			if let Some(app_block_patch) = app_block_patch.into_inner().unwrap() {
 				let app_block_bin = cstc::AppBlock::read_bin(&game_path)?;
 				let buf = towermod_util::apply_patch(&*app_block_bin, &*app_block_patch)?;
-				cstc::AppBlock::write_bin(&output_exe_path, &buf)?;
+				resources.stage_block_bin::<cstc::AppBlock>(buf);
 			}
 			if let Some(image_block_patch) = image_block_patch.into_inner().unwrap() {
 				metadatas = images::apply_imageblock_metadata_patch(metadatas, image_block_patch)?;
 			}
+
+			if !mod_info.added_plugins.is_empty() {
+				status("Adding plugins");
+				let first_id = first_added_plugin_id(&mod_info.game).await?;
+				let mut plugins = Vec::new();
+				for file_name in &mod_info.added_plugins {
+					let mut file = zip.by_name(&format!("plugins/{file_name}")).with_context(|| format!("Mod is missing plugin {file_name}"))?;
+					let mut buf = Vec::new();
+					file.read_to_end(&mut buf)?;
+					plugins.push(buf);
+				}
+				resources.stage_dllblock_plugins(first_id, &plugins)?;
+			}
+
+			for resource in &mod_info.resources {
+				let mut file = zip.by_name(&resource.zip_path()).with_context(|| format!("Mod is missing resource file {}", resource.file.display()))?;
+				let mut buf = Vec::new();
+				file.read_to_end(&mut buf)?;
+				resources.stage(resource.res_type.clone(), resource.name.clone(), resource.language, buf);
+			}
 		}
 
 		let patched_image_block = images::get_patched_image_block(images, Some(zip_path), metadatas).await?;
-		patched_image_block.write_to_pe(&output_exe_path)?;
+		resources.stage_block(&patched_image_block)?;
+		status("Writing executable");
+		resources.commit(&output_exe_path)?;
 	}
 
 	if is_towerclimb {
@@ -276,7 +307,7 @@ async fn run_patched_mod(mod_info: &ModInfo) -> Result<u32> {
 	let game_name = game_path.file_name().unwrap();
 	let output_exe_path = runtime_dir.join(game_name);
 
-	Ok(crate::run_game(&output_exe_path).await?)
+	Ok(thunks::run_game(&output_exe_path, &crate::mod_log_dir_path(mod_info.unique_version_name())).await?)
 }
 
 
@@ -340,19 +371,95 @@ pub async fn rebase_towerclimb_save_path(events: &mut cstc::EventBlock, unique_n
 pub async fn set_game(file_path: Option<PathBuf>) -> Result<()> {
 	if let Some(file_path) = file_path {
 		let game = Game::from_path(file_path.clone()).await?;
-		STORE.dispatch(AppAction::SetGame(Some(game))).await;
+		let previous_game = selectors::get_game().await;
 		// Update config with new game path
-		STORE.dispatch(ConfigAction::SetConfig(TowermodConfig {
-			game_path: Some(file_path)
-		}).into()).await;
+		let mut config = selectors::get_config().await;
+		config.game_path = Some(file_path.clone());
+		#[cfg(not(windows))]
+		let new_profile = config.profile(&file_path).is_none();
+		let profile = config.profile_mut(&file_path);
+		profile.game_type = game.game_type;
+		profile.data_hash = game.data_hash.clone();
+		#[cfg(not(windows))]
+		if game.game_type == GameType::Towerclimb && new_profile {
+			// Run TowerClimb through Proton by default, when Steam has created a prefix for it
+			if let Ok(launcher) = tokio::task::spawn_blocking(crate::GameLauncher::detect_towerclimb_proton).await? {
+				config.profile_mut(&file_path).launcher.launcher = launcher;
+			}
+		}
+		let last_project = config.current_profile().and_then(|p| p.recent_projects.first().cloned());
+		STORE.dispatch(AppAction::SetGame(Some(game))).await;
+		thunks::set_config(config).await;
 		// Attempt to save updated config
 		let _ = towermod_util::log_error(thunks::save_config().await, "");
+		// When switching games, pick up where the user left off with the new one
+		if previous_game.is_some() {
+			if let Some(manifest_path) = last_project.filter(|p| p.is_file()) {
+				let _ = towermod_util::log_error(load_project(manifest_path).await, "Failed to restore last project");
+			}
+		}
 	} else {
 		STORE.dispatch(AppAction::SetGame(None)).await;
 	}
 	Ok(())
 }
 
+/// Switch to a game that already has a profile
+pub async fn select_game_profile(game_path: PathBuf) -> Result<()> {
+	let config = selectors::get_config().await;
+	config.profile(&game_path).with_context(|| format!("No profile for {}", game_path.display()))?;
+	set_game(Some(game_path)).await
+}
+
+/// Remove a game's profile. The current game cannot be removed
+pub async fn remove_game_profile(game_path: PathBuf) -> Result<()> {
+	let mut config = selectors::get_config().await;
+	if config.game_path.as_ref() == Some(&game_path) {
+		anyhow::bail!("Cannot remove the profile of the current game");
+	}
+	config.profiles.retain(|p| p.path != game_path);
+	thunks::set_config(config).await;
+	thunks::save_config().await
+}
+
+/// Change the display name of a game's profile
+pub async fn rename_game_profile(game_path: PathBuf, name: String) -> Result<()> {
+	let mut config = selectors::get_config().await;
+	config.profiles.iter_mut().find(|p| p.path == game_path).context("No such profile")?.name = name;
+	thunks::set_config(config).await;
+	thunks::save_config().await
+}
+
+/// Remember a project as the most recently opened one for the current game
+async fn add_recent_project(dir_path: &Path) -> Result<()> {
+	let mut config = selectors::get_config().await;
+	let game_path = config.game_path.clone().context("No game set")?;
+	config.profile_mut(&game_path).add_recent_project(dir_path.join("manifest.toml"));
+	thunks::set_config(config).await;
+	thunks::save_config().await
+}
+
+/// Recently opened projects made for the current game, most recent first
+pub async fn get_recent_projects() -> Result<Vec<Project>> {
+	let game = selectors::get_game().await.context("No game set")?;
+	let config = selectors::get_config().await;
+	// projects opened with other copies of the same game are included too
+	let manifest_paths = config.current_profile().into_iter()
+		.chain(config.profiles.iter())
+		.flat_map(|p| p.recent_projects.iter())
+		.unique()
+		.cloned()
+		.collect::<Vec<_>>();
+	let mut projects = Vec::new();
+	for manifest_path in manifest_paths {
+		let Ok(project) = Project::from_path(&manifest_path).await else { continue };
+		if project.game.data_hash == game.data_hash {
+			projects.push(project);
+		}
+	}
+	Ok(projects)
+}
+
 #[instrument]
 pub async fn new_project() -> Result<()> {
 	let game = selectors::get_game().await.context("No game set")?;
@@ -364,7 +471,7 @@ pub async fn new_project() -> Result<()> {
 		EventBlock::read_from_pe(&game_path),
 	);
 	let (level_block, app_block, event_block) = result?;
-	let editor_plugins = get_editor_plugins(&game).await?;
+	let editor_plugins = get_editor_plugins(&game, &[]).await?;
 	let image_block = cstc::ImageBlock::read_from_pe(game.game_path()?).await?;
 	let (_images, image_block) = images::split_imageblock(image_block);
 
@@ -375,17 +482,138 @@ pub async fn new_project() -> Result<()> {
 	Ok(())
 }
 
-async fn get_editor_plugins(game: &Game) -> Result<HashMap<i32, plugin::PluginData>> {
+async fn get_editor_plugins(game: &Game, added_plugins: &[String]) -> Result<HashMap<i32, plugin::PluginData>> {
 	status("Loading Construct Classic plugin data");
 	let (mut editor_plugins, _) = game.load_editor_plugins().await?;
 	editor_plugins.insert(-1, cstc::get_system_plugin());
+	let first_id = first_added_plugin_id(game).await?;
+	for (i, file_name) in added_plugins.iter().enumerate() {
+		let data = crate::dllreader_client::remote_read_editor_plugin(crate::get_editor_plugins_path().await?.join(file_name)).await?;
+		editor_plugins.insert(first_id + i as i32, data);
+	}
 	status("Loading");
 	Ok(editor_plugins.into_iter().map(|(k, v)| (k, v)).collect())
 }
 
+/// Plugin ID given to the first plugin a project adds to the game's DLLBLOCK
+pub async fn first_added_plugin_id(game: &Game) -> Result<i32> {
+	let (_, plugin_names) = game.load_editor_plugins().await?;
+	Ok(plugin_names.keys().max().map_or(0, |id| id + 1))
+}
+
+/// Runtime builds of project-added plugins, in plugin ID order
+pub async fn read_added_plugins(added_plugins: &[String]) -> Result<Vec<Vec<u8>>> {
+	let dir = crate::get_runtime_plugins_path().await?;
+	let mut plugins = Vec::with_capacity(added_plugins.len());
+	for file_name in added_plugins {
+		plugins.push(fs::read(dir.join(file_name)).await?);
+	}
+	Ok(plugins)
+}
+
+/// File names of the Construct Classic runtime plugins that can be added to a project
+pub async fn get_runtime_plugin_names() -> Result<Vec<String>> {
+	let mut names = Vec::new();
+	let mut entries = fs::read_dir(crate::get_runtime_plugins_path().await?).await?;
+	while let Some(entry) = entries.next_entry().await? {
+		let name = entry.file_name().to_string_lossy().to_string();
+		if name.to_lowercase().ends_with(".csx") && entry.metadata().await?.is_file() {
+			names.push(name);
+		}
+	}
+	names.sort();
+	Ok(names)
+}
+
+/// Add a runtime plugin the game doesn't ship with to the project and save its manifest, returning the plugin ID
+pub async fn add_plugin(file_name: String) -> Result<i32> {
+	let game = selectors::get_game().await.context("No game set")?;
+	let mut project = selectors::get_project().await.context("Save the project before adding plugins")?;
+	if project.added_plugins.contains(&file_name) {
+		anyhow::bail!("{file_name} has already been added");
+	}
+	let runtime_path = crate::get_runtime_plugins_path().await?.join(&file_name);
+	if !fs::try_exists(&runtime_path).await? {
+		anyhow::bail!("No runtime build found for {file_name}");
+	}
+	let data = crate::dllreader_client::remote_read_editor_plugin(crate::get_editor_plugins_path().await?.join(&file_name)).await?;
+	let name = data.string_table.name.clone();
+	if let Some(id) = select(move |s| s.data.editor_plugins.iter().find(|(_, p)| p.string_table.name == name).map(|(id, _)| *id)).await {
+		anyhow::bail!("{} is already available as plugin {id}", data.string_table.name);
+	}
+
+	let id = first_added_plugin_id(&game).await? + project.added_plugins.len() as i32;
+	project.added_plugins.push(file_name);
+	// plugin IDs follow the order in the manifest, so it's saved right away
+	project.save().await?;
+	STORE.dispatch(AppAction::EditProjectInfo(project)).await;
+	STORE.dispatch(DataAction::AddEditorPlugin { id, plugin: data }.into()).await;
+	Ok(id)
+}
+
+/// Every resource embedded in the game executable
+pub async fn list_game_resources() -> Result<Vec<crate::PeResourceInfo>> {
+	let game = selectors::get_game().await.context("No game set")?;
+	let game_path = game.game_path()?.clone();
+	tokio::task::spawn_blocking(move || crate::list_pe_resources(game_path)).await?
+}
+
+/// Remove the `towermod:` URL handler and `.towermod` file association installed by [`first_time_setup`]
+pub async fn uninstall_desktop_integration() -> Result<()> {
+	#[cfg(windows)]
+	anyhow::bail!("Uninstalling file associations is not supported on Windows");
+	#[cfg(not(windows))]
+	tokio::task::spawn_blocking(|| crate::uninstall_desktop_integration(&Default::default())).await?
+}
+
+/// Start a game executable with the configured launcher, returning the process ID
+pub async fn run_game(game_path: &Path, log_dir: &Path) -> Result<u32> {
+	let launcher = selectors::get_config().await.current_profile().map(|p| p.launcher.clone()).unwrap_or_default();
+	let session = crate::spawn_game(game_path, &launcher, log_dir).await?;
+	Ok(session.pid)
+}
+
+/// Change the launcher of the current game
+pub async fn set_launcher_config(launcher: crate::LauncherConfig) -> Result<()> {
+	let mut config = selectors::get_config().await;
+	let game_path = config.game_path.clone().context("No game set")?;
+	config.profile_mut(&game_path).launcher = launcher;
+	thunks::set_config(config).await;
+	thunks::save_config().await
+}
+
+/// Replace the launcher of the current game with the Proton prefix Steam created for TowerClimb
+pub async fn detect_launcher() -> Result<crate::GameLauncher> {
+	let launcher = tokio::task::spawn_blocking(crate::GameLauncher::detect_towerclimb_proton).await??;
+	let mut config = selectors::get_config().await;
+	let game_path = config.game_path.clone().context("No game set")?;
+	config.profile_mut(&game_path).launcher.launcher = launcher.clone();
+	thunks::set_config(config).await;
+	thunks::save_config().await?;
+	Ok(launcher)
+}
+
+/// Name and version of the current game, if it is in the database of known games
+pub async fn get_known_game() -> Result<Option<crate::KnownGame>> {
+	let game = selectors::get_game().await.context("No game set")?;
+	Ok(crate::load_known_games().await.find_game(&game).cloned())
+}
+
+/// Version information embedded in the game executable
+pub async fn get_game_version_info() -> Result<crate::VersionInfo> {
+	let game = selectors::get_game().await.context("No game set")?;
+	let game_path = game.game_path()?.clone();
+	tokio::task::spawn_blocking(move || crate::read_version_info(game_path)).await?
+}
+
+pub async fn extract_game_resource(res_type: crate::ResourceId, name: crate::ResourceId, language: Option<u16>, dest_path: PathBuf) -> Result<()> {
+	let game = selectors::get_game().await.context("No game set")?;
+	crate::extract_pe_resource(game.game_path()?, &res_type, &name, language, dest_path).await
+}
+
 pub async fn play_vanilla() -> Result<u32> {
 	let game = selectors::get_game().await.context("No game set")?;
-	Ok(crate::run_game(game.game_path()?).await?)
+	Ok(thunks::run_game(game.game_path()?, &crate::mod_log_dir_path("vanilla")).await?)
 }
 
 pub async fn load_project_preflight(manifest_path: PathBuf) -> Result<Option<String>> {
@@ -396,12 +624,13 @@ pub async fn load_project_preflight(manifest_path: PathBuf) -> Result<Option<Str
 	let game = selectors::get_game().await.context("No game set")?;
 	if !(proj.game.file_hash == game.file_hash) {
 		// Warn if project and current game differ
+		let known_games = crate::load_known_games().await;
 		let msg = indoc::formatdoc!(r#"
-			Project and current game version DO NOT match.
+			Project {} was made for {}, but the current game is {}.
 			Combining project data with the wrong game data usually won't end well.
 			Back up your project first.
 
-			Expected game for project {}:
+			Expected game:
 			- Filename: {}
 			- Size: {} bytes
 			- MD5: {}
@@ -410,7 +639,8 @@ pub async fn load_project_preflight(manifest_path: PathBuf) -> Result<Option<Str
 			- Filename: {}
 			- Size: {} bytes
 			- MD5: {}
-		"#, proj.name, proj.game.file_name, proj.game.file_size, proj.game.file_hash, game.file_name, game.file_size, game.file_hash);
+		"#, proj.name, known_games.describe(&proj.game), known_games.describe(&game),
+			proj.game.file_name, proj.game.file_size, proj.game.file_hash, game.file_name, game.file_size, game.file_hash);
 		return Ok(Some(msg));
 	}
 	Ok(None)
@@ -465,16 +695,17 @@ pub async fn load_project(manifest_path: PathBuf) -> Result<()> {
 			image_block
 		},
 	};
-	let editor_plugins = get_editor_plugins(&game).await?;
+	let editor_plugins = get_editor_plugins(&game, &project.added_plugins).await?;
 
 	let data = CstcData::from_stable((editor_plugins, app_block, image_block, level_block, event_block))?;
 
 	STORE.dispatch(AppAction::SetProject(Some(project)).into()).await;
 	STORE.dispatch(DataAction::SetData(data).into()).await;
+	log_on_error(add_recent_project(proj_dir).await);
 	Ok(())
 }
 
-pub async fn save_new_project(dir_path: PathBuf, author: String, name: String, display_name: String) -> Result<()> {
+pub async fn save_new_project(dir_path: PathBuf, author: String, name: String, display_name: String) -> Result<Vec<LayoutImageIdsChange>> {
 	let game = selectors::get_game().await.context("No game set")?;
 	let mut project = Project::new(author, name, display_name, "0.0.1".to_string(), game);
 	project.dir_path = Some(PathBuf::from(&dir_path));
@@ -482,11 +713,11 @@ pub async fn save_new_project(dir_path: PathBuf, author: String, name: String, d
 	fs::create_dir_all(project.files_path()?).await?;
 	fs::create_dir_all(project.savefiles_path()?).await?;
 	STORE.dispatch(AppAction::SetProject(Some(project)).into()).await;
-	save_project(dir_path).await?;
-	Ok(())
+	save_project(dir_path).await
 }
 
-pub async fn save_project(dir_path: PathBuf) -> Result<()> {
+/// Returns the images that had to be added to the layouts' preloaded images
+pub async fn save_project(dir_path: PathBuf) -> Result<Vec<LayoutImageIdsChange>> {
 	let game = selectors::get_game().await.context("No game set")?;
 	let mut project = selectors::get_project().await.context("No project set")?;
 
@@ -497,8 +728,13 @@ pub async fn save_project(dir_path: PathBuf) -> Result<()> {
 	project.game = game;
 	// Save manifest.toml
 	project.save().await?;
+	let upgrade_legacy_text = project.upgrade_legacy_text;
 	STORE.dispatch(AppAction::EditProjectInfo(project)).await;
 
+	if upgrade_legacy_text {
+		STORE.dispatch(DataAction::UpgradeLegacyTextObjects.into()).await;
+	}
+	let layout_image_ids = thunks::update_layout_image_ids().await;
 	let data = select(|s| s.data.clone()).await;
 	let (_editor_plugins, app_block, image_block, level_block, event_block) = data.to_stable()?;
 
@@ -538,7 +774,8 @@ pub async fn save_project(dir_path: PathBuf) -> Result<()> {
 	})}.await;
 	for result in results { result?? }
 
-	Ok(())
+	log_on_error(add_recent_project(&dir_path).await);
+	Ok(layout_image_ids)
 }
 
 pub async fn edit_project_info(project: Project) {
@@ -559,7 +796,8 @@ pub async fn nuke_cache() -> Result<()> {
 	Ok(())
 }
 
-pub async fn export_mod(mod_type: ModType) -> Result<()> {
+/// Returns the images that had to be added to the layouts' preloaded images
+pub async fn export_mod(mod_type: ModType) -> Result<Vec<LayoutImageIdsChange>> {
 	status("Exporting");
 	let mod_type: ModType = From::from(mod_type);
 	let project = selectors::get_project().await.context("Project not set")?;
@@ -574,6 +812,7 @@ pub async fn export_mod(mod_type: ModType) -> Result<()> {
 	let mut zip = zip::ZipWriter::new(Cursor::new(&mut buffer));
 	let options: zip::write::FileOptions<'_, ()> = zip::write::FileOptions::default().compression_method(zip::CompressionMethod::Deflated);
 
+	let mut layout_image_ids = Vec::new();
 	if mod_type == ModType::BinaryPatch {
 		let mut level_block_patch = Vec::new();
 		let mut app_block_patch = Vec::new();
@@ -585,6 +824,7 @@ pub async fn export_mod(mod_type: ModType) -> Result<()> {
 			let original_event_block_bin = cstc::EventBlock::read_bin(&game_path)?;
 			let original_image_block_bin = cstc::ImageBlock::read_bin(&game_path)?;
 
+			layout_image_ids = thunks::update_layout_image_ids().await;
 			let data = select(|s| s.data.clone()).await;
 			let (_editor_plugins, app_block, image_metadatas, level_block, event_block) = data.to_stable()?;
 
@@ -627,6 +867,18 @@ pub async fn export_mod(mod_type: ModType) -> Result<()> {
 			zip.start_file("imageblock.patch", options)?;
 			zip.write_all(&image_metadata_patch)?;
 		}
+		let plugins = read_added_plugins(&project.added_plugins).await?;
+		for (file_name, bin) in project.added_plugins.iter().zip(plugins) {
+			zip.start_file(format!("plugins/{file_name}"), options)?;
+			zip.write_all(&bin)?;
+		}
+		for resource in &project.resources {
+			// the project info may have been edited since it was loaded
+			resource.validate()?;
+			let bin = fs::read(project_dir.join(&resource.file)).await?;
+			zip.start_file(resource.zip_path(), options)?;
+			zip.write_all(&bin)?;
+		}
 	}
 
 
@@ -646,7 +898,7 @@ pub async fn export_mod(mod_type: ModType) -> Result<()> {
 	let mut file = fs::File::create(&mod_info.export_path()).await?;
 	file.write_all(&buffer).await?;
 
-	Ok(())
+	Ok(layout_image_ids)
 }
 
 pub async fn get_patched_image_block_load_game(dir_or_zip: Option<PathBuf>, metadata: Option<Vec<ImageMetadata>>) -> Result<ImageBlock> {
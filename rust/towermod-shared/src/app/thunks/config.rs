@@ -1,28 +1,37 @@
-use std::path::PathBuf;
-use crate::app::state::{STORE, ConfigAction as Action};
+use crate::app::state::{STORE, ConfigAction as Action, TowermodConfig};
 use crate::app::selectors::get_config;
 use anyhow::{Context, Result};
 use fs_err::tokio as fs;
 
-fn config_path() -> PathBuf {
-	crate::get_cache_dir_path().join("towermod-config.toml")
-}
-
-// TODO: include towermod version
 pub async fn save_config() -> Result<()> {
-	let path = config_path();
+	let path = crate::get_config_path();
 	let config = get_config().await;
 	let s = toml::to_string_pretty(&config)?;
+	if let Some(dir) = path.parent() {
+		fs::create_dir_all(dir).await?;
+	}
 	fs::write(path, s).await.context("Failed to write config")?;
 	Ok(())
 }
 
+/// Replace the config in the store, and apply the paths it overrides
+pub async fn set_config(config: TowermodConfig) {
+	crate::set_path_overrides(config.path_overrides());
+	STORE.dispatch(Action::SetConfig(config).into()).await;
+}
+
 pub async fn load_config() -> Result<()> {
-	let path = config_path();
-	match fs::read_to_string(path).await {
+	let path = crate::get_config_path();
+	match fs::read_to_string(&path).await {
 		Ok(s) => {
-			let config = toml::from_str(&s).context("Failed to parse config")?;
-			STORE.dispatch(Action::SetConfig(config).into()).await;
+			let (config, migrated) = TowermodConfig::from_toml(&s)?;
+			set_config(config).await;
+			if migrated {
+				log::info!("Migrated config to version {}", TowermodConfig::VERSION);
+				// keep the original in case a newer config is incompatible with an older towermod
+				fs::copy(&path, path.with_extension("toml.bak")).await?;
+				save_config().await?;
+			}
 			Ok(())
 		}
 		Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
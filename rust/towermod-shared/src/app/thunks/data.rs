@@ -1,9 +1,9 @@
-use std::{cell::RefCell, sync::RwLock};
+use std::{cell::RefCell, collections::HashMap, io::{Cursor, Read, Write}, path::PathBuf, sync::RwLock};
 
 use anyhow::{Context, Result};
 use futures::StreamExt;
 use towermod_cstc::{ImageMetadata, ObjectType};
-use crate::{app::{selectors, state::{select, DataAction, STORE}}, cstc_editing::EdObjectInstance};
+use crate::{layout_renderer::{self, RenderOptions, RgbaImage}, tiled::{self, TiledImport, TiledMap}, app::{selectors, state::{select, DataAction, STORE}}, cstc_editing::{CstcData, EdObjectInstance, InstanceClipboard, LayoutImageIdsChange, PasteResult, Prefab, PrefabIds, PrefabImport}};
 use fs_err::tokio as fs;
 
 pub async fn get_image(id: i32) -> Option<Vec<u8>> {
@@ -65,3 +65,236 @@ pub async fn is_image_overridden(id: i32) -> bool {
 pub async fn set_image_metadata(data: ImageMetadata) {
 	STORE.dispatch(DataAction::SetImageMetadata(data).into()).await;
 }
+
+/// Render a layout preview to a PNG, using the game's images patched with the project's image overrides
+pub async fn render_layout_png(layout_name: String, options: RenderOptions) -> Result<Vec<u8>> {
+	let (data, layout) = select(move |s| {
+		let layout = selectors::select_layout(layout_name.clone())(s).cloned();
+		let data = CstcData { animations: s.data.animations.clone(), image_block: s.data.image_block.clone(), ..Default::default() };
+		(data, layout)
+	}).await;
+	let layout = layout.context("Layout not found")?;
+	let image_ids = layout_renderer::required_image_ids(&data, &layout);
+	let mut pngs = HashMap::new();
+	let mut missing = Vec::new();
+	for id in image_ids {
+		match get_image(id).await {
+			Some(png) => { pngs.insert(id, png); },
+			None => missing.push(id),
+		}
+	}
+	// images that haven't been dumped have to be read from the game
+	if !missing.is_empty() {
+		let game = selectors::get_game().await.context("No game set")?;
+		let (mut game_images, _) = crate::game_images::images_from_game(game.game_path()?).await?;
+		pngs.extend(missing.into_iter().filter_map(|id| Some((id, game_images.remove(&id)?))));
+	}
+	tokio::task::spawn_blocking(move || {
+		let images = pngs.into_iter()
+			.map(|(id, png)| Ok((id, RgbaImage::from_png(&png).with_context(|| format!("Failed to decode image {id}"))?)))
+			.collect::<Result<HashMap<_, _>>>()?;
+		layout_renderer::render_layout(&data, &layout, &images, &options).to_png()
+	}).await?
+}
+
+fn is_json_path(path: &std::path::Path) -> bool {
+	path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("json"))
+}
+
+/// Export a layout as a Tiled map (JSON if the path ends in `.json`, otherwise TMX), writing its images next to it
+pub async fn export_layout_tiled(layout_name: String, path: PathBuf) -> Result<()> {
+	let (map, image_ids) = select(move |s| {
+		let layout = selectors::select_layout(layout_name.clone())(s)?;
+		Some(tiled::export_layout(&s.data, layout))
+	}).await.context("Layout not found")?;
+	let contents = if is_json_path(&path) { map.to_json()? } else { map.to_tmx() };
+	let dir = path.parent().context("Invalid path")?.to_path_buf();
+	fs::create_dir_all(dir.join("images")).await?;
+	for id in image_ids {
+		if let Some(image) = get_image(id).await {
+			fs::write(dir.join(tiled::image_path(id)), image).await?;
+		}
+	}
+	fs::write(&path, contents).await?;
+	Ok(())
+}
+
+/// Apply a Tiled map to a layout, updating instances that match by id and creating the rest
+pub async fn import_layout_tiled(layout_name: String, path: PathBuf) -> Result<TiledImport> {
+	let contents = fs::read_to_string(&path).await?;
+	let map = if is_json_path(&path) { TiledMap::from_json(&contents)? } else { TiledMap::from_tmx(&contents)? };
+	let changes = select(move |s| {
+		let layout = selectors::select_layout(layout_name.clone())(s)?;
+		let first_id = selectors::select_new_object_instance_id()(s);
+		Some(tiled::import_map(&s.data, layout, &map, first_id))
+	}).await.context("Layout not found")?;
+	let report = changes.report.clone();
+	STORE.dispatch(DataAction::ImportTiledMap(changes).into()).await;
+	Ok(report)
+}
+
+/// Make sure every layout preloads the images of the objects placed on it, returning the images that were added
+pub async fn update_layout_image_ids() -> Vec<LayoutImageIdsChange> {
+	let changes = select(|s| s.data.compute_layout_image_ids()).await;
+	for change in &changes {
+		log::info!("Layout {}: added images {:?}", change.layout_name, change.added);
+	}
+	if !changes.is_empty() {
+		STORE.dispatch(DataAction::UpdateLayoutImageIds(changes.clone()).into()).await;
+	}
+	changes
+}
+
+/// Images preloaded by layouts that no instance placed there uses, to be confirmed before [`remove_layout_image_ids`]
+pub async fn get_unused_layout_image_ids() -> Vec<LayoutImageIdsChange> {
+	select(|s| s.data.compute_unused_layout_image_ids()).await
+}
+
+/// Remove the `removed` images of each change from its layout
+pub async fn remove_layout_image_ids(changes: Vec<LayoutImageIdsChange>) {
+	let changes = changes.into_iter().map(|c| LayoutImageIdsChange { added: Vec::new(), ..c }).collect();
+	STORE.dispatch(DataAction::UpdateLayoutImageIds(changes).into()).await;
+}
+
+/// Add a behavior to an object type, returning its `mov_index`
+pub async fn create_behavior(object_type_id: i32, plugin_id: i32, name: String) -> Result<i32> {
+	let plugin_name = select(move |s| s.data.editor_plugins.get(&plugin_id).map(|p| p.string_table.name.clone())).await.context("Plugin not found")?;
+	if select(move |s| s.data.new_behavior_data(plugin_id).is_none()).await {
+		anyhow::bail!("New {plugin_name} behaviors have to be copied from an existing one, but no object type in this project has one");
+	}
+	if !select(move |s| s.data.object_types.contains_key(&object_type_id)).await {
+		anyhow::bail!("Object type not found");
+	}
+	let mov_index = select(move |s| s.data.new_behavior_index(object_type_id)).await;
+	STORE.dispatch(DataAction::CreateBehavior { object_type_id, plugin_id, name }.into()).await;
+	Ok(mov_index)
+}
+
+pub async fn update_behavior_data(object_type_id: i32, mov_index: i32, data: towermod_cstc::BehaviorData) -> Result<()> {
+	let matches = {
+		let data = data.clone();
+		select(move |s| {
+			let behavior = selectors::select_behavior(object_type_id, mov_index)(s)?;
+			Some(s.data.behavior_data_matches(behavior, &data))
+		}).await.context("Behavior not found")?
+	};
+	if !matches {
+		anyhow::bail!("Behavior data doesn't match the behavior's plugin");
+	}
+	STORE.dispatch(DataAction::UpdateBehaviorData { object_type_id, mov_index, data }.into()).await;
+	Ok(())
+}
+
+pub async fn delete_behavior(object_type_id: i32, mov_index: i32) -> Result<()> {
+	let references = select(move |s| s.data.behavior_event_references(object_type_id, mov_index)).await;
+	if references > 0 {
+		anyhow::bail!("Behavior is still used by {references} conditions or actions");
+	}
+	STORE.dispatch(DataAction::DeleteBehavior { object_type_id, mov_index }.into()).await;
+	Ok(())
+}
+
+/// Place a new instance of an object type on a layer, returning its id
+pub async fn create_object_instance(object_type_id: i32, layout_layer_id: i32) -> Result<i32> {
+	let (id, can_create) = select(move |s| (selectors::select_new_object_instance_id()(s), s.data.new_object_data(object_type_id).is_some())).await;
+	if !can_create {
+		let plugin_name = select(move |s| selectors::select_object_type_plugin_name(object_type_id)(s).cloned()).await.context("Object type not found")?;
+		anyhow::bail!("New {plugin_name} objects have to be copied from an existing one, place or paste an instance from the game first");
+	}
+	STORE.dispatch(DataAction::CreateObjectInstance { id, object_type_id, layout_layer_id }.into()).await;
+	Ok(id)
+}
+
+/// Delete an empty layer. Instances must be deleted or moved to another layer first
+pub async fn delete_layout_layer(id: i32) -> Result<()> {
+	let instances = select(move |s| selectors::select_layout_layer(id)(s).map(|l| l.objects.len())).await.context("Layer not found")?;
+	if instances > 0 {
+		anyhow::bail!("Layer still contains {instances} instances");
+	}
+	STORE.dispatch(DataAction::DeleteLayoutLayer(id).into()).await;
+	Ok(())
+}
+
+pub async fn copy_object_instances(ids: Vec<i32>) -> Result<InstanceClipboard> {
+	select(move |s| InstanceClipboard::copy(&s.data, &ids)).await
+}
+
+/// Paste copied instances into a layer, allocating new instance ids and offsetting their positions.
+/// Instances whose object type does not exist in the current project, or whose sprite animations can't be found there, are skipped and reported.
+pub async fn paste_object_instances(clipboard: InstanceClipboard, layout_layer_id: i32, offset_x: i32, offset_y: i32) -> Result<PasteResult> {
+	select(move |s| selectors::select_layout_layer(layout_layer_id)(s).map(|_| ())).await.context("Layer not found")?;
+	let (instances, missing_object_types) = select(move |s| {
+		let first_id = selectors::select_new_object_instance_id()(s);
+		clipboard.clone().paste(&s.data, first_id, offset_x, offset_y)
+	}).await;
+	let ids = instances.iter().map(|o| o.id).collect();
+	STORE.dispatch(DataAction::InsertObjectInstances { instances, layout_layer_id }.into()).await;
+	Ok(PasteResult { ids, missing_object_types })
+}
+
+/// Write a prefab archive containing `prefab.json` and the PNGs of all images it references
+pub async fn export_prefab(object_type_ids: Vec<i32>, path: PathBuf) -> Result<()> {
+	let prefab = select(move |s| Prefab::export(&s.data, &object_type_ids)).await?;
+	let mut images = Vec::new();
+	for id in prefab.images.iter().map(|i| i.id) {
+		let image = get_image(id).await.with_context(|| format!("Could not read image {id}"))?;
+		images.push((id, image));
+	}
+
+	let mut buffer = vec![];
+	{
+		let mut zip = zip::ZipWriter::new(Cursor::new(&mut buffer));
+		let options: zip::write::FileOptions<'_, ()> = zip::write::FileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+		zip.start_file("prefab.json", options)?;
+		zip.write_all(&serde_json::to_vec_pretty(&prefab)?)?;
+		for (id, image) in images {
+			zip.start_file(format!("images/{id}.png"), options)?;
+			zip.write_all(&image)?;
+		}
+		zip.finish()?;
+	}
+	fs::write(&path, buffer).await?;
+	Ok(())
+}
+
+/// Import a prefab archive, placing an instance of each object type on `layout_layer_id`.
+/// Images are written to the project's image overrides under their new ids.
+pub async fn import_prefab(path: PathBuf, layout_layer_id: i32) -> Result<PrefabImport> {
+	let images_path = select(|s| s.project.as_ref().map(|p| p.images_path().ok()).flatten()).await.context("Project must be saved before importing a prefab")?;
+	select(move |s| selectors::select_layout_layer(layout_layer_id)(s).map(|_| ())).await.context("Layer not found")?;
+
+	let bytes = fs::read(&path).await?;
+	let mut zip = zip::ZipArchive::new(Cursor::new(bytes))?;
+	let prefab: Prefab = {
+		let mut file = zip.by_name("prefab.json")?;
+		let mut s = String::new();
+		file.read_to_string(&mut s)?;
+		serde_json::from_str(&s)?
+	};
+
+	let (prefab, import) = select(move |s| {
+		let ids = PrefabIds {
+			object_type_id: selectors::select_new_object_type_id()(s),
+			object_instance_id: selectors::select_new_object_instance_id()(s),
+			animation_id: selectors::select_new_animation_id(s),
+			image_id: selectors::select_new_image_id(s),
+		};
+		prefab.clone().remap(&s.data, ids)
+	}).await?;
+
+	let mut images = Vec::new();
+	for (old_id, new_id) in &import.image_ids {
+		let mut file = zip.by_name(&format!("images/{old_id}.png")).with_context(|| format!("Prefab is missing the pixels of image {old_id}"))?;
+		let mut image = Vec::new();
+		file.read_to_end(&mut image)?;
+		images.push((*new_id, image));
+	}
+	drop(zip);
+	fs::create_dir_all(&images_path).await?;
+	for (id, image) in images {
+		fs::write(images_path.join(format!("{id}.png")), image).await?;
+	}
+
+	STORE.dispatch(DataAction::ImportPrefab { prefab, layout_layer_id }.into()).await;
+	Ok(import)
+}
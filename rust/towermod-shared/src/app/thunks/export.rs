@@ -1,10 +1,10 @@
 use std::path::PathBuf;
-use crate::{game_images, GameType, PeResource, Project, ProjectType};
+use crate::{game_images, GameType, PeResource, PeResourceSet, Project, ProjectType};
 use anyhow::Result;
 use anyhow::{Context};
 use fs_err::tokio as fs;
 use towermod_cstc::{self as cstc};
-use towermod_util::merge_copy_into;
+use towermod_util::{blocking, merge_copy_into};
 use crate::app::{state::select, selectors, thunks};
 
 /// For prepopulating the project-details form for exporting Legacy/FilesOnly projects
@@ -37,6 +37,7 @@ pub async fn export_from_legacy(patch_path: PathBuf, mut project: Project) -> Re
 }
 
 pub async fn play_project(debug: bool) -> Result<u32> {
+	thunks::update_layout_image_ids().await;
 	let data = select(|s| s.data.clone()).await;
 	let (_editor_plugins, app_block, image_metadatas, level_block, mut event_block) = data.to_stable()?;
 	let project = selectors::get_project().await;
@@ -104,21 +105,34 @@ pub async fn play_project(debug: bool) -> Result<u32> {
 		// Needed for debug builds to find on-disk runtime plugins
 		fs::write(runtime_dir.join("Construct.ini"), game.construct_ini().await?).await?;
 	}
-	patched_image_block.write_to_pe(&output_path)?;
-	app_block.write_to_pe(&output_path)?;
-	event_block.write_to_pe(&output_path)?;
-	level_block.write_to_pe(&output_path)?;
-	Ok(crate::run_game(&output_path).await?)
+	// stage everything so the executable is only rewritten once
+	let mut resources = PeResourceSet::new();
+	resources
+		.stage_block(&patched_image_block)?
+		.stage_block(&app_block)?
+		.stage_block(&event_block)?
+		.stage_block(&level_block)?;
+	if let Some(project) = &project {
+		if !project.added_plugins.is_empty() {
+			let plugins = thunks::read_added_plugins(&project.added_plugins).await?;
+			let first_id = thunks::first_added_plugin_id(&game).await?;
+			if debug {
+				crate::stage_debug_build_plugins(&mut resources, &game, first_id, &plugins).await?;
+			} else {
+				resources.stage_dllblock_plugins(first_id, &plugins)?;
+			}
+		}
+		for resource in &project.resources {
+			// the project info may have been edited since it was loaded
+			resource.validate()?;
+			let bin = fs::read(project.dir_path()?.join(&resource.file)).await?;
+			resources.stage(resource.res_type.clone(), resource.name.clone(), resource.language, bin);
+		}
+	}
+	blocking!(@(output_path) resources.commit(&output_path)).await??;
+	Ok(thunks::run_game(&output_path, &crate::mod_log_dir_path(&unique_name)).await?)
 }
 
-pub async fn wait_until_process_exits(pid: u32) -> Result<()> {
-	#[cfg(not(windows))]
-	{
-		// TODO: implement for linux
-		return Ok(());
-	}
-	#[cfg(windows)]
-	tokio::task::spawn_blocking(move || {
-		towermod_win32::process::wait_until_process_exits(pid)
-	}).await?
+pub async fn wait_until_process_exits(pid: u32) -> Result<Option<crate::GameExit>> {
+	crate::wait_until_process_exits(pid).await
 }
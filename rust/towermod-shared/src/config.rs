@@ -4,14 +4,42 @@ use fs_err::tokio as fs;
 use tracing::instrument;
 use anyhow::Result;
 use std::path::{Path, PathBuf};
+use std::sync::{LazyLock, RwLock};
 use towermod_util::log_on_error;
 use directories::{BaseDirs, UserDirs};
 
 use crate::dllreader_client;
 
-/// Directory where cached data is stored
-/// Anything here should be safe to delete without data loss
-pub fn get_cache_dir_path() -> PathBuf {
+/// Name of the file that enables portable mode when placed next to the towermod executable
+pub const PORTABLE_MARKER_FILE_NAME: &str = "towermod-portable";
+
+/// Paths configured in `TowermodConfig` that replace the default locations
+#[derive(Debug, Clone, Default)]
+pub struct PathOverrides {
+	pub cache_dir: Option<PathBuf>,
+	pub towerclimb_appdata_dir: Option<PathBuf>,
+}
+
+static PATH_OVERRIDES: RwLock<PathOverrides> = RwLock::new(PathOverrides { cache_dir: None, towerclimb_appdata_dir: None });
+
+pub fn set_path_overrides(overrides: PathOverrides) {
+	*PATH_OVERRIDES.write().unwrap() = overrides;
+}
+
+/// In portable mode, the directory next to the executable where all towermod data is kept
+pub fn get_portable_dir_path() -> Option<PathBuf> {
+	static PORTABLE_DIR: LazyLock<Option<PathBuf>> = LazyLock::new(|| {
+		let exe_dir = std::env::current_exe().ok()?.parent()?.to_path_buf();
+		exe_dir.join(PORTABLE_MARKER_FILE_NAME).exists().then(|| exe_dir.join("towermod-data"))
+	});
+	PORTABLE_DIR.clone()
+}
+
+/// Directory where cached data is stored, unless overridden in the config
+fn get_default_cache_dir_path() -> PathBuf {
+	if let Some(portable_dir) = get_portable_dir_path() {
+		return portable_dir.join("cache")
+	}
 	let base_dirs = BaseDirs::new().unwrap();
 	if cfg!(windows) {
 		return base_dirs.cache_dir().join("towermod/cache")
@@ -20,14 +48,42 @@ pub fn get_cache_dir_path() -> PathBuf {
 	}
 }
 
+/// Directory where cached data is stored
+/// Anything here should be safe to delete without data loss
+pub fn get_cache_dir_path() -> PathBuf {
+	match &PATH_OVERRIDES.read().unwrap().cache_dir {
+		Some(cache_dir) => cache_dir.clone(),
+		None => get_default_cache_dir_path(),
+	}
+}
+
+/// Location of the config file, which is not affected by the cache dir override
+pub fn get_config_path() -> PathBuf {
+	match get_portable_dir_path() {
+		Some(portable_dir) => portable_dir.join("towermod-config.toml"),
+		None => get_default_cache_dir_path().join("towermod-config.toml"),
+	}
+}
+
+/// Location towermod is linked to by [`first_time_setup`], which file associations and the URL handler point at
 pub fn get_stable_exe_path() -> PathBuf {
+	// portable installs are not registered under a stable path
+	if get_portable_dir_path().is_some() {
+		if let Ok(exe_path) = std::env::current_exe() {
+			return exe_path
+		}
+	}
 	let base_dirs = BaseDirs::new().unwrap();
-	base_dirs.data_local_dir().join("towermod/towermod.exe")
+	let file_name = if cfg!(windows) { "towermod.exe" } else { "towermod" };
+	base_dirs.data_local_dir().join("towermod").join(file_name)
 }
 
 
 /// Get the AppData directory where TowerClimb stores its savedata and settings
 pub fn get_towerclimb_appdata_dir_path() -> PathBuf {
+	if let Some(appdata_dir) = &PATH_OVERRIDES.read().unwrap().towerclimb_appdata_dir {
+		return appdata_dir.clone()
+	}
 	#[cfg(windows)]
 	unsafe {
 		use windows::Win32::UI::Shell;
@@ -37,14 +93,19 @@ pub fn get_towerclimb_appdata_dir_path() -> PathBuf {
 	}
 	#[cfg(not(windows))]
 	{
-		// BUG: make this customizable
+		if let Some(compat_data_path) = crate::find_towerclimb().and_then(|app| app.compat_data_path()) {
+			return compat_data_path.join("pfx/drive_c/users/steamuser/AppData/Roaming");
+		}
 		let base_dirs = BaseDirs::new().unwrap();
-		return base_dirs.data_local_dir().join("Steam/steamapps/steamapps/compatdata/396640/pfx/drive_c/users/steamuser/AppData/Roaming");
+		return base_dirs.data_local_dir().join(format!("Steam/steamapps/compatdata/{}/pfx/drive_c/users/steamuser/AppData/Roaming", crate::TOWERCLIMB_APP_ID));
 	}
 }
 
 /// Root directory for all towermod data & configuration
 pub fn get_towermod_dir_path() -> PathBuf {
+	if let Some(portable_dir) = get_portable_dir_path() {
+		return portable_dir
+	}
 	let user_dirs = UserDirs::new().unwrap();
 	user_dirs.document_dir().unwrap().join("towermod")
 }
@@ -64,6 +125,13 @@ pub fn mod_runtime_dir_path(mod_name: impl AsRef<Path>) -> PathBuf {
 	dir
 }
 
+/// Directory where the output of each game session is logged while running the mod
+pub fn mod_log_dir_path(mod_name: impl AsRef<Path>) -> PathBuf {
+	let mut dir = mod_cache_dir_path(mod_name);
+	dir.push("logs");
+	dir
+}
+
 
 /// Subdirectory where installed mods are stored
 pub fn get_mods_dir_path() -> PathBuf {
@@ -72,6 +140,11 @@ pub fn get_mods_dir_path() -> PathBuf {
 	path
 }
 
+/// User additions to the database of known game versions, see [`crate::KnownGames`]
+pub fn get_known_games_path() -> PathBuf {
+	get_towermod_dir_path().join("known_games.toml")
+}
+
 /// Default suggested directory for newly created projects
 pub fn get_default_project_dir_path() -> PathBuf {
 	let mut path = get_towermod_dir_path();
@@ -81,25 +154,16 @@ pub fn get_default_project_dir_path() -> PathBuf {
 
 /// Uses "dumb" heuristics to try to find the TowerClimb game executable, if it can
 pub fn try_find_towerclimb() -> Result<PathBuf> {
-	let mut path: PathBuf;
-	#[cfg(windows)]
-	{
-		let program_files_x86 = unsafe {
-			use windows::Win32::{Foundation::HANDLE, UI::Shell};
-			Shell::SHGetKnownFolderPath(&Shell::FOLDERID_ProgramFilesX86, Shell::KNOWN_FOLDER_FLAG(0), HANDLE::default())?.to_string()?
-		};
-		path = [&*program_files_x86, r"Steam\steamapps\common\TowerClimb"].iter().collect();
-	}
-	#[cfg(not(windows))]
-	{
-		let base_dirs = BaseDirs::new().unwrap();
-		path = base_dirs.data_local_dir().join("Steam/steamapps/common/TowerClimb");
-	}
+	// prefer the install location recorded by Steam, in any of its library folders
+	let path = crate::find_towerclimb().map(|app| app.install_path).or_else(|| {
+		crate::steam_dir_candidates().into_iter()
+			.map(|steam_path| steam_path.join("steamapps/common/TowerClimb"))
+			.find(|path| path.is_dir())
+	});
 	// give up if installion dir doesn't exist
-	if !path.is_dir() {
-		// TODO: also search null/SteamLibrary on other drives
+	let Some(mut path) = path else {
 		anyhow::bail!("Could not find TowerClimb executable");
-	}
+	};
 
 	// look for exe by exact name
 	path.push("TowerClimb_V1_Steam4.exe");
@@ -109,7 +173,7 @@ pub fn try_find_towerclimb() -> Result<PathBuf> {
 
 	// otherwise look for an exe containing the word "TowerClimb" (case-insensitive)
 	path.pop();
-	let pattern = glob::Pattern::escape(&*path.to_string_lossy()) + r"\*towerclimb*.exe";
+	let pattern = glob::Pattern::escape(&*path.to_string_lossy()) + std::path::MAIN_SEPARATOR_STR + "*towerclimb*.exe";
 
 	match glob::glob_with(&pattern, MATCH_OPTIONS).unwrap().next() {
 		Some(r) => Ok(r.map_err(|e| e.into_error())?),
@@ -124,13 +188,17 @@ pub async fn first_time_setup() -> Result<()> {
 	fs::create_dir_all(get_mods_dir_path()).await?;
 	fs::create_dir_all(get_cache_dir_path()).await?;
 
-	#[cfg(windows)]
-	{
-		let stable_exe_path = crate::get_stable_exe_path();
-		let stable_exe_path = stable_exe_path.to_string_lossy();
-		towermod_win32::registry::initialize_registry_settings(&stable_exe_path)?;
+	// portable installs leave the system untouched
+	if get_portable_dir_path().is_none() {
+		#[cfg(windows)]
+		{
+			let stable_exe_path = crate::get_stable_exe_path();
+			let stable_exe_path = stable_exe_path.to_string_lossy();
+			towermod_win32::registry::initialize_registry_settings(&stable_exe_path)?;
+		}
+		#[cfg(not(windows))]
+		crate::install_desktop_integration(&crate::get_stable_exe_path(), &Default::default())?;
 	}
-	// TODO: register URL handling and file associations on Linux as well
 
 	log_on_error(dllreader_client::extract_dllreader().await);
 
@@ -138,14 +206,19 @@ pub async fn first_time_setup() -> Result<()> {
 }
 
 async fn link_towermod_to_stable_path() -> Result<()> {
-	// Hardlink Towermod to a stable path
 	// TODO: Don't copy if the version has not changed
-	let exe_path = std::env::current_exe()?;
-	let dest_path = get_stable_exe_path();
+	link_to_stable_path(&std::env::current_exe()?, &get_stable_exe_path()).await
+}
+
+/// Hardlink an executable to `dest_path`, or copy it if it can't be linked (e.g. across filesystems)
+pub async fn link_to_stable_path(exe_path: &Path, dest_path: &Path) -> Result<()> {
 	if exe_path == dest_path { return Ok(()) }
-	let _ = fs::remove_file(&dest_path).await;
-	if let Err(_) = fs::hard_link(&exe_path, &dest_path).await {
-		log_on_error(fs::copy(&exe_path, &dest_path).await);
+	if let Some(parent) = dest_path.parent() {
+		fs::create_dir_all(parent).await?;
+	}
+	let _ = fs::remove_file(dest_path).await;
+	if fs::hard_link(exe_path, dest_path).await.is_err() {
+		fs::copy(exe_path, dest_path).await?;
 	}
 	Ok(())
 }
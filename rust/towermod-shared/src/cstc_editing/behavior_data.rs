@@ -0,0 +1,21 @@
+//! Resolving the plugin of a behavior to decode its `data`
+use towermod_cstc as cstc;
+use super::CstcData;
+
+impl CstcData {
+	/// Name of the movement plugin a behavior belongs to (`new_index`)
+	pub fn behavior_plugin_name(&self, behavior: &cstc::Behavior) -> Option<&str> {
+		self.editor_plugins.get(&behavior.new_index).map(|p| p.string_table.name.as_str())
+	}
+
+	pub fn decode_behavior_data(&self, behavior: &cstc::Behavior) -> cstc::BehaviorData {
+		let plugin_name = self.behavior_plugin_name(behavior).unwrap_or_default();
+		cstc::BehaviorData::decode(behavior.data.clone(), plugin_name)
+	}
+
+	/// Whether `data` is the variant of [`cstc::BehaviorData`] used by the behavior's plugin
+	pub fn behavior_data_matches(&self, behavior: &cstc::Behavior, data: &cstc::BehaviorData) -> bool {
+		let plugin_name = self.behavior_plugin_name(behavior).unwrap_or_default();
+		data.is_for_plugin(plugin_name)
+	}
+}
@@ -0,0 +1,134 @@
+//! Adding, removing and reordering the behaviors of an object type.
+//!
+//! A behavior's `new_index` is the ID of its movement plugin and `mov_index` is its position among the behaviors of
+//! its object type, which is what `movement_id` of conditions and actions refers to.
+use towermod_cstc as cstc;
+use super::CstcData;
+
+impl CstcData {
+	/// Behaviors of an object type, ordered by `mov_index`
+	pub fn object_type_behaviors(&self, object_type_id: i32) -> Vec<&cstc::Behavior> {
+		let mut behaviors = self.behaviors.iter().filter(|b| b.object_type_id == object_type_id).collect::<Vec<_>>();
+		behaviors.sort_by_key(|b| b.mov_index);
+		behaviors
+	}
+
+	pub fn new_behavior_index(&self, object_type_id: i32) -> i32 {
+		self.behaviors.iter()
+			.filter(|b| b.object_type_id == object_type_id)
+			.map(|b| b.mov_index + 1)
+			.max()
+			.unwrap_or(0)
+	}
+
+	/// Data for a new behavior of a movement plugin, copied from an existing behavior of the same plugin.
+	/// No plugin's behavior data is fully decoded, so there are no known defaults to start from.
+	pub fn new_behavior_data(&self, plugin_id: i32) -> Option<Vec<u8>> {
+		self.behaviors.iter().find(|b| b.new_index == plugin_id).map(|b| b.data.clone())
+	}
+
+	/// Append a behavior with the data of [`CstcData::new_behavior_data`].
+	/// Returns `None` if the object type doesn't exist, or there is no behavior of the plugin to copy.
+	pub fn add_behavior(&mut self, object_type_id: i32, plugin_id: i32, name: String) -> Option<i32> {
+		if !self.object_types.contains_key(&object_type_id) { return None }
+		let data = self.new_behavior_data(plugin_id)?;
+		let mov_index = self.new_behavior_index(object_type_id);
+		self.behaviors.push(cstc::Behavior {
+			object_type_id,
+			new_index: plugin_id,
+			mov_index,
+			name,
+			data,
+			descriptors: None,
+		});
+		Some(mov_index)
+	}
+
+	/// Remove a behavior, shifting the ones after it down.
+	/// Fails if any conditions or actions still refer to it.
+	pub fn remove_behavior(&mut self, object_type_id: i32, mov_index: i32) -> bool {
+		if self.behavior_event_references(object_type_id, mov_index) > 0 { return false }
+		let Some(index) = self.behaviors.iter().position(|b| b.object_type_id == object_type_id && b.mov_index == mov_index) else { return false };
+		self.behaviors.remove(index);
+		self.remap_movement_ids(object_type_id, |id| if id > mov_index { id - 1 } else { id });
+		true
+	}
+
+	/// Move a behavior to `position` among the behaviors of its object type, updating event references
+	pub fn move_behavior(&mut self, object_type_id: i32, mov_index: i32, position: usize) -> bool {
+		let mut order = self.object_type_behaviors(object_type_id).iter().map(|b| b.mov_index).collect::<Vec<_>>();
+		let Some(from) = order.iter().position(|i| *i == mov_index) else { return false };
+		let id = order.remove(from);
+		order.insert(position.min(order.len()), id);
+
+		let new_index = |old: i32| order.iter().position(|i| *i == old).map_or(old, |i| i as i32);
+		self.remap_movement_ids(object_type_id, new_index);
+		// keep the object type's behaviors in index order, without moving those of other object types
+		let slots = self.behaviors.iter().enumerate().filter(|(_, b)| b.object_type_id == object_type_id).map(|(i, _)| i).collect::<Vec<_>>();
+		let mut sorted = slots.iter().map(|i| self.behaviors[*i].clone()).collect::<Vec<_>>();
+		sorted.sort_by_key(|b| b.mov_index);
+		for (slot, b) in slots.into_iter().zip(sorted) {
+			self.behaviors[slot] = b;
+		}
+		true
+	}
+
+	/// Number of conditions and actions that use a behavior
+	pub fn behavior_event_references(&self, object_type_id: i32, mov_index: i32) -> usize {
+		let mut count = 0;
+		if let Some(event_block) = &self.event_block {
+			for sheet in &event_block.layout_sheets {
+				visit_events(sheet, &mut |e| {
+					count += e.conditions.iter().filter(|c| c.object_id == object_type_id && c.movement_id == mov_index).count();
+					count += e.actions.iter().filter(|a| a.object_id == object_type_id && a.movement_id == mov_index).count();
+				});
+			}
+		}
+		count
+	}
+
+	/// Apply `f` to every reference to a behavior index of an object type: the behaviors' own `mov_index`,
+	/// and the `movement_id` of conditions and actions in every event sheet, including groups and sub-events.
+	fn remap_movement_ids(&mut self, object_type_id: i32, f: impl Fn(i32) -> i32) {
+		for b in self.behaviors.iter_mut().filter(|b| b.object_type_id == object_type_id) {
+			b.mov_index = f(b.mov_index);
+		}
+		let Some(event_block) = &mut self.event_block else { return };
+		for sheet in &mut event_block.layout_sheets {
+			visit_events_mut(sheet, &mut |e| {
+				for c in e.conditions.iter_mut().filter(|c| c.object_id == object_type_id && c.movement_id >= 0) {
+					c.movement_id = f(c.movement_id);
+				}
+				for a in e.actions.iter_mut().filter(|a| a.object_id == object_type_id && a.movement_id >= 0) {
+					a.movement_id = f(a.movement_id);
+				}
+			});
+		}
+	}
+}
+
+fn visit_events(events: &[cstc::SomeEvent], f: &mut impl FnMut(&cstc::Event)) {
+	for event in events {
+		match event {
+			cstc::SomeEvent::Event(e) => {
+				f(e);
+				visit_events(&e.events, f);
+			},
+			cstc::SomeEvent::EventGroup(g) => visit_events(&g.events, f),
+			cstc::SomeEvent::EventInclude(_) => {},
+		}
+	}
+}
+
+fn visit_events_mut(events: &mut [cstc::SomeEvent], f: &mut impl FnMut(&mut cstc::Event)) {
+	for event in events {
+		match event {
+			cstc::SomeEvent::Event(e) => {
+				f(e);
+				visit_events_mut(&mut e.events, f);
+			},
+			cstc::SomeEvent::EventGroup(g) => visit_events_mut(&mut g.events, f),
+			cstc::SomeEvent::EventInclude(_) => {},
+		}
+	}
+}
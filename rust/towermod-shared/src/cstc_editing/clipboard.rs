@@ -0,0 +1,108 @@
+//! Portable representation of copied object instances
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use serde_alias::serde_alias;
+use towermod_cstc as cstc;
+use super::{CstcData, EdObjectInstance, VariableType, VariableValue};
+
+/// A selection of object instances that can be pasted into another layer, layout, or project.
+/// Object types are referenced by name rather than by id, since ids are not stable between projects.
+#[serde_alias(SnakeCase)]
+#[derive(Default, Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InstanceClipboard {
+	pub instances: Vec<ClipboardInstance>,
+}
+
+#[serde_alias(SnakeCase)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClipboardInstance {
+	pub object_type_name: String,
+	pub plugin_name: String,
+	pub instance: EdObjectInstance,
+}
+
+#[serde_alias(SnakeCase)]
+#[derive(Default, Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PasteResult {
+	/// IDs of the newly created instances
+	pub ids: Vec<i32>,
+	/// Names of object types that could not be found in the target project, that use a different plugin there,
+	/// or that are sprites without an instance in the target project to take their animations from
+	pub missing_object_types: Vec<String>,
+}
+
+impl InstanceClipboard {
+	/// Copy the given instances, in the order they appear in their layers.
+	///
+	/// Fails if any of them has an object type or plugin that isn't loaded, rather than leaving it out of the clipboard.
+	pub fn copy(data: &CstcData, ids: &[i32]) -> Result<Self> {
+		let mut instances = Vec::new();
+		let mut unknown: Vec<i32> = Vec::new();
+		for o in data.layouts.iter().flat_map(|l| l.layers.iter().flat_map(|l| l.objects.iter())).filter(|o| ids.contains(&o.id)) {
+			let object_type = data.object_types.get(&o.object_type_id);
+			let plugin = object_type.and_then(|t| data.editor_plugins.get(&t.plugin_id));
+			let (Some(object_type), Some(plugin)) = (object_type, plugin) else {
+				unknown.push(o.id);
+				continue
+			};
+			instances.push(ClipboardInstance {
+				object_type_name: object_type.name.clone(),
+				plugin_name: plugin.string_table.name.clone(),
+				instance: o.clone(),
+			});
+		}
+		if !unknown.is_empty() {
+			let unknown = unknown.iter().map(|id| id.to_string()).collect::<Vec<_>>().join(", ");
+			anyhow::bail!("Cannot copy instances {unknown}, their object type or plugin is not loaded");
+		}
+		Ok(InstanceClipboard { instances })
+	}
+
+	/// Remap copied instances onto the object types of `data`, assigning sequential ids starting from `first_id`.
+	/// Returns the pasted instances and the names of the object types that had to be skipped.
+	pub fn paste(self, data: &CstcData, first_id: i32, offset_x: i32, offset_y: i32) -> (Vec<EdObjectInstance>, Vec<String>) {
+		let mut missing_object_types: Vec<String> = Vec::new();
+		let mut instances = Vec::with_capacity(self.instances.len());
+		let mut id = first_id;
+		for ClipboardInstance { object_type_name, plugin_name, mut instance } in self.instances {
+			let object_type = data.object_types.values()
+				.find(|o| o.name == object_type_name)
+				.filter(|o| data.editor_plugins.get(&o.plugin_id).is_some_and(|p| p.string_table.name == plugin_name));
+			// Animation ids are not portable between projects. Every instance of a sprite object type shares its root animation,
+			// which can only be found through the existing instances.
+			let object_type = object_type.filter(|object_type| {
+				let cstc::ObjectData::Sprite(sprite) = &mut instance.data else { return true };
+				let animation_id = data.layouts.iter()
+					.flat_map(|l| l.layers.iter().flat_map(|l| l.objects.iter()))
+					.filter(|o| o.object_type_id == object_type.id)
+					.find_map(|o| if let cstc::ObjectData::Sprite(data) = &o.data { Some(data.animation) } else { None })
+					.filter(|id| data.animations.contains_key(id));
+				animation_id.inspect(|id| sprite.animation = *id).is_some()
+			});
+			let Some(object_type) = object_type else {
+				if !missing_object_types.contains(&object_type_name) { missing_object_types.push(object_type_name) }
+				continue
+			};
+
+			instance.id = id;
+			id += 1;
+			instance.object_type_id = object_type.id;
+			instance.x += offset_x;
+			instance.y += offset_y;
+
+			// Match the private variables declared on the target object type
+			let mut private_variables = std::mem::take(&mut instance.private_variables);
+			instance.private_variables = object_type.private_variables.iter().map(|(name, value_type)| {
+				let value = private_variables.remove(name)
+					.filter(|v| matches!((value_type, v), (VariableType::Number, VariableValue::Number(_)) | (VariableType::String, VariableValue::String(_))))
+					.unwrap_or_else(|| (*value_type).into());
+				(name.clone(), value)
+			}).collect();
+			instances.push(instance);
+		}
+		(instances, missing_object_types)
+	}
+}
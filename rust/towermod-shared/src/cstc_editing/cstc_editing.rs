@@ -239,6 +239,32 @@ pub struct EdLayoutLayer {
 	pub objects: Vec<EdObjectInstance>,
 }
 impl EdLayoutLayer {
+	/// Empty layer with the same defaults the Construct Classic editor uses
+	pub fn new(id: i32, name: String) -> Self {
+		EdLayoutLayer {
+			id,
+			name,
+			layer_type: cstc::LayerType::Normal,
+			filter_color: 0xffffff,
+			opacity: 1.0,
+			angle: 0.0,
+			scroll_x_factor: 1.0,
+			scroll_y_factor: 1.0,
+			scroll_x: 0.0,
+			scroll_y: 0.0,
+			zoom_x_factor: 1.0,
+			zoom_y_factor: 1.0,
+			zoom_x: 1.0,
+			zoom_y: 1.0,
+			clear_background_color: false,
+			background_color: 0xffffff,
+			force_own_texture: false,
+			sampler: cstc::LayerSamplerMode::Default,
+			enable_3d: false,
+			clear_depth_buffer: false,
+			objects: Vec::new(),
+		}
+	}
 	fn from_stable(layer: cstc::LayoutLayer, object_types: &IndexMap<i32, EdObjectType>, plugins: &HashMap<i32, cstc::plugin::PluginData>) -> Result<Self> {
 		let cstc::LayoutLayer { id, name, layer_type, filter_color, opacity, angle, scroll_x_factor, scroll_y_factor, scroll_x, scroll_y, zoom_x_factor, zoom_y_factor, zoom_x, zoom_y, clear_background_color, background_color, force_own_texture, sampler, enable_3d, clear_depth_buffer, objects } = layer;
 		let objects = objects.into_iter()
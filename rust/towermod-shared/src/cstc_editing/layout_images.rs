@@ -0,0 +1,87 @@
+//! Recomputing `EdLayout::image_ids`, the set of textures the runtime preloads for each layout
+use std::collections::HashSet;
+use serde::{Deserialize, Serialize};
+use serde_alias::serde_alias;
+use towermod_cstc as cstc;
+use super::{CstcData, EdLayout, EdObjectInstance};
+
+#[serde_alias(SnakeCase)]
+#[derive(Default, Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LayoutImageIdsChange {
+	pub layout_name: String,
+	pub added: Vec<i32>,
+	pub removed: Vec<i32>,
+}
+
+impl CstcData {
+	/// Find the images that are used by instances placed on each layout but missing from its `image_ids`,
+	/// returning only the layouts that are missing any.
+	///
+	/// Nothing is ever removed here: objects that are only created by events (bullets, effects, spawned enemies) have
+	/// no instance on the layout, but still need their images preloaded.
+	pub fn compute_layout_image_ids(&self) -> Vec<LayoutImageIdsChange> {
+		let existing_images: HashSet<i32> = self.image_block.iter().map(|i| i.id).collect();
+		self.layouts.iter().filter_map(|layout| {
+			let required = self.layout_required_image_ids(layout, &existing_images);
+			let mut added = required.into_iter().filter(|id| !layout.image_ids.contains(id)).collect::<Vec<_>>();
+			if added.is_empty() { return None }
+			added.sort();
+			Some(LayoutImageIdsChange { layout_name: layout.name.clone(), added, removed: Vec::new() })
+		}).collect()
+	}
+
+	/// Find the images in each layout's `image_ids` that are animation frames or textures of decoded objects,
+	/// but not used by any instance placed on that layout.
+	///
+	/// These are only candidates for removal, since they may still belong to objects created by events.
+	/// Images that are neither are never included, since they may be referenced by plugin data that isn't fully decoded.
+	pub fn compute_unused_layout_image_ids(&self) -> Vec<LayoutImageIdsChange> {
+		let existing_images: HashSet<i32> = self.image_block.iter().map(|i| i.id).collect();
+		let mut known_images = HashSet::new();
+		for animation in self.animations.values() {
+			collect_frame_image_ids(animation, &mut known_images);
+		}
+		for instance in self.layouts.iter().flat_map(|l| l.layers.iter().flat_map(|l| l.objects.iter())) {
+			known_images.extend(texture_image_id(instance).filter(|id| existing_images.contains(id)));
+		}
+		self.layouts.iter().filter_map(|layout| {
+			let required = self.layout_required_image_ids(layout, &existing_images);
+			let removed = layout.image_ids.iter().copied().filter(|id| known_images.contains(id) && !required.contains(id)).collect::<Vec<_>>();
+			if removed.is_empty() { return None }
+			Some(LayoutImageIdsChange { layout_name: layout.name.clone(), added: Vec::new(), removed })
+		}).collect()
+	}
+
+	/// All animation frames of sprite instances and textures of other instances placed on `layout`
+	fn layout_required_image_ids(&self, layout: &EdLayout, existing_images: &HashSet<i32>) -> HashSet<i32> {
+		let mut image_ids = HashSet::new();
+		let mut visited_animations = HashSet::new();
+		for instance in layout.layers.iter().flat_map(|l| l.objects.iter()) {
+			image_ids.extend(texture_image_id(instance).filter(|id| existing_images.contains(id)));
+			let cstc::ObjectData::Sprite(sprite) = &instance.data else { continue };
+			if !visited_animations.insert(sprite.animation) { continue }
+			if let Some(animation) = self.animations.get(&sprite.animation) {
+				collect_frame_image_ids(animation, &mut image_ids);
+			}
+		}
+		image_ids
+	}
+}
+
+/// Image used by a tiled background, panel or canvas
+fn texture_image_id(instance: &EdObjectInstance) -> Option<i32> {
+	match &instance.data {
+		cstc::ObjectData::TiledBackground(data) => Some(data.texture_id),
+		cstc::ObjectData::Panel(data) => Some(data.image_id),
+		cstc::ObjectData::Canvas(data) => Some(data.initial_image_id),
+		_ => None,
+	}
+}
+
+fn collect_frame_image_ids(animation: &cstc::Animation, image_ids: &mut HashSet<i32>) {
+	image_ids.extend(animation.frames.iter().map(|f| f.image_id));
+	for a in &animation.sub_animations {
+		collect_frame_image_ids(a, image_ids);
+	}
+}
@@ -1,2 +1,13 @@
 mod cstc_editing;
+mod clipboard;
+mod prefab;
+mod layout_images;
+mod spatial_index;
+mod behavior_data;
+mod behaviors;
+mod object_data;
 pub use cstc_editing::*;
+pub use clipboard::*;
+pub use prefab::*;
+pub use layout_images::*;
+pub use spatial_index::*;
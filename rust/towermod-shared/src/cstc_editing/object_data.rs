@@ -0,0 +1,22 @@
+//! Initial object data for new instances
+use towermod_cstc as cstc;
+use super::CstcData;
+
+impl CstcData {
+	/// Data for a new instance of an object type.
+	///
+	/// Plugins whose data is only partially decoded (see [`cstc::ObjectData::has_default`]) copy the data of an existing instance
+	/// of the same object type, or else of another object type using the same plugin.
+	/// Returns `None` if there is no such instance in the project.
+	pub fn new_object_data(&self, object_type_id: i32) -> Option<cstc::ObjectData> {
+		let object_type = self.object_types.get(&object_type_id)?;
+		let plugin_name = &self.editor_plugins.get(&object_type.plugin_id)?.string_table.name;
+		if cstc::ObjectData::has_default(plugin_name) {
+			return Some(cstc::ObjectData::new(plugin_name))
+		}
+		let instances = || self.layouts.iter().flat_map(|l| l.layers.iter().flat_map(|l| l.objects.iter()));
+		instances().find(|o| o.object_type_id == object_type_id)
+			.or_else(|| instances().find(|o| self.object_types.get(&o.object_type_id).is_some_and(|t| t.plugin_id == object_type.plugin_id)))
+			.map(|o| o.data.clone())
+	}
+}
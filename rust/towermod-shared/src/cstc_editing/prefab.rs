@@ -0,0 +1,259 @@
+//! Prefab packages for moving object types (along with everything they depend on) between projects
+use std::collections::HashMap;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_alias::serde_alias;
+use towermod_cstc as cstc;
+use super::{CstcData, EdContainer, EdFamily, EdObjectInstance, EdObjectType, VariableType};
+
+/// One or more object types with their animations, image metadata, behaviors, containers and family/trait memberships.
+/// Plugins, families and traits are referenced by name, everything else is re-numbered on import.
+#[serde_alias(SnakeCase)]
+#[derive(Default, Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Prefab {
+	pub object_types: Vec<PrefabObjectType>,
+	pub containers: Vec<EdContainer>,
+	pub families: Vec<PrefabFamily>,
+	pub images: Vec<cstc::ImageMetadata>,
+}
+
+#[serde_alias(SnakeCase)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PrefabObjectType {
+	pub object_type: EdObjectType,
+	pub plugin_name: String,
+	/// First instance of the object type. Sprites need at least one instance to own their animations.
+	pub instance: Option<EdObjectInstance>,
+	/// Root animation of a sprite
+	pub animation: Option<cstc::Animation>,
+	pub behaviors: Vec<PrefabBehavior>,
+	pub families: Vec<String>,
+	pub traits: Vec<String>,
+}
+
+#[serde_alias(SnakeCase)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PrefabBehavior {
+	/// Name of the movement plugin referenced by `new_index`.
+	/// Prefabs are never exported without it, but importing one that lacks it fails.
+	pub plugin_name: Option<String>,
+	pub behavior: cstc::Behavior,
+}
+
+#[serde_alias(SnakeCase)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PrefabFamily {
+	pub name: String,
+	pub private_variables: HashMap<String, VariableType>,
+}
+
+/// First free ids in the target project
+#[derive(Debug, Clone, Copy)]
+pub struct PrefabIds {
+	pub object_type_id: i32,
+	pub object_instance_id: i32,
+	pub animation_id: i32,
+	pub image_id: i32,
+}
+
+#[serde_alias(SnakeCase)]
+#[derive(Default, Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PrefabImport {
+	/// IDs of the imported object types in the target project
+	pub object_type_ids: Vec<i32>,
+	/// Old image ID -> new image ID
+	pub image_ids: HashMap<i32, i32>,
+}
+
+impl Prefab {
+	pub fn export(data: &CstcData, object_type_ids: &[i32]) -> Result<Self> {
+		let plugin_name = |plugin_id: i32| -> Result<String> {
+			Ok(data.editor_plugins.get(&plugin_id).context("Plugin not found")?.string_table.name.clone())
+		};
+		let mut prefab = Prefab::default();
+		let mut image_ids = Vec::new();
+		for id in object_type_ids {
+			let object_type = data.object_types.get(id).context("Object type not found")?.clone();
+			let instance = data.layouts.iter()
+				.flat_map(|l| l.layers.iter().flat_map(|l| l.objects.iter()))
+				.find(|o| o.object_type_id == *id)
+				.cloned();
+			let animation = match &instance {
+				Some(EdObjectInstance { data: cstc::ObjectData::Sprite(sprite), .. }) => data.animations.get(&sprite.animation).cloned(),
+				_ => None,
+			};
+			if let Some(animation) = &animation {
+				visit_animations(animation, &mut |a| image_ids.extend(a.frames.iter().map(|f| f.image_id)));
+			}
+			let behaviors = data.behaviors.iter()
+				.filter(|b| b.object_type_id == *id)
+				.map(|b| Ok(PrefabBehavior {
+					plugin_name: Some(plugin_name(b.new_index).with_context(|| format!("Cannot export behavior {} of {}", b.name, object_type.name))?),
+					behavior: b.clone(),
+				}))
+				.collect::<Result<_>>()?;
+			let families = data.families.iter().filter(|f| f.object_type_ids.contains(id)).map(|f| f.name.clone()).collect();
+			let traits = data.traits.iter().filter(|t| t.object_type_ids.contains(id)).map(|t| t.name.clone()).collect();
+			prefab.object_types.push(PrefabObjectType {
+				plugin_name: plugin_name(object_type.plugin_id)?,
+				object_type,
+				instance,
+				animation,
+				behaviors,
+				families,
+				traits,
+			});
+		}
+
+		prefab.families = data.families.iter()
+			.filter(|f| f.object_type_ids.iter().any(|id| object_type_ids.contains(id)))
+			.map(|f| PrefabFamily { name: f.name.clone(), private_variables: f.private_variables.clone() })
+			.collect();
+		// Only keep container members that are part of the prefab
+		prefab.containers = data.containers.values()
+			.filter(|c| object_type_ids.contains(&c.id))
+			.map(|c| EdContainer { id: c.id, object_ids: c.object_ids.iter().copied().filter(|id| object_type_ids.contains(id)).collect() })
+			.filter(|c| !c.object_ids.is_empty())
+			.collect();
+		prefab.images = data.image_block.iter().filter(|i| image_ids.contains(&i.id)).cloned().collect();
+		Ok(prefab)
+	}
+
+	/// Assign new ids (starting from `ids`) and resolve plugin references against `data`.
+	/// The result can then be merged into `data` with [`Prefab::merge_into`].
+	pub fn remap(mut self, data: &CstcData, ids: PrefabIds) -> Result<(Self, PrefabImport)> {
+		let plugin_id = |name: &str| -> Result<i32> {
+			data.editor_plugins.iter()
+				.find(|(_, p)| p.string_table.name == name)
+				.map(|(id, _)| *id)
+				.with_context(|| format!("Plugin {name} is not available in this project"))
+		};
+		let PrefabIds { object_type_id: mut next_object_type_id, object_instance_id: mut next_instance_id, animation_id: mut next_animation_id, image_id: mut next_image_id } = ids;
+
+		let object_type_ids: HashMap<i32, i32> = self.object_types.iter().map(|o| {
+			let id = next_object_type_id;
+			next_object_type_id += 1;
+			(o.object_type.id, id)
+		}).collect();
+		let mut image_ids = HashMap::new();
+		for image in &mut self.images {
+			image_ids.insert(image.id, next_image_id);
+			image.id = next_image_id;
+			next_image_id += 1;
+		}
+
+		let mut taken_names: Vec<String> = data.object_types.values().map(|o| o.name.clone()).collect();
+		for o in &mut self.object_types {
+			o.object_type.id = object_type_ids[&o.object_type.id];
+			o.object_type.plugin_id = plugin_id(&o.plugin_name)?;
+			o.object_type.name = unique_name(&o.object_type.name, &taken_names);
+			taken_names.push(o.object_type.name.clone());
+
+			if let Some(animation) = &mut o.animation {
+				let mut missing_image_id = None;
+				visit_animations_mut(animation, &mut |a| {
+					a.id = next_animation_id;
+					next_animation_id += 1;
+					for frame in &mut a.frames {
+						match image_ids.get(&frame.image_id) {
+							Some(id) => frame.image_id = *id,
+							None => missing_image_id = Some(frame.image_id),
+						}
+					}
+				});
+				if let Some(id) = missing_image_id {
+					anyhow::bail!("Prefab is missing image {id} used by the animations of {}", o.object_type.name);
+				}
+			}
+			if let Some(instance) = &mut o.instance {
+				instance.id = next_instance_id;
+				next_instance_id += 1;
+				instance.object_type_id = o.object_type.id;
+				if let cstc::ObjectData::Sprite(sprite) = &mut instance.data {
+					if let Some(animation) = &o.animation {
+						sprite.animation = animation.id;
+					}
+				}
+			}
+			for b in &mut o.behaviors {
+				b.behavior.object_type_id = o.object_type.id;
+				// the plugin index of the source project would point at an unrelated plugin here
+				let plugin_name = b.plugin_name.as_ref().with_context(|| format!("Prefab does not name the movement plugin of behavior {} of {}", b.behavior.name, o.object_type.name))?;
+				b.behavior.new_index = plugin_id(plugin_name)?;
+			}
+		}
+		for c in &mut self.containers {
+			for id in std::iter::once(&mut c.id).chain(c.object_ids.iter_mut()) {
+				*id = *object_type_ids.get(&*id).context("Container references an object type that is not part of the prefab")?;
+			}
+		}
+
+		let import = PrefabImport { object_type_ids: self.object_types.iter().map(|o| o.object_type.id).collect(), image_ids };
+		Ok((self, import))
+	}
+
+	/// Add a remapped prefab to `data`, placing object type instances on `layout_layer_id`
+	pub fn merge_into(self, data: &mut CstcData, layout_layer_id: i32) {
+		let Prefab { object_types, containers, families, images } = self;
+		for family in families {
+			if !data.families.iter().any(|f| f.name == family.name) {
+				data.families.push(EdFamily { name: family.name, object_type_ids: Vec::new(), private_variables: family.private_variables });
+			}
+		}
+		let mut instances = Vec::new();
+		for o in object_types {
+			let id = o.object_type.id;
+			data.object_types.insert(id, o.object_type);
+			if let Some(animation) = o.animation {
+				data.animations.insert(animation.id, animation);
+			}
+			instances.extend(o.instance);
+			data.behaviors.extend(o.behaviors.into_iter().map(|b| b.behavior));
+			for name in o.families {
+				if let Some(family) = data.families.iter_mut().find(|f| f.name == name) {
+					family.object_type_ids.push(id);
+				}
+			}
+			for name in o.traits {
+				match data.traits.iter_mut().find(|t| t.name == name) {
+					Some(t) => t.object_type_ids.push(id),
+					None => data.traits.push(cstc::ObjectTrait { name, object_type_ids: vec![id] }),
+				}
+			}
+		}
+		let layer = data.layouts.iter_mut()
+			.flat_map(|l| l.layers.iter_mut())
+			.find(|l| l.id == layout_layer_id);
+		if let Some(layer) = layer {
+			layer.objects.extend(instances);
+		}
+		for c in containers {
+			data.containers.insert(c.id, c);
+		}
+		data.image_block.extend(images);
+	}
+}
+
+fn unique_name(name: &str, taken: &[String]) -> String {
+	if !taken.iter().any(|n| n == name) { return name.to_string() }
+	(2..).map(|i| format!("{name}{i}")).find(|n| !taken.contains(n)).unwrap()
+}
+
+fn visit_animations(animation: &cstc::Animation, f: &mut impl FnMut(&cstc::Animation)) {
+	f(animation);
+	for a in &animation.sub_animations {
+		visit_animations(a, f);
+	}
+}
+
+fn visit_animations_mut(animation: &mut cstc::Animation, f: &mut impl FnMut(&mut cstc::Animation)) {
+	f(animation);
+	for a in &mut animation.sub_animations {
+		visit_animations_mut(a, f);
+	}
+}
@@ -0,0 +1,236 @@
+//! Uniform grid index of object instances for hit-testing and region queries in the layout editor
+use std::collections::{HashMap, HashSet};
+use towermod_cstc as cstc;
+use super::{CstcData, EdLayoutLayer, EdObjectInstance};
+
+const CELL_SIZE: f32 = 128.0;
+
+#[derive(Debug, Default, Clone)]
+pub struct SpatialIndex {
+	layers: HashMap<i32, LayerIndex>,
+	/// Instance ID -> layer ID
+	instance_layers: HashMap<i32, i32>,
+}
+
+#[derive(Debug, Default, Clone)]
+struct LayerIndex {
+	/// Instances by the cells their bounding box overlaps
+	cells: HashMap<(i32, i32), Vec<i32>>,
+	/// Instances by the cell their position (hotspot) is in
+	points: HashMap<(i32, i32), Vec<i32>>,
+	instances: HashMap<i32, IndexedInstance>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct IndexedInstance {
+	/// Position in the layer's draw order
+	z: usize,
+	shape: InstanceShape,
+	min: (f32, f32),
+	max: (f32, f32),
+}
+
+/// Rotated rectangle of an instance, relative to its hotspot
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct InstanceShape {
+	pub x: f32,
+	pub y: f32,
+	pub width: f32,
+	pub height: f32,
+	/// Degrees, clockwise
+	pub angle: f32,
+	pub hotspot_x: f32,
+	pub hotspot_y: f32,
+}
+impl InstanceShape {
+	/// Corners in clockwise order starting from the top-left of the unrotated rectangle
+	pub fn corners(&self) -> [(f32, f32); 4] {
+		let (sin, cos) = self.angle.to_radians().sin_cos();
+		let (left, top) = (-self.hotspot_x, -self.hotspot_y);
+		let (right, bottom) = (left + self.width, top + self.height);
+		[(left, top), (right, top), (right, bottom), (left, bottom)].map(|(dx, dy)| {
+			(self.x + dx * cos - dy * sin, self.y + dx * sin + dy * cos)
+		})
+	}
+	pub fn contains(&self, x: f32, y: f32) -> bool {
+		// rotate the point into the instance's local space
+		let (sin, cos) = (-self.angle.to_radians()).sin_cos();
+		let (dx, dy) = (x - self.x, y - self.y);
+		let (lx, ly) = (dx * cos - dy * sin + self.hotspot_x, dx * sin + dy * cos + self.hotspot_y);
+		let (x0, x1) = (self.width.min(0.0), self.width.max(0.0));
+		let (y0, y1) = (self.height.min(0.0), self.height.max(0.0));
+		lx >= x0 && lx <= x1 && ly >= y0 && ly <= y1
+	}
+}
+
+impl SpatialIndex {
+	pub fn rebuild(&mut self, data: &CstcData) {
+		let hotspots = Hotspots::new(data);
+		self.layers.clear();
+		self.instance_layers.clear();
+		for layer in data.layouts.iter().flat_map(|l| l.layers.iter()) {
+			self.index_layer(layer, &hotspots);
+		}
+	}
+
+	/// Re-index only the layers that contain (or previously contained) the given instances
+	pub fn update_instances(&mut self, data: &CstcData, ids: &[i32]) {
+		let mut layer_ids: HashSet<i32> = ids.iter().filter_map(|id| self.instance_layers.get(id).copied()).collect();
+		for layer in data.layouts.iter().flat_map(|l| l.layers.iter()) {
+			if layer.objects.iter().any(|o| ids.contains(&o.id)) {
+				layer_ids.insert(layer.id);
+			}
+		}
+		self.update_layers(data, &layer_ids.into_iter().collect::<Vec<_>>());
+	}
+
+	/// Re-index the given layers, dropping any that no longer exist
+	pub fn update_layers(&mut self, data: &CstcData, layer_ids: &[i32]) {
+		let hotspots = Hotspots::new(data);
+		for id in layer_ids {
+			if let Some(old) = self.layers.remove(id) {
+				// instances that moved may already have been indexed on their new layer
+				for instance_id in old.instances.keys() {
+					if self.instance_layers.get(instance_id) == Some(id) {
+						self.instance_layers.remove(instance_id);
+					}
+				}
+			}
+			let layer = data.layouts.iter().flat_map(|l| l.layers.iter()).find(|l| l.id == *id);
+			if let Some(layer) = layer {
+				self.index_layer(layer, &hotspots);
+			}
+		}
+	}
+
+	fn index_layer(&mut self, layer: &EdLayoutLayer, hotspots: &Hotspots) {
+		let mut index = LayerIndex::default();
+		for (z, obj) in layer.objects.iter().enumerate() {
+			let shape = hotspots.shape(obj);
+			let corners = shape.corners();
+			let (mut min, mut max) = ((shape.x, shape.y), (shape.x, shape.y));
+			for (x, y) in corners {
+				min = (min.0.min(x), min.1.min(y));
+				max = (max.0.max(x), max.1.max(y));
+			}
+			for cell in cells_in(min, max) {
+				index.cells.entry(cell).or_default().push(obj.id);
+			}
+			index.points.entry(cell_of(shape.x, shape.y)).or_default().push(obj.id);
+			index.instances.insert(obj.id, IndexedInstance { z, shape, min, max });
+			self.instance_layers.insert(obj.id, layer.id);
+		}
+		self.layers.insert(layer.id, index);
+	}
+
+	/// Instances whose bounding box intersects the rectangle, in draw order
+	pub fn query_rect(&self, layer_id: i32, x: f32, y: f32, width: f32, height: f32) -> Vec<i32> {
+		let Some(layer) = self.layers.get(&layer_id) else { return vec![] };
+		let (min, max) = ((x.min(x + width), y.min(y + height)), (x.max(x + width), y.max(y + height)));
+		let mut found = layer.candidates(&layer.cells, cells_in(min, max))
+			.filter(|(_, o)| o.min.0 <= max.0 && o.max.0 >= min.0 && o.min.1 <= max.1 && o.max.1 >= min.1)
+			.collect::<Vec<_>>();
+		found.sort_by_key(|(_, o)| o.z);
+		found.into_iter().map(|(id, _)| id).collect()
+	}
+
+	/// Instances whose rotated rectangle contains the point, topmost first
+	pub fn query_point(&self, layer_id: i32, x: f32, y: f32) -> Vec<i32> {
+		let Some(layer) = self.layers.get(&layer_id) else { return vec![] };
+		let mut found = layer.candidates(&layer.cells, std::iter::once(cell_of(x, y)))
+			.filter(|(_, o)| o.shape.contains(x, y))
+			.collect::<Vec<_>>();
+		found.sort_by_key(|(_, o)| std::cmp::Reverse(o.z));
+		found.into_iter().map(|(id, _)| id).collect()
+	}
+
+	/// Instance whose position (hotspot) is closest to the point
+	pub fn query_nearest(&self, layer_id: i32, x: f32, y: f32) -> Option<i32> {
+		let layer = self.layers.get(&layer_id)?;
+		let (cx, cy) = cell_of(x, y);
+		// furthest ring that contains an instance
+		let max_ring = layer.points.keys().map(|(x, y)| (x - cx).abs().max((y - cy).abs())).max()?;
+		let mut best: Option<(i32, f32)> = None;
+		for ring in 0..=max_ring {
+			// rings are searched outwards, so stop once the closest point this ring could hold is further than the best match
+			if let Some((_, dist)) = best {
+				if dist <= ring_distance(x, y, ring) { break }
+			}
+			let cells = (cx - ring..=cx + ring).flat_map(|x| (cy - ring..=cy + ring).map(move |y| (x, y)))
+				.filter(|(x, y)| (x - cx).abs() == ring || (y - cy).abs() == ring);
+			for (id, o) in layer.candidates(&layer.points, cells) {
+				let dist = (o.shape.x - x).hypot(o.shape.y - y);
+				if best.is_none_or(|(best_id, d)| dist < d || (dist == d && o.z > layer.instances[&best_id].z)) {
+					best = Some((id, dist));
+				}
+			}
+		}
+		best.map(|(id, _)| id)
+	}
+}
+
+impl LayerIndex {
+	/// Unique instances registered in any of the given cells of `grid`
+	fn candidates<'a>(&'a self, grid: &'a HashMap<(i32, i32), Vec<i32>>, cells: impl Iterator<Item = (i32, i32)>) -> impl Iterator<Item = (i32, &'a IndexedInstance)> {
+		let mut seen = HashSet::new();
+		cells.filter_map(|cell| grid.get(&cell))
+			.flatten()
+			.filter(move |id| seen.insert(**id))
+			.filter_map(|id| Some((*id, self.instances.get(id)?)))
+	}
+}
+
+fn cell_of(x: f32, y: f32) -> (i32, i32) {
+	((x / CELL_SIZE).floor() as i32, (y / CELL_SIZE).floor() as i32)
+}
+
+/// Shortest distance from a point to any cell `ring` cells away from the point's own cell
+fn ring_distance(x: f32, y: f32, ring: i32) -> f32 {
+	if ring == 0 { return 0.0 }
+	let (cx, cy) = cell_of(x, y);
+	// distance to the edges of the square of cells enclosed by the ring
+	let inner = ring - 1;
+	let left = x - (cx - inner) as f32 * CELL_SIZE;
+	let right = (cx + inner + 1) as f32 * CELL_SIZE - x;
+	let top = y - (cy - inner) as f32 * CELL_SIZE;
+	let bottom = (cy + inner + 1) as f32 * CELL_SIZE - y;
+	left.min(right).min(top).min(bottom)
+}
+
+fn cells_in(min: (f32, f32), max: (f32, f32)) -> impl Iterator<Item = (i32, i32)> {
+	let (x0, y0) = cell_of(min.0, min.1);
+	let (x1, y1) = cell_of(max.0, max.1);
+	(x0..=x1).flat_map(move |x| (y0..=y1).map(move |y| (x, y)))
+}
+
+/// Resolves the hotspot of sprite instances from the first frame of their animation
+struct Hotspots<'a> {
+	data: &'a CstcData,
+	images: HashMap<i32, &'a cstc::ImageMetadata>,
+}
+impl<'a> Hotspots<'a> {
+	fn new(data: &'a CstcData) -> Self {
+		Hotspots { data, images: data.image_block.iter().map(|i| (i.id, i)).collect() }
+	}
+
+	fn shape(&self, obj: &EdObjectInstance) -> InstanceShape {
+		let (width, height) = (obj.width as f32, obj.height as f32);
+		let (mut hotspot_x, mut hotspot_y) = (0.0, 0.0);
+		if let cstc::ObjectData::Sprite(sprite) = &obj.data {
+			let image = self.data.animations.get(&sprite.animation)
+				.and_then(first_frame_image_id)
+				.and_then(|id| self.images.get(&id));
+			if let Some(image) = image {
+				// hotspot is in image pixels, scale it to the instance's size
+				if image.collision_width > 0 { hotspot_x = image.hotspot_x as f32 * width / image.collision_width as f32 }
+				if image.collision_height > 0 { hotspot_y = image.hotspot_y as f32 * height / image.collision_height as f32 }
+			}
+		}
+		InstanceShape { x: obj.x as f32, y: obj.y as f32, width, height, angle: obj.angle, hotspot_x, hotspot_y }
+	}
+}
+
+fn first_frame_image_id(animation: &cstc::Animation) -> Option<i32> {
+	animation.frames.first().map(|f| f.image_id)
+		.or_else(|| animation.sub_animations.iter().find_map(first_frame_image_id))
+}
@@ -0,0 +1,192 @@
+//! Registering towermod as the handler for `towermod:` URLs and `.towermod` files on freedesktop platforms
+//! (the equivalent of `towermod_win32::registry::initialize_registry_settings`)
+use std::path::{Path, PathBuf};
+use anyhow::Result;
+use directories::BaseDirs;
+use indoc::formatdoc;
+
+const DESKTOP_FILE_NAME: &str = "towermod.desktop";
+const MIME_PACKAGE_FILE_NAME: &str = "towermod.xml";
+pub const TOWERMOD_MIME_TYPE: &str = "application/x-towermod";
+pub const TOWERMOD_SCHEME_MIME_TYPE: &str = "x-scheme-handler/towermod";
+
+/// Locations of the files written by [`install_desktop_integration`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct DesktopIntegrationPaths {
+	/// `$XDG_DATA_HOME`
+	pub data_dir: PathBuf,
+	/// `$XDG_CONFIG_HOME`
+	pub config_dir: PathBuf,
+}
+
+impl Default for DesktopIntegrationPaths {
+	fn default() -> Self {
+		let base_dirs = BaseDirs::new().unwrap();
+		Self {
+			data_dir: base_dirs.data_dir().to_path_buf(),
+			config_dir: base_dirs.config_dir().to_path_buf(),
+		}
+	}
+}
+
+impl DesktopIntegrationPaths {
+	pub fn applications_dir(&self) -> PathBuf { self.data_dir.join("applications") }
+	pub fn desktop_entry(&self) -> PathBuf { self.applications_dir().join(DESKTOP_FILE_NAME) }
+	pub fn mime_dir(&self) -> PathBuf { self.data_dir.join("mime") }
+	pub fn mime_package(&self) -> PathBuf { self.mime_dir().join("packages").join(MIME_PACKAGE_FILE_NAME) }
+	pub fn mimeapps_list(&self) -> PathBuf { self.config_dir.join("mimeapps.list") }
+}
+
+/// Install the desktop entry, the MIME type of `.towermod` files, and make towermod their default handler.
+/// Should be idempotent
+pub fn install_desktop_integration(exe_path: &Path, paths: &DesktopIntegrationPaths) -> Result<()> {
+	let mut changed = write_if_changed(&paths.desktop_entry(), &desktop_entry(exe_path))?;
+	changed |= write_if_changed(&paths.mime_package(), &mime_package())?;
+
+	let mimeapps_list = fs_err::read_to_string(paths.mimeapps_list()).unwrap_or_default();
+	let defaults = [TOWERMOD_SCHEME_MIME_TYPE, TOWERMOD_MIME_TYPE].map(|mime_type| (mime_type, Some(DESKTOP_FILE_NAME)));
+	changed |= write_if_changed(&paths.mimeapps_list(), &set_default_applications(&mimeapps_list, &defaults))?;
+
+	if changed {
+		update_databases(paths);
+	}
+	Ok(())
+}
+
+/// Remove everything written by [`install_desktop_integration`]
+pub fn uninstall_desktop_integration(paths: &DesktopIntegrationPaths) -> Result<()> {
+	for path in [paths.desktop_entry(), paths.mime_package()] {
+		if let Err(e) = fs_err::remove_file(path) {
+			if e.kind() != std::io::ErrorKind::NotFound { Err(e)? }
+		}
+	}
+	if let Ok(mimeapps_list) = fs_err::read_to_string(paths.mimeapps_list()) {
+		let defaults = [TOWERMOD_SCHEME_MIME_TYPE, TOWERMOD_MIME_TYPE].map(|mime_type| (mime_type, None));
+		write_if_changed(&paths.mimeapps_list(), &set_default_applications(&mimeapps_list, &defaults))?;
+	}
+	update_databases(paths);
+	Ok(())
+}
+
+pub fn desktop_entry(exe_path: &Path) -> String {
+	let exec = quote_exec_arg(&exe_path.to_string_lossy());
+	formatdoc! {"
+		[Desktop Entry]
+		Type=Application
+		Name=Towermod
+		Comment=Mod manager and editor for TowerClimb
+		Exec={exec} %u
+		Icon=towermod
+		Terminal=false
+		Categories=Game;Utility;
+		MimeType={TOWERMOD_SCHEME_MIME_TYPE};{TOWERMOD_MIME_TYPE};application/zip;
+	"}
+}
+
+pub fn mime_package() -> String {
+	formatdoc! {r#"
+		<?xml version="1.0" encoding="UTF-8"?>
+		<mime-info xmlns="http://www.freedesktop.org/standards/shared-mime-info">
+			<mime-type type="{TOWERMOD_MIME_TYPE}">
+				<comment>Towermod package</comment>
+				<sub-class-of type="application/zip"/>
+				<glob pattern="*.towermod"/>
+			</mime-type>
+		</mime-info>
+	"#}
+}
+
+/// Quote an argument of a desktop entry's `Exec` key, if needed
+fn quote_exec_arg(arg: &str) -> String {
+	const RESERVED: &[char] = &[' ', '\t', '\n', '"', '\'', '\\', '>', '<', '~', '|', '&', ';', '$', '*', '?', '#', '(', ')', '`'];
+	let mut quoted = if arg.contains(RESERVED) {
+		let mut quoted = String::from("\"");
+		for c in arg.chars() {
+			if matches!(c, '"' | '`' | '$' | '\\') { quoted.push('\\') }
+			quoted.push(c);
+		}
+		quoted.push('"');
+		quoted
+	} else {
+		arg.to_string()
+	};
+	// field codes are expanded even inside quotes
+	quoted = quoted.replace('%', "%%");
+	// the desktop entry format itself uses backslash escapes
+	quoted.replace('\\', "\\\\")
+}
+
+/// Set (or with `None`, remove) entries of the `[Default Applications]` group of a `mimeapps.list`,
+/// keeping every other line as it is
+pub fn set_default_applications(mimeapps_list: &str, entries: &[(&str, Option<&str>)]) -> String {
+	const GROUP: &str = "[Default Applications]";
+	let mut lines = mimeapps_list.lines().map(|l| l.to_string()).collect::<Vec<_>>();
+	let is_entry_for = |line: &str, mime_type: &str| {
+		line.split_once('=').is_some_and(|(key, _)| key.trim() == mime_type)
+	};
+
+	let group_start = match lines.iter().position(|l| l.trim() == GROUP) {
+		Some(i) => i,
+		None => {
+			if entries.iter().all(|(_, value)| value.is_none()) {
+				return mimeapps_list.to_string()
+			}
+			if lines.last().is_some_and(|l| !l.trim().is_empty()) {
+				lines.push(String::new());
+			}
+			lines.push(GROUP.to_string());
+			lines.len() - 1
+		},
+	};
+	let mut group_end = lines[group_start + 1..].iter()
+		.position(|l| l.trim_start().starts_with('['))
+		.map_or(lines.len(), |i| group_start + 1 + i);
+	// insert new entries after the last non-empty line of the group
+	while group_end > group_start + 1 && lines[group_end - 1].trim().is_empty() {
+		group_end -= 1;
+	}
+
+	for (mime_type, value) in entries {
+		let existing = lines[group_start + 1..group_end].iter().position(|l| is_entry_for(l, mime_type)).map(|i| group_start + 1 + i);
+		match (existing, value) {
+			(Some(i), Some(value)) => lines[i] = format!("{mime_type}={value}"),
+			(Some(i), None) => {
+				lines.remove(i);
+				group_end -= 1;
+			},
+			(None, Some(value)) => {
+				lines.insert(group_end, format!("{mime_type}={value}"));
+				group_end += 1;
+			},
+			(None, None) => {},
+		}
+	}
+	let mut result = lines.join("\n");
+	result.push('\n');
+	result
+}
+
+/// Returns whether the file was written
+fn write_if_changed(path: &Path, contents: &str) -> Result<bool> {
+	if fs_err::read_to_string(path).is_ok_and(|existing| existing == contents) {
+		return Ok(false)
+	}
+	if let Some(dir) = path.parent() {
+		fs_err::create_dir_all(dir)?;
+	}
+	fs_err::write(path, contents)?;
+	Ok(true)
+}
+
+/// Rebuild the MIME and desktop entry caches, if the tools are installed
+fn update_databases(paths: &DesktopIntegrationPaths) {
+	use std::process::Command;
+	for (tool, dir) in [("update-mime-database", paths.mime_dir()), ("update-desktop-database", paths.applications_dir())] {
+		if !dir.is_dir() { continue }
+		match Command::new(tool).arg(&dir).output() {
+			Ok(output) if !output.status.success() => log::warn!("{tool} failed: {}", String::from_utf8_lossy(&output.stderr)),
+			Err(e) => log::info!("Could not run {tool}: {e}"),
+			_ => {},
+		}
+	}
+}
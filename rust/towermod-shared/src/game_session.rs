@@ -0,0 +1,175 @@
+//! Supervising running game processes
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::Mutex;
+use std::time::Instant;
+use anyhow::{Context, Result};
+use fs_err::tokio as fs;
+use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
+use serde_alias::serde_alias;
+use tokio::process::Command;
+use tokio::sync::watch;
+use crate::LauncherConfig;
+
+/// Emitted once a game process started with [`spawn_game`] has exited
+#[serde_alias(SnakeCase)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GameExit {
+	pub pid: u32,
+	pub game_path: PathBuf,
+	/// Captured stdout/stderr of the process
+	pub log_path: PathBuf,
+	/// `None` if the process was terminated by a signal or could not be waited on
+	pub exit_code: Option<i32>,
+	pub success: bool,
+	pub duration_secs: f64,
+}
+
+/// A running game process
+pub struct GameSession {
+	pub pid: u32,
+	pub game_path: PathBuf,
+	pub log_path: PathBuf,
+	exit: watch::Receiver<Option<GameExit>>,
+}
+
+type ExitHandler = Box<dyn Fn(&GameExit) + Send + Sync>;
+
+lazy_static! {
+	/// Sessions whose process has not exited yet, by process ID
+	static ref SESSIONS: Mutex<HashMap<u32, watch::Receiver<Option<GameExit>>>> = Mutex::new(HashMap::new());
+	static ref EXIT_HANDLER: Mutex<Option<ExitHandler>> = Mutex::new(None);
+}
+
+/// Set a callback that runs whenever a game session ends
+pub fn on_game_exit(handler: impl Fn(&GameExit) + Send + Sync + 'static) {
+	*EXIT_HANDLER.lock().unwrap() = Some(Box::new(handler));
+}
+
+impl GameSession {
+	/// Wait for the game to exit.
+	/// The process itself is waited on by a background task that owns the child process,
+	/// so the session does not need to be kept alive for the exit to be reported.
+	pub async fn wait(&mut self) -> Result<GameExit> {
+		let exit = self.exit.wait_for(|exit| exit.is_some()).await.context("Game session was dropped")?;
+		Ok(exit.clone().unwrap())
+	}
+}
+
+/// Start a game executable, capturing its output to a new log file in `log_dir`
+pub async fn spawn_game(game_path: &Path, launcher: &LauncherConfig, log_dir: &Path) -> Result<GameSession> {
+	log::info!("Starting game... {}", &game_path.to_string_lossy());
+	fs::create_dir_all(log_dir).await?;
+	let (log_path, log_file) = create_log_file(log_dir)?;
+
+	let mut child = Command::from(launcher.command(game_path)?)
+		.stdin(Stdio::null())
+		.stdout(log_file.try_clone()?)
+		.stderr(log_file)
+		.spawn()
+		.context("Failed to start game")?;
+	let pid = child.id().context("Failed to start game")?;
+	let started = Instant::now();
+
+	let (sender, receiver) = watch::channel(None);
+	SESSIONS.lock().unwrap().insert(pid, receiver.clone());
+	tokio::spawn({
+		let game_path = game_path.to_path_buf();
+		let log_path = log_path.clone();
+		async move {
+			let status = child.wait().await;
+			if let Err(e) = &status {
+				log::error!("Failed to wait for game process {pid}: {e}");
+			}
+			let exit = GameExit {
+				pid,
+				game_path,
+				log_path,
+				exit_code: status.as_ref().ok().and_then(|s| s.code()),
+				success: status.is_ok_and(|s| s.success()),
+				duration_secs: started.elapsed().as_secs_f64(),
+			};
+			log::info!("Game process {pid} exited with code {:?} after {:.1}s", exit.exit_code, exit.duration_secs);
+			SESSIONS.lock().unwrap().remove(&pid);
+			if let Some(handler) = &*EXIT_HANDLER.lock().unwrap() {
+				handler(&exit);
+			}
+			sender.send_replace(Some(exit));
+		}
+	});
+
+	Ok(GameSession { pid, game_path: game_path.to_path_buf(), log_path, exit: receiver })
+}
+
+/// Create a log file named after the current time, adding a counter when several games start within the same second
+fn create_log_file(log_dir: &Path) -> Result<(PathBuf, std::fs::File)> {
+	let now = time::OffsetDateTime::now_utc();
+	let stem = format!("{}-{:02}-{:02}_{:02}-{:02}-{:02}", now.year(), now.month() as u8, now.day(), now.hour(), now.minute(), now.second());
+	for i in 0.. {
+		let log_path = match i {
+			0 => log_dir.join(format!("{stem}.log")),
+			i => log_dir.join(format!("{stem}_{i}.log")),
+		};
+		match std::fs::File::create_new(&log_path) {
+			Ok(file) => return Ok((log_path, file)),
+			Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => continue,
+			Err(e) => return Err(e).context("Failed to create game log"),
+		}
+	}
+	unreachable!()
+}
+
+/// Wait until a process exits.
+/// Returns the exit information if the process is a game session started by towermod.
+pub async fn wait_until_process_exits(pid: u32) -> Result<Option<GameExit>> {
+	let session = SESSIONS.lock().unwrap().get(&pid).cloned();
+	if let Some(mut exit) = session {
+		let exit = exit.wait_for(|exit| exit.is_some()).await.context("Game session was dropped")?;
+		return Ok(exit.clone())
+	}
+	tokio::task::spawn_blocking(move || wait_for_pid(pid)).await??;
+	Ok(None)
+}
+
+#[cfg(windows)]
+fn wait_for_pid(pid: u32) -> Result<()> {
+	towermod_win32::process::wait_until_process_exits(pid)
+}
+
+#[cfg(target_os = "linux")]
+fn wait_for_pid(pid: u32) -> Result<()> {
+	use std::os::fd::{AsRawFd, FromRawFd, OwnedFd};
+	let fd = unsafe { libc::syscall(libc::SYS_pidfd_open, pid as libc::pid_t, 0) } as libc::c_int;
+	if fd < 0 {
+		let err = std::io::Error::last_os_error();
+		if err.raw_os_error() == Some(libc::ESRCH) {
+			// already exited
+			return Ok(())
+		}
+		return Err(err).context(format!("Failed to open process {pid}"))
+	}
+	let fd = unsafe { OwnedFd::from_raw_fd(fd) };
+	log::info!("Waiting for process {} to exit", pid);
+	let mut pollfd = libc::pollfd { fd: fd.as_raw_fd(), events: libc::POLLIN, revents: 0 };
+	// the pidfd becomes readable once the process exits
+	while unsafe { libc::poll(&mut pollfd, 1, -1) } < 0 {
+		let err = std::io::Error::last_os_error();
+		if err.kind() != std::io::ErrorKind::Interrupted {
+			return Err(err).context(format!("Failed to wait for process {pid}"))
+		}
+	}
+	log::info!("Process {} exited", pid);
+	Ok(())
+}
+
+#[cfg(all(unix, not(target_os = "linux")))]
+fn wait_for_pid(pid: u32) -> Result<()> {
+	// no pidfd, poll whether the process still exists
+	while unsafe { libc::kill(pid as libc::pid_t, 0) } == 0 {
+		std::thread::sleep(std::time::Duration::from_millis(500));
+	}
+	Ok(())
+}
@@ -0,0 +1,102 @@
+//! Forwarding messages (e.g. command-line arguments) to an already running instance of towermod.
+//! Uses a named pipe on Windows and a Unix domain socket elsewhere.
+use anyhow::Result;
+
+/// Whether another instance is listening for messages
+pub fn instance_exists() -> bool {
+	#[cfg(windows)]
+	return towermod_win32::pipe::pipe_exists();
+	#[cfg(unix)]
+	return unix::socket_exists(&unix::socket_path());
+}
+
+/// Send a message to the running instance
+pub fn send_to_instance(msg: &str) -> Result<()> {
+	#[cfg(windows)]
+	return towermod_win32::pipe::write_to_pipe(msg.as_bytes());
+	#[cfg(unix)]
+	return unix::write_to_socket(&unix::socket_path(), msg.as_bytes());
+}
+
+/// Listen for messages from other instances, calling `handler` for each one
+pub fn listen(handler: impl Sync + Send + 'static + Fn(&str)) -> Result<!> {
+	#[cfg(windows)]
+	return towermod_win32::pipe::listen_pipe(handler);
+	#[cfg(unix)]
+	return unix::listen_socket(&unix::socket_path(), handler);
+}
+
+#[cfg(unix)]
+pub mod unix {
+	use std::io::{Read, Write};
+	use std::os::unix::fs::PermissionsExt;
+	use std::os::unix::net::{UnixListener, UnixStream};
+	use std::path::{Path, PathBuf};
+	use std::sync::Arc;
+	use anyhow::{Context, Result};
+	use directories::BaseDirs;
+
+	/// `$XDG_RUNTIME_DIR/towermod.sock`, or a per-user socket in the temp directory
+	pub fn socket_path() -> PathBuf {
+		match BaseDirs::new().and_then(|dirs| dirs.runtime_dir().map(|d| d.to_path_buf())) {
+			Some(runtime_dir) => runtime_dir.join("towermod.sock"),
+			None => std::env::temp_dir().join(format!("towermod-{}.sock", unsafe { libc::getuid() })),
+		}
+	}
+
+	pub fn socket_exists(path: &Path) -> bool {
+		UnixStream::connect(path).is_ok()
+	}
+
+	pub fn write_to_socket(path: &Path, msg: &[u8]) -> Result<()> {
+		log::info!("Sending message to socket");
+		let mut stream = UnixStream::connect(path).context("Failed to connect to socket")?;
+		stream.write_all(msg)?;
+		// the end of the message is marked by closing our half of the connection
+		stream.shutdown(std::net::Shutdown::Write)?;
+		Ok(())
+	}
+
+	pub fn listen_socket(path: &Path, handler: impl Sync + Send + 'static + Fn(&str)) -> Result<!> {
+		if path.exists() {
+			if socket_exists(path) {
+				anyhow::bail!("Another instance is already listening on {}", path.display());
+			}
+			// left behind by an instance that did not shut down cleanly
+			fs_err::remove_file(path)?;
+		}
+		let listener = UnixListener::bind(path).context("Could not create socket")?;
+		fs_err::set_permissions(path, std::fs::Permissions::from_mode(0o600))?;
+
+		let handler = Arc::new(handler);
+		loop {
+			log::info!("Socket: Waiting for connection...");
+			let stream = match listener.accept() {
+				Ok((stream, _)) => stream,
+				Err(e) => {
+					log::error!("Socket: Failed to accept connection: {e:?}");
+					continue;
+				}
+			};
+			log::info!("Socket: Connection received");
+			let handler = handler.clone();
+			std::thread::spawn(move || socket_connection_instance(stream, handler));
+		}
+	}
+
+	fn socket_connection_instance(mut stream: UnixStream, handler: Arc<impl Fn(&str)>) {
+		let mut bytes = Vec::new();
+		if let Err(e) = stream.read_to_end(&mut bytes) {
+			log::error!("Socket: Read failed: {e:?}");
+			return;
+		}
+		// connections made by `socket_exists` carry no message
+		if bytes.is_empty() { return }
+		if let Ok(msg) = std::str::from_utf8(&bytes) {
+			log::info!("Message from socket: {}", msg);
+			handler(msg);
+		} else {
+			log::warn!("Could not decode message from socket");
+		}
+	}
+}
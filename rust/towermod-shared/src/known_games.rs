@@ -0,0 +1,99 @@
+//! Database of known game releases, used to give them friendly names
+use std::path::Path;
+use anyhow::{Context, Result};
+use fs_err::tokio as fs;
+use serde::{Deserialize, Serialize};
+use crate::{Game, GameType};
+
+const BUNDLED_KNOWN_GAMES: &str = include_str!("known_games.toml");
+
+/// A release of a game, identified by its `file_hash` or `data_hash`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KnownGame {
+	pub name: String,
+	/// e.g. `v1.0 (Steam)`
+	pub version: String,
+	#[serde(default)]
+	pub game_type: GameType,
+	/// Matches [`Game::file_hash`]
+	#[serde(default)]
+	pub file_hash: Option<String>,
+	/// Matches [`Game::data_hash`]. Executables that were patched without touching the game data share it
+	#[serde(default)]
+	pub data_hash: Option<String>,
+}
+
+impl KnownGame {
+	/// e.g. `TowerClimb v1.0 (Steam)`
+	pub fn display_name(&self) -> String {
+		format!("{} {}", self.name, self.version)
+	}
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct KnownGames {
+	#[serde(default, rename = "game")]
+	pub games: Vec<KnownGame>,
+}
+
+impl KnownGames {
+	pub fn from_toml(s: &str) -> Result<Self> {
+		Ok(toml::from_str(s)?)
+	}
+
+	/// The database shipped with towermod
+	pub fn bundled() -> Self {
+		Self::from_toml(BUNDLED_KNOWN_GAMES).expect("Bundled known_games.toml is invalid")
+	}
+
+	/// The bundled database, extended by the user's file at `path` if it exists
+	pub async fn load(path: &Path) -> Result<Self> {
+		let mut known_games = Self::bundled();
+		match fs::read_to_string(path).await {
+			Ok(s) => {
+				let user = Self::from_toml(&s).with_context(|| format!("Failed to parse {}", path.display()))?;
+				known_games.extend(user);
+			},
+			Err(e) if e.kind() == std::io::ErrorKind::NotFound => {},
+			Err(e) => Err(e)?,
+		}
+		Ok(known_games)
+	}
+
+	/// Add entries that take precedence over the existing ones
+	pub fn extend(&mut self, other: KnownGames) {
+		self.games.splice(0..0, other.games);
+	}
+
+	/// Exact matches of the executable are preferred over matches of its game data
+	pub fn find(&self, file_hash: &str, data_hash: Option<&str>) -> Option<&KnownGame> {
+		self.games.iter().find(|g| g.file_hash.as_deref() == Some(file_hash))
+			.or_else(|| data_hash.and_then(|data_hash| self.games.iter().find(|g| g.data_hash.as_deref() == Some(data_hash))))
+	}
+
+	pub fn find_game(&self, game: &Game) -> Option<&KnownGame> {
+		self.find(&game.file_hash, game.data_hash.as_deref())
+	}
+
+	/// Describe a game version in plain words, for messages shown to the user
+	pub fn describe(&self, game: &Game) -> String {
+		match self.find_game(game) {
+			Some(known) => known.display_name(),
+			None => format!("an unrecognized version of {} ({} bytes)", game.file_name, game.file_size),
+		}
+	}
+}
+
+/// Known games from the bundled database and the user's `known_games.toml`.
+/// Errors in the user's file are logged and ignored
+pub async fn load_known_games() -> KnownGames {
+	let path = crate::get_known_games_path();
+	match KnownGames::load(&path).await {
+		Ok(known_games) => known_games,
+		Err(e) => {
+			log::warn!("{e:?}");
+			KnownGames::bundled()
+		},
+	}
+}
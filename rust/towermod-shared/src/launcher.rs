@@ -0,0 +1,164 @@
+//! Starting game executables, either directly or through Wine/Proton
+use std::path::{Path, PathBuf};
+use anyhow::{Context, Result};
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+use serde_alias::serde_alias;
+use crate::{steam_dir_candidates, steam_library_folders, TOWERCLIMB_APP_ID};
+
+/// How game executables are started
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", tag = "type")]
+pub enum GameLauncher {
+	/// Run the executable directly
+	Native,
+	/// Run the executable with a Wine binary, optionally in a specific prefix
+	#[serde(rename_all = "camelCase")]
+	Wine {
+		wine_path: PathBuf,
+		prefix: Option<PathBuf>,
+	},
+	/// Run the executable with `proton run`, using a Steam compatdata directory
+	#[serde(rename_all = "camelCase")]
+	Proton {
+		/// Path to the `proton` script
+		proton_path: PathBuf,
+		/// e.g. `steamapps/compatdata/396640`
+		compat_data_path: PathBuf,
+		/// Steam installation directory, which Proton expects in `STEAM_COMPAT_CLIENT_INSTALL_PATH`
+		steam_path: Option<PathBuf>,
+		/// Steam app the prefix belongs to, defaults to the name of the compatdata directory
+		app_id: Option<u32>,
+	},
+}
+
+impl Default for GameLauncher {
+	fn default() -> Self {
+		if cfg!(windows) {
+			Self::Native
+		} else {
+			Self::Wine { wine_path: PathBuf::from("wine"), prefix: None }
+		}
+	}
+}
+
+#[serde_alias(SnakeCase)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct LauncherConfig {
+	pub launcher: GameLauncher,
+	/// Additional environment variables set for the game process
+	pub env: IndexMap<String, String>,
+	/// Defaults to the directory containing the game executable
+	pub working_dir: Option<PathBuf>,
+}
+
+impl GameLauncher {
+	/// Wine prefix the game runs in, if it runs through Wine/Proton
+	pub fn wine_prefix(&self) -> Option<PathBuf> {
+		match self {
+			Self::Native => None,
+			Self::Wine { prefix, .. } => prefix.clone(),
+			Self::Proton { compat_data_path, .. } => Some(compat_data_path.join("pfx")),
+		}
+	}
+
+	/// Find the Proton prefix that Steam created for TowerClimb
+	pub fn detect_towerclimb_proton() -> Result<Self> {
+		Self::detect_proton(&steam_dir_candidates(), TOWERCLIMB_APP_ID)
+	}
+
+	/// Find the Proton prefix of a Steam app in any library of the given Steam installations
+	pub fn detect_proton(steam_dirs: &[PathBuf], app_id: u32) -> Result<Self> {
+		let mut found_prefix = false;
+		for steam_path in steam_dirs {
+			let libraries = steam_library_folders(steam_path);
+			// the prefix is created in the library the app is installed in
+			let compat_data_path = libraries.iter()
+				.map(|library| library.join("steamapps/compatdata").join(app_id.to_string()))
+				.find(|path| path.is_dir());
+			let Some(compat_data_path) = compat_data_path else { continue };
+			found_prefix = true;
+			// another Steam installation may have both
+			let Some(proton_path) = find_proton(&libraries, &compat_data_path) else { continue };
+			return Ok(Self::Proton { proton_path, compat_data_path, steam_path: Some(steam_path.clone()), app_id: Some(app_id) });
+		}
+		if found_prefix {
+			anyhow::bail!("Found a Proton prefix for app {app_id}, but no Proton installation")
+		}
+		anyhow::bail!("Could not find a Proton prefix for app {app_id}")
+	}
+}
+
+impl LauncherConfig {
+	pub fn command(&self, game_path: &Path) -> Result<std::process::Command> {
+		let mut command = match &self.launcher {
+			GameLauncher::Native => std::process::Command::new(game_path),
+			GameLauncher::Wine { wine_path, prefix } => {
+				let mut command = std::process::Command::new(wine_path);
+				command.arg(game_path);
+				if let Some(prefix) = prefix {
+					command.env("WINEPREFIX", prefix);
+				}
+				command
+			},
+			GameLauncher::Proton { proton_path, compat_data_path, steam_path, app_id } => {
+				let mut command = std::process::Command::new(proton_path);
+				command.arg("run").arg(game_path);
+				command.env("STEAM_COMPAT_DATA_PATH", compat_data_path);
+				let app_id = app_id.or_else(|| compat_data_path.file_name()?.to_str()?.parse().ok());
+				if let Some(app_id) = app_id {
+					command.env("SteamAppId", app_id.to_string());
+					command.env("SteamGameId", app_id.to_string());
+				}
+				if let Some(steam_path) = steam_path {
+					command.env("STEAM_COMPAT_CLIENT_INSTALL_PATH", steam_path);
+				}
+				command
+			},
+		};
+		command.envs(&self.env);
+		let working_dir = match &self.working_dir {
+			Some(dir) => dir.clone(),
+			None => game_path.parent().context("Game path has no parent directory")?.to_path_buf(),
+		};
+		command.current_dir(working_dir);
+		Ok(command)
+	}
+}
+
+/// Find the Proton installation used for a prefix.
+/// Prefers the one recorded in the prefix's `config_info`, then the newest one installed in any library.
+fn find_proton(libraries: &[PathBuf], compat_data_path: &Path) -> Option<PathBuf> {
+	if let Ok(config_info) = fs_err::read_to_string(compat_data_path.join("config_info")) {
+		if let Some(proton_path) = proton_from_config_info(&config_info).filter(|p| p.is_file()) {
+			return Some(proton_path)
+		}
+	}
+	libraries.iter()
+		.filter_map(|library| fs_err::read_dir(library.join("steamapps/common")).ok())
+		.flatten()
+		.filter_map(|entry| entry.ok())
+		.filter(|entry| entry.file_name().to_string_lossy().starts_with("Proton"))
+		.map(|entry| (proton_version(&entry.file_name().to_string_lossy()), entry.path().join("proton")))
+		.filter(|(_, path)| path.is_file())
+		.max_by(|(a, _), (b, _)| a.cmp(b))
+		.map(|(_, path)| path)
+}
+
+/// Numeric version of a Proton installation directory, e.g. `[10, 0]` for `Proton 10.0`.
+/// Installations without one (`Proton - Experimental`, `Proton Hotfix`) sort before all numbered ones.
+pub fn proton_version(dir_name: &str) -> Vec<u32> {
+	dir_name.split(|c: char| !c.is_ascii_digit())
+		.filter(|part| !part.is_empty())
+		.filter_map(|part| part.parse().ok())
+		.collect()
+}
+
+/// `config_info` lists files from the Proton installation, e.g. `.../common/Proton 9.0/files/share/fonts/`
+pub fn proton_from_config_info(config_info: &str) -> Option<PathBuf> {
+	config_info.lines()
+		.filter_map(|line| line.split_once("/files/").or_else(|| line.split_once("/dist/")))
+		.map(|(proton_dir, _)| Path::new(proton_dir).join("proton"))
+		.next()
+}
@@ -0,0 +1,296 @@
+//! CPU renderer for layout previews (thumbnails, screenshots, visual regression tests)
+use std::collections::{HashMap, HashSet};
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use serde_alias::serde_alias;
+use towermod_cstc as cstc;
+use crate::cstc_editing::{CstcData, EdLayout, EdLayoutLayer, EdObjectInstance};
+
+#[derive(Debug, Clone)]
+pub struct RgbaImage {
+	pub width: u32,
+	pub height: u32,
+	/// Non-premultiplied RGBA8, row-major
+	pub pixels: Vec<u8>,
+}
+impl RgbaImage {
+	pub fn new(width: u32, height: u32) -> Self {
+		RgbaImage { width, height, pixels: vec![0; width as usize * height as usize * 4] }
+	}
+	fn get(&self, x: u32, y: u32) -> [u8; 4] {
+		let i = (y as usize * self.width as usize + x as usize) * 4;
+		self.pixels[i..i + 4].try_into().unwrap()
+	}
+	fn fill(&mut self, color: [u8; 4]) {
+		for px in self.pixels.chunks_exact_mut(4) {
+			px.copy_from_slice(&color);
+		}
+	}
+	/// Source-over blend of a non-premultiplied color
+	fn blend(&mut self, x: u32, y: u32, [r, g, b, a]: [f32; 4]) {
+		let i = (y as usize * self.width as usize + x as usize) * 4;
+		let dst = &mut self.pixels[i..i + 4];
+		let dst_a = dst[3] as f32 / 255.0;
+		let out_a = a + dst_a * (1.0 - a);
+		if out_a <= 0.0 { return }
+		for (c, src) in dst[..3].iter_mut().zip([r, g, b]) {
+			let value = (src * a + (*c as f32 / 255.0) * dst_a * (1.0 - a)) / out_a;
+			*c = (value * 255.0).round().clamp(0.0, 255.0) as u8;
+		}
+		dst[3] = (out_a * 255.0).round() as u8;
+	}
+
+	pub fn from_png(bytes: &[u8]) -> Result<Self> {
+		let mut decoder = png::Decoder::new(bytes);
+		decoder.set_transformations(png::Transformations::normalize_to_color8());
+		let mut reader = decoder.read_info()?;
+		let mut buf = vec![0; reader.output_buffer_size()];
+		let info = reader.next_frame(&mut buf)?;
+		buf.truncate(info.buffer_size());
+		let pixels = match info.color_type {
+			png::ColorType::Rgba => buf,
+			png::ColorType::Rgb => buf.chunks_exact(3).flat_map(|p| [p[0], p[1], p[2], 255]).collect(),
+			png::ColorType::GrayscaleAlpha => buf.chunks_exact(2).flat_map(|p| [p[0], p[0], p[0], p[1]]).collect(),
+			png::ColorType::Grayscale => buf.iter().flat_map(|p| [*p, *p, *p, 255]).collect(),
+			png::ColorType::Indexed => anyhow::bail!("Unexpected indexed PNG after expansion"),
+		};
+		Ok(RgbaImage { width: info.width, height: info.height, pixels })
+	}
+
+	pub fn to_png(&self) -> Result<Vec<u8>> {
+		let mut buf = Vec::new();
+		{
+			let mut encoder = png::Encoder::new(&mut buf, self.width, self.height);
+			encoder.set_color(png::ColorType::Rgba);
+			encoder.set_depth(png::BitDepth::Eight);
+			let mut writer = encoder.write_header()?;
+			writer.write_image_data(&self.pixels)?;
+		}
+		Ok(buf)
+	}
+}
+
+#[serde_alias(SnakeCase)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RenderOptions {
+	/// Scroll position of the view, in layout coordinates
+	pub x: f32,
+	pub y: f32,
+	/// Size of the view in layout pixels. Defaults to the size of the layout.
+	pub width: Option<u32>,
+	pub height: Option<u32>,
+	/// Output scale, e.g. 0.25 for thumbnails
+	pub scale: f32,
+}
+impl Default for RenderOptions {
+	fn default() -> Self {
+		RenderOptions { x: 0.0, y: 0.0, width: None, height: None, scale: 1.0 }
+	}
+}
+
+/// Render a layout using the starting frame of each sprite's starting animation, picking the angle closest to the instance's angle.
+/// Tiled backgrounds repeat their texture over the instance, panels stretch it (their margins aren't decoded).
+/// `images` must contain the decoded images of the (patched) image block.
+///
+/// Other instances and effects are not drawn.
+pub fn render_layout(data: &CstcData, layout: &EdLayout, images: &HashMap<i32, RgbaImage>, options: &RenderOptions) -> RgbaImage {
+	let view_width = options.width.unwrap_or(layout.width.max(1) as u32);
+	let view_height = options.height.unwrap_or(layout.height.max(1) as u32);
+	let scale = if options.scale > 0.0 { options.scale } else { 1.0 };
+	let mut canvas = RgbaImage::new(((view_width as f32 * scale).round() as u32).max(1), ((view_height as f32 * scale).round() as u32).max(1));
+	canvas.fill(colorref_to_rgba(layout.color, 255));
+
+	let metadata: HashMap<i32, &cstc::ImageMetadata> = data.image_block.iter().map(|m| (m.id, m)).collect();
+	for layer in &layout.layers {
+		if !matches!(layer.layer_type, cstc::LayerType::Normal) { continue }
+		if layer.clear_background_color {
+			let [r, g, b, _] = colorref_to_rgba(layer.background_color, 255);
+			let color = [r as f32 / 255.0, g as f32 / 255.0, b as f32 / 255.0, layer.opacity.clamp(0.0, 1.0)];
+			for y in 0..canvas.height {
+				for x in 0..canvas.width {
+					canvas.blend(x, y, color);
+				}
+			}
+		}
+		// layers with a scroll factor other than 1 move at a different rate than the view,
+		// and layer zoom and rotation are applied around the center of the view
+		let (center_x, center_y) = (view_width as f32 / 2.0, view_height as f32 / 2.0);
+		let layer_transform = Transform::scale(scale, scale)
+			.then_translate(center_x, center_y)
+			.then_rotate(layer.angle)
+			.then_scale(layer.zoom_x, layer.zoom_y)
+			.then_translate(-center_x - options.x * layer.scroll_x_factor, -center_y - options.y * layer.scroll_y_factor);
+		for obj in &layer.objects {
+			if obj.width == 0 || obj.height == 0 { continue }
+			match &obj.data {
+				cstc::ObjectData::Sprite(sprite) => {
+					if sprite.hide_at_start { continue }
+					let Some((image_id, animation_angle)) = sprite_frame(data, sprite, obj.angle) else { continue };
+					let Some(image) = images.get(&image_id) else { continue };
+					let (hotspot_x, hotspot_y) = metadata.get(&image_id).map(|m| (m.hotspot_x as f32, m.hotspot_y as f32)).unwrap_or_default();
+					if image.width == 0 || image.height == 0 { continue }
+					// the angle animation already shows the image at its own angle, only rotate by what's left
+					let transform = layer_transform
+						.then_translate(obj.x as f32, obj.y as f32)
+						.then_rotate(obj.angle - animation_angle)
+						.then_scale(obj.width as f32 / image.width as f32, obj.height as f32 / image.height as f32)
+						.then_translate(-hotspot_x, -hotspot_y);
+					draw_image(&mut canvas, layer, obj, image, (image.width as f32, image.height as f32), &transform);
+				},
+				// positioned by their top left corner
+				cstc::ObjectData::TiledBackground(tiled) => {
+					let Some(image) = images.get(&tiled.texture_id).filter(|i| i.width > 0 && i.height > 0) else { continue };
+					let transform = layer_transform
+						.then_translate(obj.x as f32, obj.y as f32)
+						.then_rotate(obj.angle);
+					draw_image(&mut canvas, layer, obj, image, (obj.width as f32, obj.height as f32), &transform);
+				},
+				cstc::ObjectData::Panel(panel) => {
+					let Some(image) = images.get(&panel.image_id).filter(|i| i.width > 0 && i.height > 0) else { continue };
+					let transform = layer_transform
+						.then_translate(obj.x as f32, obj.y as f32)
+						.then_rotate(obj.angle)
+						.then_scale(obj.width as f32 / image.width as f32, obj.height as f32 / image.height as f32);
+					draw_image(&mut canvas, layer, obj, image, (image.width as f32, image.height as f32), &transform);
+				},
+				_ => {},
+			}
+		}
+	}
+	canvas
+}
+
+/// Images that [`render_layout`] needs in order to draw `layout`
+pub fn required_image_ids(data: &CstcData, layout: &EdLayout) -> HashSet<i32> {
+	let mut image_ids = sprite_image_ids(data, layout);
+	image_ids.extend(layout.layers.iter().flat_map(|l| l.objects.iter()).filter_map(|o| match &o.data {
+		cstc::ObjectData::TiledBackground(tiled) => Some(tiled.texture_id),
+		cstc::ObjectData::Panel(panel) => Some(panel.image_id),
+		_ => None,
+	}));
+	image_ids
+}
+
+/// Frames that [`render_layout`] draws for the sprites on `layout`
+pub fn sprite_image_ids(data: &CstcData, layout: &EdLayout) -> HashSet<i32> {
+	layout.layers.iter()
+		.flat_map(|l| l.objects.iter())
+		.filter_map(|o| if let cstc::ObjectData::Sprite(sprite) = &o.data { sprite_frame(data, sprite, o.angle).map(|(id, _)| id) } else { None })
+		.collect()
+}
+
+/// Draw the `(width, height)` rectangle of `image` through `transform` (image pixels to canvas pixels).
+/// The image repeats if the rectangle is larger than it.
+fn draw_image(canvas: &mut RgbaImage, layer: &EdLayoutLayer, obj: &EdObjectInstance, image: &RgbaImage, (width, height): (f32, f32), transform: &Transform) {
+	let Some(inverse) = transform.inverse() else { return };
+	// bounding box of the transformed rectangle on the canvas
+	let corners = [(0.0, 0.0), (width, 0.0), (width, height), (0.0, height)].map(|(u, v)| transform.apply(u, v));
+	let min_x = corners.iter().map(|c| c.0).fold(f32::MAX, f32::min).floor().max(0.0) as u32;
+	let min_y = corners.iter().map(|c| c.1).fold(f32::MAX, f32::min).floor().max(0.0) as u32;
+	let max_x = corners.iter().map(|c| c.0).fold(f32::MIN, f32::max).ceil().min(canvas.width as f32) as u32;
+	let max_y = corners.iter().map(|c| c.1).fold(f32::MIN, f32::max).ceil().min(canvas.height as f32) as u32;
+
+	let [filter_r, filter_g, filter_b, filter_a] = argb_to_rgba(obj.filter).map(|c| c as f32 / 255.0);
+	let [layer_r, layer_g, layer_b, _] = colorref_to_rgba(layer.filter_color, 255).map(|c| c as f32 / 255.0);
+	let opacity = filter_a * layer.opacity.clamp(0.0, 1.0);
+	for y in min_y..max_y {
+		for x in min_x..max_x {
+			// map the pixel center back into image space (nearest neighbour)
+			let (u, v) = inverse.apply(x as f32 + 0.5, y as f32 + 0.5);
+			if u < 0.0 || v < 0.0 || u >= width || v >= height { continue }
+			let [r, g, b, a] = image.get(u as u32 % image.width, v as u32 % image.height).map(|c| c as f32 / 255.0);
+			if a <= 0.0 { continue }
+			canvas.blend(x, y, [r * filter_r * layer_r, g * filter_g * layer_g, b * filter_b * layer_b, a * opacity]);
+		}
+	}
+}
+
+/// 2D affine transform, mapping (x, y) to (a*x + c*y + tx, b*x + d*y + ty)
+#[derive(Debug, Clone, Copy)]
+struct Transform { a: f32, b: f32, c: f32, d: f32, tx: f32, ty: f32 }
+impl Transform {
+	fn scale(x: f32, y: f32) -> Self {
+		Transform { a: x, b: 0.0, c: 0.0, d: y, tx: 0.0, ty: 0.0 }
+	}
+	/// `self` applied after `other`
+	fn then(&self, other: &Transform) -> Self {
+		Transform {
+			a: self.a * other.a + self.c * other.b,
+			b: self.b * other.a + self.d * other.b,
+			c: self.a * other.c + self.c * other.d,
+			d: self.b * other.c + self.d * other.d,
+			tx: self.a * other.tx + self.c * other.ty + self.tx,
+			ty: self.b * other.tx + self.d * other.ty + self.ty,
+		}
+	}
+	fn then_translate(&self, x: f32, y: f32) -> Self {
+		self.then(&Transform { a: 1.0, b: 0.0, c: 0.0, d: 1.0, tx: x, ty: y })
+	}
+	fn then_scale(&self, x: f32, y: f32) -> Self {
+		self.then(&Transform::scale(x, y))
+	}
+	/// Degrees, clockwise
+	fn then_rotate(&self, angle: f32) -> Self {
+		let (sin, cos) = angle.to_radians().sin_cos();
+		self.then(&Transform { a: cos, b: sin, c: -sin, d: cos, tx: 0.0, ty: 0.0 })
+	}
+	fn apply(&self, x: f32, y: f32) -> (f32, f32) {
+		(self.a * x + self.c * y + self.tx, self.b * x + self.d * y + self.ty)
+	}
+	fn inverse(&self) -> Option<Self> {
+		let det = self.a * self.d - self.b * self.c;
+		if det.abs() < f32::EPSILON { return None }
+		let (a, b, c, d) = (self.d / det, -self.b / det, -self.c / det, self.a / det);
+		Some(Transform { a, b, c, d, tx: -(a * self.tx + c * self.ty), ty: -(b * self.tx + d * self.ty) })
+	}
+}
+
+/// Image of the sprite's starting animation and frame, at the 0° angle
+pub fn sprite_image_id(data: &CstcData, sprite: &cstc::SpriteObjectData) -> Option<i32> {
+	sprite_frame(data, sprite, 0.0).map(|(id, _)| id)
+}
+
+/// Image of the sprite's starting animation and frame, taken from the angle closest to `angle`, along with that angle
+fn sprite_frame(data: &CstcData, sprite: &cstc::SpriteObjectData, angle: f32) -> Option<(i32, f32)> {
+	let root = data.animations.get(&sprite.animation)?;
+	let animation = find_animation_by_name(root, &sprite.start_anim).unwrap_or(root);
+	let frames = closest_angle(animation, angle).or_else(|| first_with_frames(animation)).or_else(|| first_with_frames(root))?;
+	let frame = frames.frames.get((sprite.start_frame - 1).max(0) as usize).or(frames.frames.first())?;
+	Some((frame.image_id, frames.angle))
+}
+
+/// Among the animation and its angles, the one with frames whose angle is closest to `angle`
+fn closest_angle(animation: &cstc::Animation, angle: f32) -> Option<&cstc::Animation> {
+	let distance = |a: &cstc::Animation| {
+		let d = (a.angle - angle).rem_euclid(360.0);
+		d.min(360.0 - d)
+	};
+	std::iter::once(animation)
+		.chain(animation.sub_animations.iter().filter(|a| a.is_angle))
+		.filter(|a| !a.frames.is_empty())
+		.min_by(|a, b| distance(a).total_cmp(&distance(b)))
+}
+
+fn find_animation_by_name<'a>(animation: &'a cstc::Animation, name: &str) -> Option<&'a cstc::Animation> {
+	if !animation.is_angle && animation.name.eq_ignore_ascii_case(name) { return Some(animation) }
+	animation.sub_animations.iter().find_map(|a| find_animation_by_name(a, name))
+}
+
+/// The animation itself, or its first descendant (e.g. the 0° angle) that has frames
+fn first_with_frames(animation: &cstc::Animation) -> Option<&cstc::Animation> {
+	if !animation.frames.is_empty() { return Some(animation) }
+	animation.sub_animations.iter().find_map(first_with_frames)
+}
+
+/// Layout and layer colors are stored as Win32 COLORREF (0x00BBGGRR)
+fn colorref_to_rgba(color: i32, alpha: u8) -> [u8; 4] {
+	let [r, g, b, _] = color.to_le_bytes();
+	[r, g, b, alpha]
+}
+
+/// Instance filters are stored as D3DCOLOR (0xAARRGGBB)
+fn argb_to_rgba(color: i32) -> [u8; 4] {
+	let [b, g, r, a] = color.to_le_bytes();
+	[r, g, b, a]
+}
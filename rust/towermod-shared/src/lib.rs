@@ -14,6 +14,9 @@
 pub mod dllreader_client;
 pub mod game_images;
 pub mod cstc_editing;
+pub mod layout_renderer;
+pub mod tiled;
+pub mod ipc;
 
 pub mod app;
 mod newtype;
@@ -21,11 +24,23 @@ mod project;
 mod config;
 mod pe_resource;
 mod filesystem;
+mod version_info;
+mod launcher;
+mod game_session;
+mod desktop_integration;
+mod steam;
+mod known_games;
 pub use newtype::*;
 pub use project::*;
 pub use config::*;
 pub use pe_resource::*;
 pub use filesystem::*;
+pub use version_info::*;
+pub use launcher::*;
+pub use game_session::*;
+pub use desktop_integration::*;
+pub use steam::*;
+pub use known_games::*;
 
 pub use towermod_util;
 pub use towermod_cstc;
@@ -14,18 +14,26 @@ use std::path::{Path, PathBuf};
 use log::warn;
 use tokio::io::{AsyncSeekExt, AsyncWriteExt};
 use tokio::task::JoinSet;
-use tokio::process::Command;
 use fs_err::tokio as fs;
 use tracing::{instrument, Instrument, info_span};
 use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_alias::serde_alias;
 use crate::{Game, ModInfo, ModType, Project, dllreader_client};
 use towermod_cstc as cstc;
 
 use super::{cstc_binary_dir};
 
 pub trait PeResource: Sized + Send {
-	fn read_bin(pe_path: impl AsRef<Path>) -> Result<Vec<u8>, anyhow::Error>;
-	fn write_bin(pe_path: impl AsRef<Path>, bin: &[u8]) -> Result<(), anyhow::Error>;
+	/// Resource type and ID of the block in the executable
+	const RES_TYPE: &'static str;
+	const RES_ID: u16;
+	fn read_bin(pe_path: impl AsRef<Path>) -> Result<Vec<u8>, anyhow::Error> {
+		read_pe_file_resource(pe_path, Self::RES_TYPE, Self::RES_ID)
+	}
+	fn write_bin(pe_path: impl AsRef<Path>, bin: &[u8]) -> Result<(), anyhow::Error> {
+		replace_pe_file_resource(pe_path, Self::RES_TYPE, Self::RES_ID, bin)
+	}
 	fn to_bin(&self) -> Result<Vec<u8>, anyhow::Error>;
 	fn from_bin(bin: &[u8]) -> Result<Self, anyhow::Error>;
 	#[allow(async_fn_in_trait)]
@@ -78,42 +86,345 @@ fn read_pe_file_resource(pe_path: impl AsRef<Path>, name: &str, id: u16) -> Resu
 }
 
 fn replace_pe_file_resource(pe_path: impl AsRef<Path>, name: &str, id: u16, bin: &[u8]) -> Result<()> {
+	replace_pe_resource(pe_path, &ResourceId::from(name), &ResourceId::from(id), None, bin)
+}
+
+/// Integer or string ID of a PE resource type or name
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum ResourceId {
+	Int(u16),
+	String(String),
+}
+impl From<u16> for ResourceId {
+	fn from(value: u16) -> Self {
+		Self::Int(value)
+	}
+}
+impl From<&str> for ResourceId {
+	fn from(value: &str) -> Self {
+		Self::String(value.to_string())
+	}
+}
+impl std::fmt::Display for ResourceId {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			ResourceId::Int(i) => write!(f, "{i}"),
+			ResourceId::String(s) => write!(f, "{s}"),
+		}
+	}
+}
+#[cfg(windows)]
+impl From<&ResourceId> for ResId {
+	fn from(value: &ResourceId) -> Self {
+		match value {
+			ResourceId::Int(i) => ResId::from(*i),
+			ResourceId::String(s) => ResId::from(s.as_str()),
+		}
+	}
+}
+#[cfg(windows)]
+impl From<ResId> for ResourceId {
+	fn from(value: ResId) -> Self {
+		match value {
+			ResId::Int(i) => ResourceId::Int(i),
+			ResId::String(hstring) => ResourceId::String(hstring.to_string()),
+		}
+	}
+}
+impl ResourceId {
+	/// Name of a predefined resource type, e.g. `RCDATA` for `RT_RCDATA`
+	pub fn type_name(&self) -> Option<&'static str> {
+		let ResourceId::Int(i) = self else { return None };
+		Some(match i {
+			1 => "CURSOR",
+			2 => "BITMAP",
+			3 => "ICON",
+			4 => "MENU",
+			5 => "DIALOG",
+			6 => "STRING",
+			7 => "FONTDIR",
+			8 => "FONT",
+			9 => "ACCELERATOR",
+			10 => "RCDATA",
+			11 => "MESSAGETABLE",
+			12 => "GROUP_CURSOR",
+			14 => "GROUP_ICON",
+			16 => "VERSION",
+			17 => "DLGINCLUDE",
+			19 => "PLUGPLAY",
+			20 => "VXD",
+			21 => "ANICURSOR",
+			22 => "ANIICON",
+			23 => "HTML",
+			24 => "MANIFEST",
+			_ => return None,
+		})
+	}
+}
+
+#[serde_alias(SnakeCase)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PeResourceInfo {
+	pub res_type: ResourceId,
+	/// Name of predefined resource types
+	pub type_name: Option<String>,
+	pub name: ResourceId,
+	pub language: u16,
+	/// Size in bytes
+	pub size: usize,
+}
+impl PeResourceInfo {
+	fn new(res_type: ResourceId, name: ResourceId, language: u16, size: usize) -> Self {
+		let type_name = res_type.type_name().map(String::from);
+		PeResourceInfo { res_type, type_name, name, language, size }
+	}
+}
+
+/// List every resource in an executable, by type, name and language
+pub fn list_pe_resources(pe_path: impl AsRef<Path>) -> Result<Vec<PeResourceInfo>> {
+	#[cfg(windows)]
+	return {
+		let resources = towermod_win32::pe_resource::list_pe_file_resources(pe_path.as_ref())?;
+		Ok(resources.into_iter().map(|(res_type, name, language, size)| PeResourceInfo::new(res_type.into(), name.into(), language, size)).collect())
+	};
+	#[cfg(not(windows))]
+	return {
+		use lief::pe::resources::{Node, NodeBase};
+		let pe = lief::pe::Binary::parse(pe_path).context("Could not parse binary")?;
+		let res = pe.resources().context("Could not get resources")?;
+		let mut resources = Vec::new();
+		for res_type in res.children() {
+			for name in res_type.children() {
+				for data in name.children() {
+					let Node::Data(ref content) = data else { continue };
+					resources.push(PeResourceInfo::new(lief_node_id(&res_type), lief_node_id(&name), data.id() as u16, content.content().len()));
+				}
+			}
+		}
+		Ok(resources)
+	};
+}
+
+/// Every resource in an executable along with its contents
+pub fn read_all_pe_resources(pe_path: impl AsRef<Path>) -> Result<Vec<(PeResourceInfo, Vec<u8>)>> {
+	#[cfg(windows)]
+	return {
+		let pe_path = pe_path.as_ref();
+		list_pe_resources(pe_path)?.into_iter().map(|info| {
+			let bin = towermod_win32::pe_resource::read_pe_file_resource_language(pe_path, &(&info.res_type).into(), &(&info.name).into(), info.language)?;
+			Ok((info, bin))
+		}).collect()
+	};
+	#[cfg(not(windows))]
+	return {
+		use lief::pe::resources::{Node, NodeBase};
+		let pe = lief::pe::Binary::parse(pe_path).context("Could not parse binary")?;
+		let res = pe.resources().context("Could not get resources")?;
+		let mut resources = Vec::new();
+		for res_type in res.children() {
+			for name in res_type.children() {
+				for data in name.children() {
+					let Node::Data(ref content) = data else { continue };
+					let bin = content.content().to_vec();
+					resources.push((PeResourceInfo::new(lief_node_id(&res_type), lief_node_id(&name), data.id() as u16, bin.len()), bin));
+				}
+			}
+		}
+		Ok(resources)
+	};
+}
+
+/// Copy every resource of `src_exe` on to `dest_exe`, replacing those with the same type, name and language
+pub fn transplant_pe_resources(src_exe: impl AsRef<Path>, dest_exe: impl AsRef<Path>) -> Result<()> {
+	let mut set = PeResourceSet::new();
+	for (info, bin) in read_all_pe_resources(src_exe)? {
+		set.stage(info.res_type, info.name, Some(info.language), bin);
+	}
+	set.commit(dest_exe)
+}
+
+#[cfg(not(windows))]
+fn lief_node_id(node: &lief::pe::resources::Node) -> ResourceId {
+	use lief::pe::resources::NodeBase;
+	match node.name() {
+		Some(name) => ResourceId::String(name),
+		None => ResourceId::Int(node.id() as u16),
+	}
+}
+
+/// Find the child directory of a resource node with the given ID, adding it if it doesn't exist.
+/// New nodes must be attached to the tree before they are filled in.
+#[cfg(not(windows))]
+fn lief_child_directory(node: &mut lief::pe::resources::Node, id: &ResourceId) -> lief::pe::resources::Node {
+	use lief::pe::resources::{self as pe, Node, NodeBase};
+	if let Some(child) = node.children().find(|n| &lief_node_id(n) == id) {
+		return child
+	}
+	let directory = match id {
+		ResourceId::Int(id) => Node::Directory(pe::Directory::with_id((*id).into())),
+		ResourceId::String(name) => {
+			let mut directory = Node::Directory(pe::Directory::new());
+			directory.set_name(name);
+			directory
+		},
+	};
+	node.add_child(&directory)
+}
+
+/// The given language, or else the language of the existing resource
+fn resource_language(pe_path: &Path, res_type: &ResourceId, name: &ResourceId, language: Option<u16>) -> Result<Option<u16>> {
+	if language.is_some() { return Ok(language) }
+	Ok(list_pe_resources(pe_path)?.into_iter()
+		.find(|r| &r.res_type == res_type && &r.name == name)
+		.map(|r| r.language))
+}
+
+/// Read any resource from an executable. If no language is given, the first language found is used.
+pub fn read_pe_resource(pe_path: impl AsRef<Path>, res_type: &ResourceId, name: &ResourceId, language: Option<u16>) -> Result<Vec<u8>> {
+	let pe_path = pe_path.as_ref();
+	let language = resource_language(pe_path, res_type, name, language)?
+		.with_context(|| format!("Could not find resource {res_type}/{name}"))?;
 	#[cfg(windows)]
 	return {
-		towermod_win32::pe_resource::replace_pe_file_resource(pe_path.as_ref(), &ResId::from(name), &ResId::from(id), bin)
+		towermod_win32::pe_resource::read_pe_file_resource_language(pe_path, &res_type.into(), &name.into(), language)
 	};
 	#[cfg(not(windows))]
 	return {
-		use lief::pe::resources::{self as pe, Node, NodeBase};
-		fn set_named_block(node: &mut Node, name: &str, id: u16, bytes: &[u8]) {
-			let Some(section) = node.children().find(|section| section.name().map_or(false, |n| n == name)) else {
-				return
-			};
-			let mut child = section.children().find(|child| child.id() == id).unwrap_or_else(|| {
-				Node::Directory(pe::Directory::with_id(id))
+		use lief::pe::resources::{Node, NodeBase};
+		let pe = lief::pe::Binary::parse(pe_path).context("Could not parse binary")?;
+		let res = pe.resources().context("Could not get resources")?;
+		let data = res.children().find(|n| &lief_node_id(n) == res_type)
+			.and_then(|n| n.children().find(|n| &lief_node_id(n) == name))
+			.and_then(|n| n.children().find(|n| n.id() as u16 == language));
+		let Some(Node::Data(data)) = data else { anyhow::bail!("Could not find resource {res_type}/{name}") };
+		Ok(data.content().to_vec())
+	};
+}
+
+/// Replace any resource of an executable, or add it if it doesn't exist.
+/// If no language is given, the language of the existing resource is kept.
+pub fn replace_pe_resource(pe_path: impl AsRef<Path>, res_type: &ResourceId, name: &ResourceId, language: Option<u16>, bin: &[u8]) -> Result<()> {
+	let mut set = PeResourceSet::new();
+	set.stage(res_type.clone(), name.clone(), language, bin.to_vec());
+	set.commit(pe_path)
+}
+
+struct StagedResource {
+	res_type: ResourceId,
+	name: ResourceId,
+	language: Option<u16>,
+	bin: Vec<u8>,
+}
+
+/// Resource replacements that are written to an executable together, so that it is only parsed and rewritten once.
+///
+/// Entries are applied in the order they were staged.
+#[derive(Default)]
+pub struct PeResourceSet {
+	resources: Vec<StagedResource>,
+}
+impl PeResourceSet {
+	pub fn new() -> Self {
+		Self::default()
+	}
+	pub fn is_empty(&self) -> bool {
+		self.resources.is_empty()
+	}
+	/// If no language is given, the language of the existing resource is kept
+	pub fn stage(&mut self, res_type: ResourceId, name: ResourceId, language: Option<u16>, bin: Vec<u8>) -> &mut Self {
+		self.resources.push(StagedResource { res_type, name, language, bin });
+		self
+	}
+	pub fn stage_block_bin<T: PeResource>(&mut self, bin: Vec<u8>) -> &mut Self {
+		self.stage(ResourceId::from(T::RES_TYPE), ResourceId::from(T::RES_ID), None, bin)
+	}
+	pub fn stage_block<T: PeResource>(&mut self, block: &T) -> Result<&mut Self> {
+		Ok(self.stage_block_bin::<T>(block.to_bin()?))
+	}
+	/// Stage runtime plugins (.csx) for the DLLBLOCK, as plugin IDs `first_id`, `first_id + 1`, ...
+	pub fn stage_dllblock_plugins(&mut self, first_id: i32, plugins: &[Vec<u8>]) -> Result<&mut Self> {
+		for (i, bin) in plugins.iter().enumerate() {
+			let id = u16::try_from(1000 + first_id + i as i32).context("Plugin ID out of range")?;
+			self.stage(ResourceId::from("DLLBLOCK"), ResourceId::from(id), None, bin.clone());
+		}
+		Ok(self)
+	}
+
+	#[instrument(skip_all, fields(count = self.resources.len()))]
+	pub fn commit(self, pe_path: impl AsRef<Path>) -> Result<()> {
+		let pe_path = pe_path.as_ref();
+		if self.is_empty() { return Ok(()) }
+		#[cfg(windows)]
+		return {
+			let existing = if self.resources.iter().any(|r| r.language.is_none()) { list_pe_resources(pe_path)? } else { Vec::new() };
+			let resources = self.resources.iter().map(|r| {
+				let language = r.language
+					.or_else(|| existing.iter().find(|e| e.res_type == r.res_type && e.name == r.name).map(|e| e.language))
+					.unwrap_or(0x00);
+				(ResId::from(&r.res_type), ResId::from(&r.name), language, r.bin.as_slice())
+			}).collect::<Vec<_>>();
+			towermod_win32::pe_resource::replace_pe_file_resources(pe_path, &resources)
+		};
+		#[cfg(not(windows))]
+		return {
+			use lief::pe::resources::{self as pe, Node, NodeBase};
+			let mut pe = lief::pe::Binary::parse(pe_path).context("Could not parse binary")?;
+			let mut res = pe.resources().context("Could not get resources")?;
+			for StagedResource { res_type, name, language, bin } in &self.resources {
+				let mut type_node = lief_child_directory(&mut res, res_type);
+				let mut name_node = lief_child_directory(&mut type_node, name);
+				match name_node.children().find(|n| language.map_or(true, |l| n.id() as u16 == l)) {
+					Some(Node::Data(mut data)) => { data.set_content(bin); },
+					_ => {
+						let mut data = Node::Data(pe::Data::with_buffer(bin));
+						data.set_id(language.unwrap_or(0x00).into());
+						name_node.add_child(&data);
+					},
+				}
+			}
+			pe.write(pe_path);
+			self.verify(pe_path)
+		};
+	}
+
+	/// lief doesn't report write errors, so check that the staged resources can be read back
+	#[cfg(not(windows))]
+	fn verify(&self, pe_path: &Path) -> Result<()> {
+		let written = read_all_pe_resources(pe_path).context("Could not read back written resources")?;
+		// later entries replace earlier ones
+		let mut expected: Vec<&StagedResource> = Vec::new();
+		for r in &self.resources {
+			expected.retain(|e| !(e.res_type == r.res_type && e.name == r.name && (e.language == r.language || e.language.is_none() || r.language.is_none())));
+			expected.push(r);
+		}
+		for StagedResource { res_type, name, language, bin } in expected {
+			let found = written.iter().any(|(info, content)| {
+				&info.res_type == res_type && &info.name == name && language.map_or(true, |l| info.language == l) && content == bin
 			});
-			if let Some(Node::Data(mut data)) = child.children().next() {
-				data.set_content(bytes);
-			} else {
-				child.add_child(&Node::Data(pe::Data::with_buffer(bytes)));
+			if !found {
+				anyhow::bail!("Failed to write resource {res_type}/{name} to {}", pe_path.display());
 			}
 		}
-		let mut pe = lief::pe::Binary::parse(&pe_path).context("Could not parse binary")?;
-		let mut res = pe.resources().context("Could not get resources")?;
-		set_named_block(&mut res, name, id, bin);
-		pe.write(pe_path);
 		Ok(())
 	}
 }
 
+/// Write a resource of an executable to a file
+pub async fn extract_pe_resource(pe_path: impl AsRef<Path>, res_type: &ResourceId, name: &ResourceId, language: Option<u16>, dest_path: impl AsRef<Path>) -> Result<()> {
+	let (pe_path, res_type, name) = (pe_path.as_ref().to_owned(), res_type.clone(), name.clone());
+	let bin = blocking!(read_pe_resource(&pe_path, &res_type, &name, language)).await??;
+	if let Some(dir) = dest_path.as_ref().parent() {
+		fs::create_dir_all(dir).await?;
+	}
+	fs::write(dest_path.as_ref(), bin).await?;
+	Ok(())
+}
 
 impl PeResource for cstc::stable::AppBlock {
-	fn read_bin(pe_path: impl AsRef<Path>) -> Result<Vec<u8>> {
-		read_pe_file_resource(pe_path, "APPBLOCK", 997)
-	}
-	fn write_bin(pe_path: impl AsRef<Path>, bin: &[u8]) -> Result<()> {
-		replace_pe_file_resource(pe_path, "APPBLOCK", 997, bin)
-	}
+	const RES_TYPE: &'static str = "APPBLOCK";
+	const RES_ID: u16 = 997;
 	fn to_bin(&self) -> Result<Vec<u8>> {
 		cstc::serialize_appblock(self)
 	}
@@ -123,12 +434,8 @@ impl PeResource for cstc::stable::AppBlock {
 }
 
 impl PeResource for cstc::stable::LevelBlock {
-	fn read_bin(pe_path: impl AsRef<Path>) -> Result<Vec<u8>> {
-		read_pe_file_resource(pe_path, "LEVELBLOCK", 998)
-	}
-	fn write_bin(pe_path: impl AsRef<Path>, bin: &[u8]) -> Result<()> {
-		replace_pe_file_resource(pe_path, "LEVELBLOCK", 998, bin)
-	}
+	const RES_TYPE: &'static str = "LEVELBLOCK";
+	const RES_ID: u16 = 998;
 	fn to_bin(&self) -> Result<Vec<u8>> {
 		cstc::serialize_levelblock(self)
 	}
@@ -138,12 +445,8 @@ impl PeResource for cstc::stable::LevelBlock {
 }
 
 impl PeResource for cstc::stable::EventBlock {
-	fn read_bin(pe_path: impl AsRef<Path>) -> Result<Vec<u8>> {
-		read_pe_file_resource(pe_path, "EVENTBLOCK", 999)
-	}
-	fn write_bin(pe_path: impl AsRef<Path>, bin: &[u8]) -> Result<()> {
-		replace_pe_file_resource(pe_path, "EVENTBLOCK", 999, bin)
-	}
+	const RES_TYPE: &'static str = "EVENTBLOCK";
+	const RES_ID: u16 = 999;
 	fn to_bin(&self) -> Result<Vec<u8>> {
 		cstc::serialize_eventblock(self)
 	}
@@ -153,12 +456,8 @@ impl PeResource for cstc::stable::EventBlock {
 }
 
 impl PeResource for cstc::stable::ImageBlock {
-	fn read_bin(pe_path: impl AsRef<Path>) -> Result<Vec<u8>> {
-		read_pe_file_resource(pe_path, "IMAGEBLOCK", 995)
-	}
-	fn write_bin(pe_path: impl AsRef<Path>, bin: &[u8]) -> Result<()> {
-		replace_pe_file_resource(pe_path, "IMAGEBLOCK", 995, bin)
-	}
+	const RES_TYPE: &'static str = "IMAGEBLOCK";
+	const RES_ID: u16 = 995;
 	fn to_bin(&self) -> Result<Vec<u8>> {
 		cstc::serialize_imageblock(self)
 	}
@@ -166,6 +465,110 @@ impl PeResource for cstc::stable::ImageBlock {
 		cstc::deserialize_imageblock(bin)
 	}
 }
+/// Embed runtime plugins (.csx) in the DLLBLOCK of an executable, as plugin IDs `first_id`, `first_id + 1`, ...
+pub fn add_dllblock_plugins(pe_path: impl AsRef<Path>, first_id: i32, plugins: &[Vec<u8>]) -> Result<()> {
+	let mut set = PeResourceSet::new();
+	set.stage_dllblock_plugins(first_id, plugins)?;
+	set.commit(pe_path)
+}
+
+const RT_STRING: u16 = 6;
+
+/// Block of a string table resource (`RT_STRING`).
+/// Block `n` holds the strings with IDs `(n - 1) * 16` to `n * 16 - 1`, each as a UTF-16 length followed by its UTF-16 code units.
+/// Trailing empty strings can be left out.
+pub fn string_table_block(strings: &[&str]) -> Vec<u8> {
+	debug_assert!(strings.len() <= 16);
+	let mut data = Vec::new();
+	for s in strings {
+		let utf16_units: Vec<u16> = s.encode_utf16().collect();
+		data.extend((utf16_units.len() as u16).to_le_bytes());
+		for char in utf16_units {
+			data.extend(char.to_le_bytes());
+		}
+	}
+	data
+}
+
+/// String table entry pointing a debug build to a runtime plugin on disk
+fn preview_plugin_string_table_entry(file_name: &str) -> Vec<u8> {
+	string_table_block(&["", file_name])
+}
+
+/// Stage the resources of a game executable for a preview (debug) build of the Construct Classic runtime.
+///
+/// Preview builds don't embed the plugin DLLs directly,
+/// they just contain the filenames, and locate and load the plugins from disk
+/// based on the location set in a Construct.ini file in the same directory as the executable.
+/// So instead of the DLLBLOCK, string table entries are staged, and the plugins are returned
+/// with the file names they need to be written to.
+/// Other resources keep their language, so they replace the runtime's resources of the same language.
+pub fn stage_debug_build_resources(set: &mut PeResourceSet, resources: Vec<(PeResourceInfo, Vec<u8>)>) -> Vec<(String, Vec<u8>)> {
+	let dllblock = ResourceId::from("DLLBLOCK");
+	let (plugins, resources): (Vec<_>, Vec<_>) = resources.into_iter().partition(|(info, _)| info.res_type == dllblock);
+	for (info, bin) in resources {
+		set.stage(info.res_type, info.name, Some(info.language), bin);
+	}
+	plugins.into_iter().enumerate().map(|(i, (_, bin))| {
+		let file_name = format!("plugin{i}.csx");
+		set.stage(ResourceId::from(RT_STRING), ResourceId::from(1000 + i as u16), Some(0x00), preview_plugin_string_table_entry(&file_name));
+		(file_name, bin)
+	}).collect()
+}
+
+/// Transplant resources on to a debug build of the Construct Classic runtime
+/// # Errors
+/// Game path not set
+pub async fn convert_to_debug_build(game: &Game, dest_exe: &Path) -> Result<()> {
+	let src_exe = game.game_path()?.clone();
+
+	// Dump runtime plugins to a temporary directory
+	let plugin_dump_dir = game.plugin_dump_dir().await?;
+	let base_runtime_executable = cstc_binary_dir().await?.join("Data/DX9_pd.exe");
+
+	// Copy the debug runtime base executable
+	fs::copy(&base_runtime_executable, dest_exe).await?;
+
+	// Patch the annoying "first-time warning" popup out of the executable
+	{
+		let mut file = fs::OpenOptions::new().write(true).open(dest_exe).await?;
+		file.seek(tokio::io::SeekFrom::Start(0x54a6a)).await?;
+		file.write_all(&[0xeb, 0x0e]).await?;
+	}
+
+	let resources = blocking!(read_all_pe_resources(&src_exe)).await??;
+	let mut set = PeResourceSet::new();
+	for (file_name, bin) in stage_debug_build_resources(&mut set, resources) {
+		fs::write(plugin_dump_dir.join(&file_name), &bin).await?;
+	}
+	let dest_exe = dest_exe.to_owned();
+	blocking!(set.commit(&dest_exe)).await?
+}
+
+/// Transplant resources on to a release build of the Construct Classic runtime
+/// # Errors
+/// Game path not set
+pub async fn convert_to_release_build(src_exe: &Path, dest_exe: &Path) -> Result<()> {
+	let base_runtime_executable = cstc_binary_dir().await?.join("Data/DX9.exe");
+	fs::copy(&base_runtime_executable, dest_exe).await?;
+	let (src_exe, dest_exe) = (src_exe.to_owned(), dest_exe.to_owned());
+	blocking!(transplant_pe_resources(&src_exe, &dest_exe)).await?
+}
+
+/// Make additional runtime plugins available to a debug build created by [`convert_to_debug_build`],
+/// as plugin IDs `first_id`, `first_id + 1`, ...
+pub async fn stage_debug_build_plugins(set: &mut PeResourceSet, game: &Game, first_id: i32, plugins: &[Vec<u8>]) -> Result<()> {
+	let plugin_dump_dir = game.plugin_dump_dir().await?;
+	for (i, bin) in plugins.iter().enumerate() {
+		let id = first_id + i as i32;
+		let file_name = format!("plugin{id}.csx");
+		fs::write(plugin_dump_dir.join(&file_name), bin).await?;
+		let res_name = u16::try_from(1000 + id).context("Plugin ID out of range")?;
+		set.stage(ResourceId::from(RT_STRING), ResourceId::from(res_name), Some(0x00), preview_plugin_string_table_entry(&file_name));
+	}
+	Ok(())
+}
+
 #[cfg(windows)]
 mod platform {
 	use super::*;
@@ -188,11 +591,7 @@ mod platform {
 	use tokio::process::Command;
 	use fs_err::tokio as fs;
 	use tracing::{instrument, Instrument, info_span};
-	use windows::Win32::Storage::FileSystem::{GetFileVersionInfoSizeW, GetFileVersionInfoW, VerQueryValueW};
-	use windows::Win32::System::LibraryLoader::{LoadLibraryExW, LOAD_LIBRARY_AS_DATAFILE};
 	use anyhow::{Context, Result};
-	use windows::Win32::Foundation::{HANDLE, FreeLibrary};
-	use windows::core::HSTRING;
 	use crate::dllreader_client;
 	use crate::{Game, ModInfo, ModType, Project};
 	use towermod_win32::pe_resource::*;
@@ -203,135 +602,6 @@ mod platform {
 	pub async fn read_file_plugin_string_table(path: &Path) -> Result<PluginStringTable> {
 		towermod_cstc::plugin::read_file_plugin_string_table(path).await
 	}
-
-	/// Infer the original filename of a plugin
-	/// TODO: 'OriginalFilename' is not always reliable
-	pub fn get_original_filename(path: &Path) -> Result<String> {
-		let path_hstring = HSTRING::from(path.as_os_str());
-		unsafe {
-			let hmodule = LoadLibraryExW(&path_hstring, HANDLE::default(), LOAD_LIBRARY_AS_DATAFILE)?;
-			scopeguard::defer! { if let Err(e) = FreeLibrary(hmodule) { log::error!("{}", e) }; }
-			let size = GetFileVersionInfoSizeW(&path_hstring, None);
-			let mut buffer: Vec<u8> = Vec::with_capacity(size as usize);
-
-			let buffer_ptr = buffer.as_mut_slice().as_mut_ptr() as *mut _ as *mut c_void;
-			GetFileVersionInfoW(&path_hstring, 0, size, buffer_ptr)?;
-
-			let mut char_count: u32 = 0;
-			let mut p_info_buffer = 0 as *mut c_void;
-			VerQueryValueW(buffer_ptr, &HSTRING::from(r"\StringFileInfo\040904b0\OriginalFilename"), &mut p_info_buffer, &mut char_count);
-			let chars = slice::from_raw_parts(p_info_buffer as *const u16, char_count as usize);
-			let str = String::from_utf16_lossy(chars);
-			Ok(str)
-		}
-	}
-
-	/// # Errors
-	/// Game path not set
-	pub async fn convert_to_release_build(src_exe: &Path, dest_exe: &Path) -> Result<()> {
-		let base_runtime_executable = cstc_binary_dir().await?.join("Data/DX9.exe");
-		fs::copy(&base_runtime_executable, dest_exe).await?;
-
-		let src_exe = HSTRING::from(src_exe.as_os_str());
-		unsafe {
-			{
-				let hmodule = LoadLibraryExW(&src_exe, HANDLE::default(), LOAD_LIBRARY_AS_DATAFILE)?;
-				scopeguard::defer! { if let Err(e) = FreeLibrary(hmodule) { log::error!("{}", e) }; }
-
-				let res_types = list_hmodule_resource_types(hmodule)?;
-				for res_type in &res_types {
-					let res_names = list_hmodule_resource_names(hmodule, &res_type)?;
-					// Copy over all resources
-					for res_name in &res_names {
-						let bin = read_hmodule_resource(hmodule, res_type, res_name)?;
-						towermod_win32::pe_resource::replace_pe_file_resource(&dest_exe, res_type, res_name, &bin)?;
-					}
-				}
-
-				FreeLibrary(hmodule)?;
-			}
-		}
-		Ok(())
-	}
-
-	/// Transplant resources on to a debug build of the Construct Classic runtime
-	/// # Errors
-	/// Game path not set
-	pub async fn convert_to_debug_build(game: &Game, dest_exe: &Path) -> Result<()> {
-		let src_exe = game.game_path()?;
-
-		// Dump runtime plugins to a temporary directory
-		let plugin_dump_dir = game.plugin_dump_dir().await?;
-		let base_runtime_executable = cstc_binary_dir().await?.join("Data/DX9_pd.exe");
-
-		// Copy the debug runtime base executable
-		fs::copy(&base_runtime_executable, dest_exe).await?;
-
-		// Patch the annoying "first-time warning" popup out of the executable
-		{
-			let mut file = fs::OpenOptions::new().write(true).open(dest_exe).await?;
-			file.seek(tokio::io::SeekFrom::Start(0x54a6a)).await?;
-			file.write_all(&[0xeb, 0x0e]).await?;
-		}
-
-		let src_exe = HSTRING::from(src_exe.as_os_str());
-		unsafe {
-			let mut string_table_entries: Vec<Vec<u8>> = Vec::new();
-			{
-				let hmodule = LoadLibraryExW(&src_exe, HANDLE::default(), LOAD_LIBRARY_AS_DATAFILE)?;
-				scopeguard::defer! { if let Err(e) = FreeLibrary(hmodule) { log::error!("{}", e) }; }
-
-				let res_types = list_hmodule_resource_types(hmodule)?;
-				for res_type in &res_types {
-					let res_names = list_hmodule_resource_names(hmodule, &res_type)?;
-					match &res_type {
-						// Preview builds don't embed the plugin DLLs directly,
-						// they just contain the filenames, and locate and load the plugins from disk
-						// based on the location set in a Construct.ini file in the same directory as the executable.
-						ResId::String(ty) if ty == "DLLBLOCK" => {
-							for i in 0..res_names.len() {
-								// Dump each plugin to disk
-								let res_name = &res_names[i];
-								let bin = read_hmodule_resource(hmodule, &res_type, res_name)?;
-								let file_name = format!("plugin{i}.csx");
-								fs::write(plugin_dump_dir.join(&file_name), &bin).await?;
-
-								// And add an entry to the StringTable on the destination executable
-								let mut data: Vec<u8> = Vec::from([0x00, 0x00]);
-								let utf16_units: Vec<u16> = file_name.encode_utf16().collect();
-								data.extend((utf16_units.len() as u16).to_le_bytes());
-								for char in utf16_units {
-									data.extend(char.to_le_bytes());
-								}
-								string_table_entries.push(data);
-							}
-						}
-						_ => {
-							// Copy over all other resources
-							for res_name in &res_names {
-								let bin = read_hmodule_resource(hmodule, res_type, res_name)?;
-
-								while let Err(_) = towermod_win32::pe_resource::replace_pe_file_resource(&dest_exe, res_type, res_name, &bin) {
-									println!("retry");
-								};
-							}
-						}
-					}
-				}
-
-				FreeLibrary(hmodule)?;
-			}
-
-			// Add the string table entries for preview-mode plugin loading
-			let res_type_string_table = ResId::from(RES_TYPE_STRING_TABLE);
-			for (i, data) in string_table_entries.iter().enumerate() {
-				while let Err(_) = towermod_win32::pe_resource::replace_pe_file_resource(&dest_exe, &res_type_string_table, &ResId::from(1000 + i as u16), data) {
-					println!("retry");
-				};
-			}
-		}
-		Ok(())
-	}
 }
 
 #[cfg(not(windows))]
@@ -346,21 +616,6 @@ mod platform {
 	pub async fn read_dllblock_names(exe_path: &Path) -> Result<HashMap<i32, String>> {
 		dllreader_client::remote_read_dllblock_names(exe_path).await
 	}
-
-	pub fn get_original_filename(path: &Path) -> Result<String> {
-		// BUG: implement for linux
-		anyhow::bail!("not implemented on this platform")
-	}
-
-	pub async fn convert_to_release_build(src_exe: &Path, dest_exe: &Path) -> Result<()> {
-		// BUG: implement for linux
-		anyhow::bail!("not implemented on this platform")
-	}
-
-	pub async fn convert_to_debug_build(game: &Game, dest_exe: &Path) -> Result<()> {
-		// BUG: implement for linux
-		anyhow::bail!("not implemented on this platform")
-	}
 }
 
 pub use platform::*;
@@ -476,8 +731,33 @@ pub async fn export_from_files(project: &Project) -> Result<()> {
 	Ok(())
 }
 
+/// `OriginalFilename` from the version information of each plugin in the DLLBLOCK of an executable, by plugin ID.
+/// Plugins without version information are left out.
+pub fn read_dllblock_original_filenames(exe_path: impl AsRef<Path>) -> Result<HashMap<i32, String>> {
+	let dllblock = ResourceId::from("DLLBLOCK");
+	let mut names = HashMap::new();
+	for (info, bin) in read_all_pe_resources(exe_path)? {
+		let ResourceId::Int(id) = info.name else { continue };
+		if info.res_type != dllblock { continue }
+		let temp_file = tempfile::NamedTempFile::new()?;
+		std::fs::write(temp_file.path(), &bin)?;
+		if let Ok(name) = crate::get_original_filename(temp_file.path()) {
+			names.insert(id as i32 - 1000, name);
+		}
+	}
+	Ok(names)
+}
+
+/// Version of the plugin matching done by [`load_editor_plugins_by_name`].
+/// Bump it whenever the matching changes, so maps cached by an older version are rebuilt.
+pub const EDITOR_PLUGINS_CACHE_VERSION: u32 = 2;
+
+/// Editor builds of the plugins of a game, by plugin ID.
+///
+/// Plugins are matched by the name in their string table. Plugins whose editor build has a different name
+/// are matched by the `OriginalFilename` of their runtime build in the game's DLLBLOCK instead, which is the file name of the editor build.
 #[instrument]
-pub async fn load_editor_plugins_by_name(names: &HashMap<i32, String>) -> Result<HashMap<i32, PluginData>> {
+pub async fn load_editor_plugins_by_name(exe_path: &Path, names: &HashMap<i32, String>) -> Result<HashMap<i32, PluginData>> {
 	let index_by_name: HashMap<&str, i32> = names.iter()
 		.map(|(i, v)| (v as &str, *i)).collect();
 	let mut data_by_index: HashMap<i32, PluginData> = HashMap::new();
@@ -495,15 +775,31 @@ pub async fn load_editor_plugins_by_name(names: &HashMap<i32, String>) -> Result
 				log::debug!("Reading data for editor plugin: {:?}", path);
 				let data = dllreader_client::remote_read_editor_plugin(&path).await?;
 				log::debug!("Finished reading editor plugin {}", data.string_table.name);
-				anyhow::Ok(Some(data))
+				anyhow::Ok(Some((entry.file_name().to_string_lossy().to_lowercase(), data)))
 			});
 		}
 		anyhow::Ok(())
 	}.instrument(info_span!("read_dir")).await?;
+	let mut data_by_file_name: HashMap<String, PluginData> = HashMap::new();
 	while let Some(data) = set.join_next().await {
-		if let Some(data) = data?? {
+		if let Some((file_name, data)) = data?? {
 			if let Some(i) = index_by_name.get(&*data.string_table.name) {
 				data_by_index.insert(*i as i32, data);
+			} else {
+				data_by_file_name.insert(file_name, data);
+			}
+		}
+	}
+
+	if names.keys().any(|i| !data_by_index.contains_key(i)) {
+		let exe_path = exe_path.to_owned();
+		let original_filenames = blocking!(read_dllblock_original_filenames(&exe_path)).await?
+			.inspect_err(|e| warn!("Could not read plugin version information: {e}"))
+			.unwrap_or_default();
+		for (i, file_name) in original_filenames {
+			if !names.contains_key(&i) || data_by_index.contains_key(&i) { continue }
+			if let Some(data) = data_by_file_name.remove(&file_name.to_lowercase()) {
+				data_by_index.insert(i, data);
 			}
 		}
 	}
@@ -515,11 +811,3 @@ pub async fn load_editor_plugins_by_name(names: &HashMap<i32, String>) -> Result
 	}
 	Ok(data_by_index)
 }
-
-pub async fn run_game(game_path: &Path) -> Result<u32> {
-	log::info!("Starting game... {}", &game_path.to_string_lossy());
-	let child = Command::new(game_path)
-		.spawn()
-		.context("Failed to start game")?;
-	child.id().context("Failed to start game")
-}
@@ -5,7 +5,7 @@ use fs_err::tokio as fs;
 use serde::{Serialize, Deserialize};
 use anyhow::{anyhow, Result, Context};
 use serde_alias::serde_alias;
-use crate::{convert_to_debug_build, convert_to_release_build, get_cache_dir_path, mod_cache_dir_path, PeResource};
+use crate::{convert_to_debug_build, convert_to_release_build, get_cache_dir_path, mod_cache_dir_path, PeResource, ResourceId, EDITOR_PLUGINS_CACHE_VERSION};
 pub use towermod_cstc::{plugin::PluginData, AppBlock, EventBlock, ImageBlock, LevelBlock};
 
 
@@ -66,10 +66,12 @@ impl Game {
 		}
 		let data_hash = Some(Self::get_data_hash(&path).await?);
 		let file_hash = format!("{:x?}", md5::compute(&bytes));
-		let mut game_type = GameType::Other;
-		if file_name.to_lowercase().contains("towerclimb") {
-			game_type = GameType::Towerclimb;
-		}
+		let game_type = match crate::load_known_games().await.find(&file_hash, data_hash.as_deref()) {
+			Some(known) => known.game_type,
+			// fall back to guessing from the file name
+			None if file_name.to_lowercase().contains("towerclimb") => GameType::Towerclimb,
+			None => GameType::Other,
+		};
 		Ok(Game {
 			data_hash,
 			file_hash,
@@ -163,38 +165,34 @@ impl Game {
 
 	pub async fn read_plugin_data_cached(&self) -> anyhow::Result<(HashMap<i32, String>, HashMap<i32, PluginData>)> {
 		let cache_path = self.game_cache_dir().await?.join("plugin_data");
-		if let Ok(bytes) = fs::read(&cache_path).await {
-			if let Ok(data) = rmp_serde::from_slice(&bytes) {
-				return Ok(data);
-			};
+		if let Some(data) = read_editor_plugins_cache(&cache_path).await {
+			return Ok(data);
 		}
 
 		let mut plugin_names = towermod_cstc::plugin::read_dllblock_names(self.game_path()?).await?;
 		plugin_names.insert(-1, String::from("System"));
-		let mut editor_plugins = crate::load_editor_plugins_by_name(&plugin_names).await?;
+		let mut editor_plugins = crate::load_editor_plugins_by_name(self.game_path()?, &plugin_names).await?;
 		editor_plugins.insert(-1, towermod_cstc::get_system_plugin());
 		let data = (plugin_names, editor_plugins);
 
 		// Write to cache
-		fs::write(&cache_path, rmp_serde::to_vec(&data)?).await?;
+		write_editor_plugins_cache(&cache_path, &data).await?;
 
 		Ok(data)
 	}
 
 	pub async fn load_editor_plugins(&self) -> Result<(HashMap<i32, PluginData>, HashMap<i32, String>)> {
 		let plugin_cache = self.game_cache_dir().await?.join("plugindata.msgpack");
-		if let Ok(data) = fs::read(&plugin_cache).await {
+		if let Some(result) = read_editor_plugins_cache(&plugin_cache).await {
 			// Read from cache
-			let result: (HashMap<i32, PluginData>, HashMap<i32, String>) = rmp_serde::from_slice(&data)?;
 			Ok(result)
 		} else {
 			let plugin_names = towermod_cstc::plugin::read_dllblock_names(&self.game_path()?).await?;
-			let editor_plugins = crate::load_editor_plugins_by_name(&plugin_names).await?;
+			let editor_plugins = crate::load_editor_plugins_by_name(self.game_path()?, &plugin_names).await?;
 			let result = (editor_plugins, plugin_names);
 
 			// Cache for next time
-			let data = rmp_serde::to_vec(&result)?;
-			fs::write(&plugin_cache, &data).await?;
+			write_editor_plugins_cache(&plugin_cache, &result).await?;
 
 			Ok(result)
 		}
@@ -229,6 +227,19 @@ impl Game {
 	}
 }
 
+/// Read editor plugin data cached by [`write_editor_plugins_cache`].
+/// Returns `None` if the cache is missing, unreadable, or was written by another [`EDITOR_PLUGINS_CACHE_VERSION`].
+pub async fn read_editor_plugins_cache<T: serde::de::DeserializeOwned>(path: &Path) -> Option<T> {
+	let bytes = fs::read(path).await.ok()?;
+	let (version, data): (u32, T) = rmp_serde::from_slice(&bytes).ok()?;
+	(version == EDITOR_PLUGINS_CACHE_VERSION).then_some(data)
+}
+
+pub async fn write_editor_plugins_cache<T: Serialize>(path: &Path, data: &T) -> Result<()> {
+	fs::write(path, rmp_serde::to_vec(&(EDITOR_PLUGINS_CACHE_VERSION, data))?).await?;
+	Ok(())
+}
+
 #[serde_alias(SnakeCase, CamelCase)]
 #[derive(Debug, Clone, Serialize, Deserialize)]
 
@@ -244,6 +255,17 @@ pub struct Project {
 	pub project_type: ProjectType,
 	#[serde(default)]
 	pub description: String,
+	/// Convert version 1 Text objects (from older Construct Classic games) to version 2 when saving.
+	/// Their legacy data is discarded, see [`towermod_cstc::TextObjectData::upgrade`].
+	#[serde(default)]
+	pub upgrade_legacy_text: bool,
+	/// File names of Construct Classic runtime plugins (from `Plugins/Runtime`) appended to the game's DLLBLOCK.
+	/// They take the plugin IDs following the game's own plugins, in order.
+	#[serde(default)]
+	pub added_plugins: Vec<String>,
+	/// Resources of the game executable replaced with files from the project directory
+	#[serde(default)]
+	pub resources: Vec<ResourceReplacement>,
 	pub towermod_version: String,
 	/// Date that the project was last saved/exported
 	#[serde(with = "time::serde::rfc3339")]
@@ -268,6 +290,9 @@ impl Project {
 			version,
 			project_type: Default::default(),
 			description: Default::default(),
+			upgrade_legacy_text: false,
+			added_plugins: Vec::new(),
+			resources: Vec::new(),
 			towermod_version: towermod_util::VERSION.to_string(),
 			dir_path: None,
 			date: time::OffsetDateTime::now_utc(),
@@ -277,6 +302,9 @@ impl Project {
 		let file_path = file_path.as_ref();
 		let s = fs::read_to_string(&file_path).await?;
 		let mut p: Self = toml::from_str::<Self>(&s)?;
+		for resource in &p.resources {
+			resource.validate()?;
+		}
 		p.dir_path = Some(file_path.parent().unwrap().to_owned());
 		Ok(p)
 	}
@@ -291,7 +319,11 @@ impl Project {
 
 	/// # Errors
 	/// - dir_path not set
+	/// - a resource replacement points outside of the project directory
 	pub async fn save(&self) -> Result<()> {
+		for resource in &self.resources {
+			resource.validate()?;
+		}
 		let file_path = self.dir_path()?.join("manifest.toml");
 		let mut project = self.clone();
 		project.clear_unpersisted_fields();
@@ -326,6 +358,44 @@ impl Project {
 	}
 }
 
+#[serde_alias(SnakeCase, CamelCase)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+/// A resource of the game executable to replace when exporting, declared in manifest.toml as
+/// ```toml
+/// [[resources]]
+/// type = "WAVE"
+/// name = 105
+/// file = "resources/jump.wav"
+/// ```
+pub struct ResourceReplacement {
+	#[serde(rename = "type")]
+	pub res_type: ResourceId,
+	pub name: ResourceId,
+	/// Defaults to the language of the existing resource
+	#[serde(default)]
+	pub language: Option<u16>,
+	/// Path relative to the project directory, which it can't leave
+	pub file: PathBuf,
+}
+impl ResourceReplacement {
+	/// Fails if `file` is absolute or has `..` components, which would read files from outside the project
+	pub fn validate(&self) -> Result<()> {
+		let file = self.file.to_string_lossy();
+		// check both separators, since the manifest may have been written on another platform
+		let absolute = self.file.has_root() || file.starts_with(['/', '\\']) || file.get(1..2) == Some(":");
+		if absolute || file.split(['/', '\\']).any(|c| c == "..") {
+			anyhow::bail!("Resource file {file} must be a path inside the project directory");
+		}
+		Ok(())
+	}
+
+	/// Path of the replacement file within an exported mod
+	pub fn zip_path(&self) -> String {
+		format!("resources/{}", self.file.to_string_lossy().replace('\\', "/"))
+	}
+}
+
 #[serde_alias(SnakeCase, CamelCase)]
 #[derive(Debug, Clone, Derivative, Serialize, Deserialize)]
 #[derivative(Default)]
@@ -341,6 +411,12 @@ pub struct ModInfo {
 	pub mod_type: ModType,
 	#[serde(default)]
 	pub description: String,
+	/// Runtime plugins bundled in the mod's `plugins/` directory, see [`Project::added_plugins`]
+	#[serde(default)]
+	pub added_plugins: Vec<String>,
+	/// Resource replacements bundled in the mod's `resources/` directory, see [`ResourceReplacement::zip_path`]
+	#[serde(default)]
+	pub resources: Vec<ResourceReplacement>,
 	pub towermod_version: String,
 	/// Date that the mod was exported
 	#[serde(with = "time::serde::rfc3339")]
@@ -381,6 +457,8 @@ impl ModInfo {
 			version: project.version,
 			mod_type,
 			description: project.description,
+			added_plugins: project.added_plugins,
+			resources: project.resources,
 			towermod_version: project.towermod_version,
 			date: project.date,
 			file_path: None,
@@ -429,6 +507,9 @@ impl ModInfo {
 		let mut s = String::new();
 		file.read_to_string(&mut s)?;
 		let mod_info: Self = toml::from_str(&s).context("couldn't parse manifest.toml in zip")?;
+		for resource in &mod_info.resources {
+			resource.validate()?;
+		}
 		Ok(mod_info)
 	}
 }
@@ -0,0 +1,253 @@
+//! Locating Steam installations, their library folders, and installed apps
+use std::path::{Path, PathBuf};
+use anyhow::{Context, Result};
+
+/// Steam app ID of TowerClimb
+pub const TOWERCLIMB_APP_ID: u32 = 396640;
+
+/// A value in Valve's KeyValues text format (`.vdf`/`.acf` files)
+#[derive(Debug, Clone, PartialEq)]
+pub enum Vdf {
+	String(String),
+	Object(Vec<(String, Vdf)>),
+}
+
+impl Vdf {
+	/// Parse a document, returning its root key and value
+	pub fn parse(text: &str) -> Result<(String, Vdf)> {
+		let mut tokens = VdfTokens { chars: text.chars().peekable() };
+		let key = match tokens.next().transpose()? {
+			Some(VdfToken::String(key)) => key,
+			_ => anyhow::bail!("Expected root key"),
+		};
+		let value = match tokens.next().transpose()? {
+			Some(VdfToken::String(s)) => Vdf::String(s),
+			Some(VdfToken::Open) => Vdf::Object(parse_vdf_object(&mut tokens)?),
+			_ => anyhow::bail!("Expected value for root key {key:?}"),
+		};
+		Ok((key, value))
+	}
+
+	/// Keys are case-insensitive
+	pub fn get(&self, key: &str) -> Option<&Vdf> {
+		self.entries().iter().find(|(k, _)| k.eq_ignore_ascii_case(key)).map(|(_, v)| v)
+	}
+
+	pub fn get_str(&self, key: &str) -> Option<&str> {
+		self.get(key).and_then(|v| v.as_str())
+	}
+
+	pub fn as_str(&self) -> Option<&str> {
+		match self {
+			Vdf::String(s) => Some(s),
+			Vdf::Object(_) => None,
+		}
+	}
+
+	pub fn entries(&self) -> &[(String, Vdf)] {
+		match self {
+			Vdf::String(_) => &[],
+			Vdf::Object(entries) => entries,
+		}
+	}
+}
+
+enum VdfToken {
+	String(String),
+	Open,
+	Close,
+}
+
+struct VdfTokens<'a> {
+	chars: std::iter::Peekable<std::str::Chars<'a>>,
+}
+
+impl Iterator for VdfTokens<'_> {
+	type Item = Result<VdfToken>;
+	fn next(&mut self) -> Option<Self::Item> {
+		loop {
+			let c = self.chars.next()?;
+			match c {
+				'{' => return Some(Ok(VdfToken::Open)),
+				'}' => return Some(Ok(VdfToken::Close)),
+				'"' => {
+					let mut s = String::new();
+					loop {
+						match self.chars.next() {
+							None => return Some(Err(anyhow::anyhow!("Unterminated string"))),
+							Some('"') => break,
+							Some('\\') => match self.chars.next() {
+								Some('n') => s.push('\n'),
+								Some('t') => s.push('\t'),
+								Some(c) => s.push(c),
+								None => return Some(Err(anyhow::anyhow!("Unterminated string"))),
+							},
+							Some(c) => s.push(c),
+						}
+					}
+					return Some(Ok(VdfToken::String(s)))
+				},
+				'/' if self.chars.peek() == Some(&'/') => {
+					while self.chars.next_if(|c| *c != '\n').is_some() {}
+				},
+				// platform conditionals such as `[$WIN32]` are ignored
+				'[' => {
+					while self.chars.next_if(|c| *c != ']').is_some() {}
+					self.chars.next();
+				},
+				c if c.is_whitespace() => {},
+				c => {
+					let mut s = String::from(c);
+					while let Some(c) = self.chars.next_if(|c| !c.is_whitespace() && !matches!(c, '"' | '{' | '}')) {
+						s.push(c);
+					}
+					return Some(Ok(VdfToken::String(s)))
+				},
+			}
+		}
+	}
+}
+
+fn parse_vdf_object(tokens: &mut VdfTokens) -> Result<Vec<(String, Vdf)>> {
+	let mut entries = Vec::new();
+	loop {
+		let key = match tokens.next().transpose()? {
+			Some(VdfToken::Close) => return Ok(entries),
+			Some(VdfToken::String(key)) => key,
+			Some(VdfToken::Open) => anyhow::bail!("Expected key, found '{{'"),
+			None => anyhow::bail!("Unexpected end of file"),
+		};
+		let value = match tokens.next().transpose()? {
+			Some(VdfToken::String(s)) => Vdf::String(s),
+			Some(VdfToken::Open) => Vdf::Object(parse_vdf_object(tokens)?),
+			_ => anyhow::bail!("Expected value for key {key:?}"),
+		};
+		entries.push((key, value));
+	}
+}
+
+/// An app installed in a Steam library
+#[derive(Debug, Clone, PartialEq)]
+pub struct SteamApp {
+	pub app_id: u32,
+	pub name: String,
+	/// Library folder containing the app's `steamapps` directory
+	pub library_path: PathBuf,
+	/// e.g. `<library>/steamapps/common/TowerClimb`
+	pub install_path: PathBuf,
+	/// Steam installation the library belongs to
+	pub steam_path: PathBuf,
+}
+
+impl SteamApp {
+	/// Proton prefix of the app (`<library>/steamapps/compatdata/<app id>`), if it exists
+	pub fn compat_data_path(&self) -> Option<PathBuf> {
+		[&self.library_path, &self.steam_path].into_iter()
+			.map(|library| library.join("steamapps/compatdata").join(self.app_id.to_string()))
+			.find(|path| path.is_dir())
+	}
+}
+
+/// Usual locations of the Steam installation (native, legacy symlink and Flatpak on Linux)
+pub fn steam_dir_candidates() -> Vec<PathBuf> {
+	let mut dirs = Vec::new();
+	#[cfg(windows)]
+	{
+		use windows::Win32::System::Registry::HKEY_CURRENT_USER;
+		if let Ok(steam_path) = towermod_win32::registry::read_reg_sz(HKEY_CURRENT_USER, r"Software\Valve\Steam", "SteamPath") {
+			dirs.push(PathBuf::from(steam_path));
+		}
+		let program_files_x86: Result<String> = unsafe {
+			use windows::Win32::{Foundation::HANDLE, UI::Shell};
+			try { Shell::SHGetKnownFolderPath(&Shell::FOLDERID_ProgramFilesX86, Shell::KNOWN_FOLDER_FLAG(0), HANDLE::default())?.to_string()? }
+		};
+		if let Ok(program_files_x86) = program_files_x86 {
+			dirs.push(PathBuf::from(program_files_x86).join("Steam"));
+		}
+	}
+	#[cfg(not(windows))]
+	{
+		let base_dirs = directories::BaseDirs::new().unwrap();
+		dirs.push(base_dirs.data_local_dir().join("Steam"));
+		dirs.push(base_dirs.home_dir().join(".steam/steam"));
+		dirs.push(base_dirs.home_dir().join(".var/app/com.valvesoftware.Steam/.local/share/Steam"));
+	}
+	// `.steam/steam` is usually a symlink to one of the others
+	let mut seen = Vec::new();
+	dirs.retain(|dir| {
+		if !dir.is_dir() { return false }
+		let dir = dir.canonicalize().unwrap_or_else(|_| dir.clone());
+		if seen.contains(&dir) { return false }
+		seen.push(dir);
+		true
+	});
+	dirs
+}
+
+/// Library folders of a Steam installation, listed in `steamapps/libraryfolders.vdf`.
+/// Always includes the installation directory itself.
+pub fn steam_library_folders(steam_path: &Path) -> Vec<PathBuf> {
+	let mut libraries = vec![steam_path.to_path_buf()];
+	let vdf_path = steam_path.join("steamapps/libraryfolders.vdf");
+	let parsed = fs_err::read_to_string(&vdf_path).map_err(anyhow::Error::from).and_then(|s| Vdf::parse(&s));
+	let folders = match parsed {
+		Ok((_, folders)) => folders,
+		Err(e) => {
+			if vdf_path.exists() { log::warn!("Could not read {}: {e}", vdf_path.display()) }
+			return libraries
+		},
+	};
+	for (key, value) in folders.entries() {
+		// older versions store the path directly under the index
+		let path = match value {
+			Vdf::String(path) if key.parse::<u32>().is_ok() => path,
+			Vdf::Object(_) => match value.get_str("path") {
+				Some(path) => path,
+				None => continue,
+			},
+			_ => continue,
+		};
+		let path = PathBuf::from(path);
+		if !libraries.iter().any(|l| same_dir(l, &path)) {
+			libraries.push(path);
+		}
+	}
+	libraries
+}
+
+/// Read `steamapps/appmanifest_<app id>.acf` in a library
+pub fn read_app_manifest(library_path: &Path, app_id: u32) -> Result<(String, PathBuf)> {
+	let manifest_path = library_path.join("steamapps").join(format!("appmanifest_{app_id}.acf"));
+	let (_, manifest) = Vdf::parse(&fs_err::read_to_string(&manifest_path)?)?;
+	let name = manifest.get_str("name").unwrap_or_default().to_string();
+	let install_dir = manifest.get_str("installdir").context("App manifest has no installdir")?;
+	Ok((name, library_path.join("steamapps/common").join(install_dir)))
+}
+
+/// Find an installed app in any library of the given Steam installations
+pub fn find_steam_app(steam_dirs: &[PathBuf], app_id: u32) -> Option<SteamApp> {
+	for steam_path in steam_dirs {
+		for library_path in steam_library_folders(steam_path) {
+			match read_app_manifest(&library_path, app_id) {
+				Ok((name, install_path)) if install_path.is_dir() => {
+					return Some(SteamApp { app_id, name, library_path, install_path, steam_path: steam_path.clone() })
+				},
+				Ok(_) => {},
+				Err(e) => {
+					if library_path.join("steamapps").is_dir() {
+						log::debug!("App {app_id} not found in {}: {e}", library_path.display());
+					}
+				},
+			}
+		}
+	}
+	None
+}
+
+pub fn find_towerclimb() -> Option<SteamApp> {
+	find_steam_app(&steam_dir_candidates(), TOWERCLIMB_APP_ID)
+}
+
+fn same_dir(a: &Path, b: &Path) -> bool {
+	a == b || a.canonicalize().ok().is_some_and(|a| Some(a) == b.canonicalize().ok())
+}
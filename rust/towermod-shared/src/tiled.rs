@@ -0,0 +1,473 @@
+//! Import and export of layouts as [Tiled](https://www.mapeditor.org/) maps (TMX or JSON)
+//!
+//! Layers become object layers and instances become objects whose type is the object type name.
+//! Sprite images are exported as an image collection tileset, with paths relative to the map file.
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write;
+use anyhow::{Context, Result};
+use indexmap::IndexMap;
+use quick_xml::{escape::escape, events::{BytesStart, Event}};
+use serde::{Deserialize, Serialize};
+use serde_alias::serde_alias;
+use towermod_cstc as cstc;
+use crate::{cstc_editing::{CstcData, EdLayout, EdObjectInstance, VariableType, VariableValue}, layout_renderer};
+
+/// Grid size of the exported map. Only used by Tiled for snapping, objects are placed freely.
+const TILE_SIZE: i32 = 32;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TiledMap {
+	#[serde(default)]
+	pub width: i32,
+	#[serde(default)]
+	pub height: i32,
+	#[serde(default)]
+	pub tilewidth: i32,
+	#[serde(default)]
+	pub tileheight: i32,
+	#[serde(default)]
+	pub layers: Vec<TiledLayer>,
+	#[serde(default)]
+	pub tilesets: Vec<TiledTileset>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TiledLayer {
+	#[serde(default)]
+	pub id: i32,
+	#[serde(default)]
+	pub name: String,
+	/// Only "objectgroup" layers are exported or imported
+	#[serde(rename = "type")]
+	pub layer_type: String,
+	#[serde(default = "default_opacity")]
+	pub opacity: f64,
+	#[serde(default)]
+	pub objects: Vec<TiledObject>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TiledObject {
+	pub id: i32,
+	#[serde(default)]
+	pub name: String,
+	/// Object type name
+	#[serde(rename = "type", alias = "class", default)]
+	pub object_type: String,
+	pub x: f64,
+	pub y: f64,
+	#[serde(default)]
+	pub width: f64,
+	#[serde(default)]
+	pub height: f64,
+	#[serde(default)]
+	pub rotation: f64,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub gid: Option<u32>,
+	#[serde(default, skip_serializing_if = "Vec::is_empty")]
+	pub properties: Vec<TiledProperty>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TiledProperty {
+	pub name: String,
+	/// "string", "float", "int" or "bool"
+	#[serde(rename = "type", default = "default_property_type")]
+	pub property_type: String,
+	pub value: serde_json::Value,
+}
+
+/// Image collection tileset
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TiledTileset {
+	pub firstgid: u32,
+	pub name: String,
+	pub tilewidth: i32,
+	pub tileheight: i32,
+	pub tilecount: i32,
+	pub columns: i32,
+	pub tiles: Vec<TiledTile>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TiledTile {
+	pub id: u32,
+	/// Path relative to the map file
+	pub image: String,
+	pub imagewidth: i32,
+	pub imageheight: i32,
+}
+
+fn default_opacity() -> f64 { 1.0 }
+fn default_property_type() -> String { String::from("string") }
+
+#[serde_alias(SnakeCase)]
+#[derive(Default, Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TiledImport {
+	/// IDs of existing instances that were updated
+	pub updated: Vec<i32>,
+	/// IDs of newly created instances
+	pub created: Vec<i32>,
+	pub unknown_object_types: Vec<String>,
+	/// Object layers with no matching layer (by id or name) in the layout
+	pub unknown_layers: Vec<String>,
+}
+
+/// Changes to apply to the project after importing a map
+#[derive(Default, Debug)]
+pub struct TiledImportChanges {
+	/// Layer ID -> updated and new instances that belong on it
+	pub layers: IndexMap<i32, Vec<EdObjectInstance>>,
+	pub report: TiledImport,
+}
+impl TiledImportChanges {
+	/// Updated instances are replaced in place if they are already on the right layer, otherwise they are moved to the end of it.
+	/// New instances are appended.
+	pub fn apply(self, data: &mut CstcData) {
+		let layer_ids: HashSet<i32> = data.layouts.iter().flat_map(|l| l.layers.iter()).map(|l| l.id).collect();
+		let target_layers: HashMap<i32, i32> = self.layers.iter()
+			.filter(|(layer_id, _)| layer_ids.contains(layer_id))
+			.flat_map(|(layer_id, instances)| instances.iter().map(|o| (o.id, *layer_id)))
+			.collect();
+		for layer in data.layouts.iter_mut().flat_map(|l| l.layers.iter_mut()) {
+			layer.objects.retain(|o| target_layers.get(&o.id).is_none_or(|id| *id == layer.id));
+		}
+		for (layer_id, instances) in self.layers {
+			let Some(layer) = data.layouts.iter_mut().flat_map(|l| l.layers.iter_mut()).find(|l| l.id == layer_id) else { continue };
+			let positions: HashMap<i32, usize> = layer.objects.iter().enumerate().map(|(i, o)| (o.id, i)).collect();
+			for obj in instances {
+				match positions.get(&obj.id) {
+					Some(i) => layer.objects[*i] = obj,
+					None => layer.objects.push(obj),
+				}
+			}
+		}
+	}
+}
+
+/// Path of an exported image, relative to the map file
+pub fn image_path(image_id: i32) -> String {
+	format!("images/{image_id}.png")
+}
+
+/// Export a layout to a Tiled map. Returns the map along with the IDs of the images referenced by its tileset.
+pub fn export_layout(data: &CstcData, layout: &EdLayout) -> (TiledMap, Vec<i32>) {
+	let metadata: HashMap<i32, &cstc::ImageMetadata> = data.image_block.iter().map(|m| (m.id, m)).collect();
+	let mut image_ids = layout_renderer::sprite_image_ids(data, layout).into_iter().collect::<Vec<_>>();
+	image_ids.sort();
+	let image_size = |id: i32| metadata.get(&id).map(|m| (m.collision_width as i32, m.collision_height as i32)).unwrap_or((0, 0));
+
+	let tiles = image_ids.iter().enumerate().map(|(i, id)| {
+		let (imagewidth, imageheight) = image_size(*id);
+		TiledTile { id: i as u32, image: image_path(*id), imagewidth, imageheight }
+	}).collect::<Vec<_>>();
+	let tileset = TiledTileset {
+		firstgid: 1,
+		name: String::from("images"),
+		tilewidth: tiles.iter().map(|t| t.imagewidth).max().unwrap_or(0),
+		tileheight: tiles.iter().map(|t| t.imageheight).max().unwrap_or(0),
+		tilecount: tiles.len() as i32,
+		columns: 0,
+		tiles,
+	};
+
+	let layers = layout.layers.iter().map(|layer| {
+		let objects = layer.objects.iter().map(|obj| {
+			let object_type = data.object_types.get(&obj.object_type_id);
+			let properties = object_type.map(|t| t.private_variables.keys().filter_map(|name| {
+				let (property_type, value) = match obj.private_variables.get(name)? {
+					VariableValue::Number(n) => ("float", serde_json::json!(n)),
+					VariableValue::String(s) => ("string", serde_json::json!(s)),
+				};
+				Some(TiledProperty { name: name.clone(), property_type: property_type.to_string(), value })
+			}).collect()).unwrap_or_default();
+
+			let (mut x, mut y) = (obj.x as f64, obj.y as f64);
+			let mut gid = None;
+			if let Some((image_id, offset)) = tile_anchor_offset(data, &metadata, obj, obj.width, obj.height) {
+				// tile objects are positioned by their bottom-left corner
+				x += offset.0;
+				y += offset.1;
+				gid = image_ids.iter().position(|id| *id == image_id).map(|i| i as u32 + tileset.firstgid);
+			}
+			TiledObject {
+				id: obj.id,
+				name: String::new(),
+				object_type: object_type.map(|t| t.name.clone()).unwrap_or_default(),
+				x,
+				y,
+				width: obj.width as f64,
+				height: obj.height as f64,
+				rotation: obj.angle as f64,
+				gid,
+				properties,
+			}
+		}).collect();
+		TiledLayer { id: layer.id, name: layer.name.clone(), layer_type: String::from("objectgroup"), opacity: layer.opacity as f64, objects }
+	}).collect();
+
+	let map = TiledMap {
+		width: (layout.width + TILE_SIZE - 1) / TILE_SIZE,
+		height: (layout.height + TILE_SIZE - 1) / TILE_SIZE,
+		tilewidth: TILE_SIZE,
+		tileheight: TILE_SIZE,
+		layers,
+		tilesets: if tileset.tiles.is_empty() { vec![] } else { vec![tileset] },
+	};
+	(map, image_ids)
+}
+
+/// Compute the changes needed to apply a Tiled map to a layout.
+/// Objects whose id matches an instance in the layout update that instance (moving it to the object's layer),
+/// all other objects create new instances with ids starting from `first_new_id`.
+pub fn import_map(data: &CstcData, layout: &EdLayout, map: &TiledMap, first_new_id: i32) -> TiledImportChanges {
+	let metadata: HashMap<i32, &cstc::ImageMetadata> = data.image_block.iter().map(|m| (m.id, m)).collect();
+	let mut changes = TiledImportChanges::default();
+	let mut next_id = first_new_id;
+	for tiled_layer in map.layers.iter().filter(|l| l.layer_type == "objectgroup") {
+		let layer = layout.layers.iter().find(|l| l.id == tiled_layer.id)
+			.or_else(|| layout.layers.iter().find(|l| l.name == tiled_layer.name));
+		let Some(layer) = layer else {
+			changes.report.unknown_layers.push(tiled_layer.name.clone());
+			continue
+		};
+		for object in &tiled_layer.objects {
+			let existing = layout.layers.iter().flat_map(|l| l.objects.iter()).find(|o| o.id == object.id);
+			let mut obj = match existing {
+				Some(obj) => obj.clone(),
+				None => {
+					let Some(object_type) = data.object_types.values().find(|t| t.name == object.object_type) else {
+						if !changes.report.unknown_object_types.contains(&object.object_type) {
+							changes.report.unknown_object_types.push(object.object_type.clone());
+						}
+						continue
+					};
+					let Some(obj) = new_instance(data, object_type.id, next_id) else { continue };
+					next_id += 1;
+					obj
+				}
+			};
+			obj.width = object.width.round() as i32;
+			obj.height = object.height.round() as i32;
+			obj.angle = object.rotation as f32;
+			let (mut x, mut y) = (object.x, object.y);
+			if object.gid.is_some() {
+				if let Some((_, offset)) = tile_anchor_offset(data, &metadata, &obj, obj.width, obj.height) {
+					x -= offset.0;
+					y -= offset.1;
+				}
+			}
+			obj.x = x.round() as i32;
+			obj.y = y.round() as i32;
+			if let Some(object_type) = data.object_types.get(&obj.object_type_id) {
+				for property in &object.properties {
+					let Some(value_type) = object_type.private_variables.get(&property.name) else { continue };
+					let value = match (value_type, &property.value) {
+						(VariableType::Number, serde_json::Value::Number(n)) => VariableValue::Number(n.as_f64().unwrap_or(0.0)),
+						(VariableType::Number, serde_json::Value::String(s)) => VariableValue::Number(s.parse().unwrap_or(0.0)),
+						(VariableType::Number, serde_json::Value::Bool(b)) => VariableValue::Number(*b as i32 as f64),
+						(VariableType::String, serde_json::Value::String(s)) => VariableValue::String(s.clone()),
+						(VariableType::String, v) => VariableValue::String(v.to_string()),
+						_ => continue,
+					};
+					obj.private_variables.insert(property.name.clone(), value);
+				}
+			}
+			if existing.is_some() {
+				changes.report.updated.push(obj.id);
+			} else {
+				changes.report.created.push(obj.id);
+			}
+			changes.layers.entry(layer.id).or_default().push(obj);
+		}
+	}
+	changes
+}
+
+/// New instance with the object type's default variables. Sprites share the animation of the type's first instance.
+fn new_instance(data: &CstcData, object_type_id: i32, id: i32) -> Option<EdObjectInstance> {
+	let object_type = data.object_types.get(&object_type_id)?;
+	let template = data.layouts.iter()
+		.flat_map(|l| l.layers.iter().flat_map(|l| l.objects.iter()))
+		.find(|o| o.object_type_id == object_type_id);
+	let private_variables = object_type.private_variables.iter().map(|(name, value_type)| (name.clone(), (*value_type).into())).collect();
+	let data = match template {
+		Some(template) => template.data.clone(),
+		None => data.new_object_data(object_type_id)?,
+	};
+	Some(EdObjectInstance { id, object_type_id, private_variables, data, filter: -1, ..Default::default() })
+}
+
+/// Offset from an instance's hotspot to the bottom-left corner of its image, which is how Tiled positions tile objects
+fn tile_anchor_offset(data: &CstcData, metadata: &HashMap<i32, &cstc::ImageMetadata>, obj: &EdObjectInstance, width: i32, height: i32) -> Option<(i32, (f64, f64))> {
+	let cstc::ObjectData::Sprite(sprite) = &obj.data else { return None };
+	let image_id = layout_renderer::sprite_image_id(data, sprite)?;
+	let image = metadata.get(&image_id)?;
+	if image.collision_width == 0 || image.collision_height == 0 { return None }
+	let scale_x = width as f64 / image.collision_width as f64;
+	let scale_y = height as f64 / image.collision_height as f64;
+	let (lx, ly) = (-image.hotspot_x as f64 * scale_x, (image.collision_height as f64 - image.hotspot_y as f64) * scale_y);
+	let (sin, cos) = (obj.angle as f64).to_radians().sin_cos();
+	Some((image_id, (lx * cos - ly * sin, lx * sin + ly * cos)))
+}
+
+impl TiledMap {
+	pub fn to_json(&self) -> Result<String> {
+		let mut value = serde_json::to_value(self)?;
+		let map = value.as_object_mut().context("Invalid map")?;
+		map.insert("type".into(), "map".into());
+		map.insert("version".into(), "1.10".into());
+		map.insert("orientation".into(), "orthogonal".into());
+		map.insert("renderorder".into(), "right-down".into());
+		map.insert("infinite".into(), false.into());
+		map.insert("nextlayerid".into(), self.next_layer_id().into());
+		map.insert("nextobjectid".into(), self.next_object_id().into());
+		Ok(serde_json::to_string_pretty(&value)?)
+	}
+
+	pub fn from_json(s: &str) -> Result<Self> {
+		Ok(serde_json::from_str(s)?)
+	}
+
+	pub fn to_tmx(&self) -> String {
+		let mut s = String::new();
+		let _ = self.write_tmx(&mut s);
+		s
+	}
+
+	fn write_tmx(&self, s: &mut String) -> std::fmt::Result {
+		writeln!(s, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+		writeln!(s, r#"<map version="1.10" orientation="orthogonal" renderorder="right-down" width="{}" height="{}" tilewidth="{}" tileheight="{}" infinite="0" nextlayerid="{}" nextobjectid="{}">"#,
+			self.width, self.height, self.tilewidth, self.tileheight, self.next_layer_id(), self.next_object_id())?;
+		for tileset in &self.tilesets {
+			writeln!(s, r#" <tileset firstgid="{}" name="{}" tilewidth="{}" tileheight="{}" tilecount="{}" columns="{}">"#,
+				tileset.firstgid, escape(&tileset.name), tileset.tilewidth, tileset.tileheight, tileset.tilecount, tileset.columns)?;
+			writeln!(s, r#"  <grid orientation="orthogonal" width="1" height="1"/>"#)?;
+			for tile in &tileset.tiles {
+				writeln!(s, r#"  <tile id="{}">"#, tile.id)?;
+				writeln!(s, r#"   <image width="{}" height="{}" source="{}"/>"#, tile.imagewidth, tile.imageheight, escape(&tile.image))?;
+				writeln!(s, r#"  </tile>"#)?;
+			}
+			writeln!(s, r#" </tileset>"#)?;
+		}
+		for layer in self.layers.iter().filter(|l| l.layer_type == "objectgroup") {
+			writeln!(s, r#" <objectgroup id="{}" name="{}" opacity="{}">"#, layer.id, escape(&layer.name), layer.opacity)?;
+			for o in &layer.objects {
+				write!(s, r#"  <object id="{}" type="{}" x="{}" y="{}" width="{}" height="{}""#, o.id, escape(&o.object_type), o.x, o.y, o.width, o.height)?;
+				if o.rotation != 0.0 { write!(s, r#" rotation="{}""#, o.rotation)? }
+				if let Some(gid) = o.gid { write!(s, r#" gid="{gid}""#)? }
+				if o.properties.is_empty() {
+					writeln!(s, "/>")?;
+					continue
+				}
+				writeln!(s, ">")?;
+				writeln!(s, "   <properties>")?;
+				for p in &o.properties {
+					let value = match &p.value {
+						serde_json::Value::String(v) => v.clone(),
+						v => v.to_string(),
+					};
+					writeln!(s, r#"    <property name="{}" type="{}" value="{}"/>"#, escape(&p.name), escape(&p.property_type), escape(&value))?;
+				}
+				writeln!(s, "   </properties>")?;
+				writeln!(s, "  </object>")?;
+			}
+			writeln!(s, " </objectgroup>")?;
+		}
+		writeln!(s, "</map>")
+	}
+
+	/// Parse the map and object layers of a TMX file. Tilesets are not needed for import and are skipped.
+	pub fn from_tmx(s: &str) -> Result<Self> {
+		let mut reader = quick_xml::Reader::from_str(s);
+		reader.config_mut().trim_text(true);
+		let mut map = TiledMap { width: 0, height: 0, tilewidth: 0, tileheight: 0, layers: vec![], tilesets: vec![] };
+		let mut layer: Option<TiledLayer> = None;
+		let mut object: Option<TiledObject> = None;
+		loop {
+			let (e, is_empty) = match reader.read_event()? {
+				Event::Start(e) => (e, false),
+				Event::Empty(e) => (e, true),
+				Event::End(e) => {
+					match e.name().as_ref() {
+						b"object" => if let (Some(o), Some(l)) = (object.take(), layer.as_mut()) { l.objects.push(o) },
+						b"objectgroup" => map.layers.extend(layer.take()),
+						_ => {},
+					}
+					continue
+				}
+				Event::Eof => break,
+				_ => continue,
+			};
+			let attrs = attributes(&e)?;
+			let num = |name: &str| attrs.get(name).and_then(|v| v.parse::<f64>().ok()).unwrap_or(0.0);
+			match e.name().as_ref() {
+				b"map" => {
+					map.width = num("width") as i32;
+					map.height = num("height") as i32;
+					map.tilewidth = num("tilewidth") as i32;
+					map.tileheight = num("tileheight") as i32;
+				},
+				b"objectgroup" => {
+					let l = TiledLayer {
+						id: num("id") as i32,
+						name: attrs.get("name").cloned().unwrap_or_default(),
+						layer_type: String::from("objectgroup"),
+						opacity: attrs.get("opacity").and_then(|v| v.parse().ok()).unwrap_or(1.0),
+						objects: vec![],
+					};
+					if is_empty { map.layers.push(l) } else { layer = Some(l) }
+				},
+				b"object" => {
+					let o = TiledObject {
+						id: num("id") as i32,
+						name: attrs.get("name").cloned().unwrap_or_default(),
+						object_type: attrs.get("type").or(attrs.get("class")).cloned().unwrap_or_default(),
+						x: num("x"),
+						y: num("y"),
+						width: num("width"),
+						height: num("height"),
+						rotation: num("rotation"),
+						// strip flip flags
+						gid: attrs.get("gid").and_then(|v| v.parse::<u32>().ok()).map(|gid| gid & 0x1fffffff),
+						properties: vec![],
+					};
+					match (is_empty, layer.as_mut()) {
+						(true, Some(l)) => l.objects.push(o),
+						(false, _) => object = Some(o),
+						_ => {},
+					}
+				},
+				b"property" => {
+					// only object properties are imported
+					let Some(o) = object.as_mut() else { continue };
+					let property_type = attrs.get("type").cloned().unwrap_or_else(default_property_type);
+					let raw = attrs.get("value").cloned().unwrap_or_default();
+					let value = match property_type.as_str() {
+						"float" | "int" => raw.parse::<f64>().map(serde_json::Value::from).unwrap_or(serde_json::Value::String(raw)),
+						"bool" => serde_json::Value::Bool(raw == "true"),
+						_ => serde_json::Value::String(raw),
+					};
+					o.properties.push(TiledProperty { name: attrs.get("name").cloned().unwrap_or_default(), property_type, value });
+				},
+				_ => {},
+			}
+		}
+		Ok(map)
+	}
+
+	fn next_layer_id(&self) -> i32 {
+		self.layers.iter().map(|l| l.id).max().unwrap_or(0) + 1
+	}
+	fn next_object_id(&self) -> i32 {
+		self.layers.iter().flat_map(|l| l.objects.iter()).map(|o| o.id).max().unwrap_or(0) + 1
+	}
+}
+
+fn attributes(e: &BytesStart) -> Result<HashMap<String, String>> {
+	e.attributes().map(|a| {
+		let a = a?;
+		Ok((String::from_utf8_lossy(a.key.as_ref()).into_owned(), a.unescape_value()?.into_owned()))
+	}).collect()
+}
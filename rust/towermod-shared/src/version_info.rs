@@ -0,0 +1,201 @@
+//! Reading the version information resource (`VS_VERSIONINFO`) of executables and plugins
+use std::path::Path;
+use anyhow::{Context, Result};
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+use serde_alias::serde_alias;
+use crate::{list_pe_resources, read_pe_resource, ResourceId};
+
+const RT_VERSION: u16 = 16;
+const FIXED_FILE_INFO_SIGNATURE: u32 = 0xfeef04bd;
+const LANGUAGE_EN_US: u16 = 0x0409;
+
+#[serde_alias(SnakeCase)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VersionInfo {
+	pub fixed: Option<FixedFileInfo>,
+	/// `StringFileInfo` tables, in the order they are stored
+	pub string_tables: Vec<VersionStringTable>,
+	/// (language, code page) pairs listed in `VarFileInfo\Translation`
+	pub translations: Vec<(u16, u16)>,
+}
+
+/// `VS_FIXEDFILEINFO`
+#[serde_alias(SnakeCase)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FixedFileInfo {
+	pub file_version: [u16; 4],
+	pub product_version: [u16; 4],
+	pub file_flags: u32,
+	pub file_os: u32,
+	pub file_type: u32,
+	pub file_subtype: u32,
+}
+
+#[serde_alias(SnakeCase)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VersionStringTable {
+	pub language: u16,
+	pub code_page: u16,
+	pub strings: IndexMap<String, String>,
+}
+
+impl VersionInfo {
+	pub fn from_bin(bin: &[u8]) -> Result<Self> {
+		let (root, _) = read_block(bin)?;
+		if root.key != "VS_VERSION_INFO" {
+			anyhow::bail!("Not a VS_VERSIONINFO resource");
+		}
+		let mut info = VersionInfo {
+			fixed: read_fixed_file_info(root.value),
+			..Default::default()
+		};
+		for child in read_children(root.children)? {
+			match &*child.key {
+				"StringFileInfo" => {
+					for table in read_children(child.children)? {
+						let lang_code_page = u32::from_str_radix(&table.key, 16).unwrap_or_default();
+						let strings = read_children(table.children)?.into_iter()
+							.map(|s| (s.key, read_text(s.value)))
+							.collect();
+						info.string_tables.push(VersionStringTable {
+							language: (lang_code_page >> 16) as u16,
+							code_page: lang_code_page as u16,
+							strings,
+						});
+					}
+				},
+				"VarFileInfo" => {
+					for var in read_children(child.children)?.into_iter().filter(|v| v.key == "Translation") {
+						info.translations.extend(var.value.chunks_exact(4).map(|c| (u16::from_le_bytes([c[0], c[1]]), u16::from_le_bytes([c[2], c[3]]))));
+					}
+				},
+				_ => {},
+			}
+		}
+		Ok(info)
+	}
+
+	/// Look up a string in the `StringFileInfo` tables.
+	/// Tables listed in `Translation` are checked first, then US English, then any other table.
+	pub fn string(&self, key: &str) -> Option<&str> {
+		let preferred = self.translations.iter()
+			.filter_map(|(language, code_page)| self.string_tables.iter().find(|t| t.language == *language && t.code_page == *code_page))
+			.chain(self.string_tables.iter().filter(|t| t.language == LANGUAGE_EN_US))
+			.chain(self.string_tables.iter());
+		preferred
+			.filter_map(|t| t.strings.get(key))
+			.map(|s| s.as_str())
+			.find(|s| !s.is_empty())
+	}
+	pub fn original_filename(&self) -> Option<&str> { self.string("OriginalFilename") }
+	pub fn internal_name(&self) -> Option<&str> { self.string("InternalName") }
+	pub fn file_description(&self) -> Option<&str> { self.string("FileDescription") }
+	pub fn file_version(&self) -> Option<&str> { self.string("FileVersion") }
+	pub fn product_name(&self) -> Option<&str> { self.string("ProductName") }
+	pub fn product_version(&self) -> Option<&str> { self.string("ProductVersion") }
+	pub fn company_name(&self) -> Option<&str> { self.string("CompanyName") }
+}
+
+/// Read the version information of an executable or DLL
+pub fn read_version_info(pe_path: impl AsRef<Path>) -> Result<VersionInfo> {
+	let pe_path = pe_path.as_ref();
+	let resource = list_pe_resources(pe_path)?.into_iter()
+		.find(|r| r.res_type == ResourceId::Int(RT_VERSION))
+		.context("No version information found")?;
+	let bin = read_pe_resource(pe_path, &resource.res_type, &resource.name, Some(resource.language))?;
+	VersionInfo::from_bin(&bin)
+}
+
+/// Infer the original filename of a plugin
+/// TODO: 'OriginalFilename' is not always reliable
+pub fn get_original_filename(path: &Path) -> Result<String> {
+	let version_info = read_version_info(path)?;
+	Ok(version_info.original_filename().context("No OriginalFilename in version information")?.to_string())
+}
+
+/// A node of the version information tree
+struct Block<'a> {
+	key: String,
+	value: &'a [u8],
+	children: &'a [u8],
+}
+
+fn align4(offset: usize) -> usize {
+	offset.next_multiple_of(4)
+}
+
+fn read_u16(bin: &[u8], offset: usize) -> u16 {
+	u16::from_le_bytes([bin[offset], bin[offset + 1]])
+}
+
+/// Returns the block and the (aligned) number of bytes it takes up
+fn read_block(bin: &[u8]) -> Result<(Block<'_>, usize)> {
+	if bin.len() < 6 {
+		anyhow::bail!("Version information block is truncated");
+	}
+	let length = read_u16(bin, 0) as usize;
+	let value_length = read_u16(bin, 2) as usize;
+	let is_text = read_u16(bin, 4) == 1;
+	if length < 6 || length > bin.len() {
+		anyhow::bail!("Invalid version information block length {length}");
+	}
+	let bin = &bin[..length];
+
+	let mut offset = 6;
+	let mut key = Vec::new();
+	while offset + 1 < length {
+		let unit = read_u16(bin, offset);
+		offset += 2;
+		if unit == 0 { break }
+		key.push(unit);
+	}
+	let value_start = align4(offset).min(length);
+	// the length of text values is given in UTF-16 code units
+	let value_size = if is_text { value_length * 2 } else { value_length };
+	let value_end = (value_start + value_size).min(length);
+	let children_start = align4(value_end).min(length);
+	let block = Block {
+		key: String::from_utf16_lossy(&key),
+		value: &bin[value_start..value_end],
+		children: &bin[children_start..],
+	};
+	Ok((block, align4(length)))
+}
+
+fn read_children(mut bin: &[u8]) -> Result<Vec<Block<'_>>> {
+	let mut children = Vec::new();
+	while bin.len() >= 6 {
+		let (block, length) = read_block(bin)?;
+		children.push(block);
+		bin = &bin[length.min(bin.len())..];
+	}
+	Ok(children)
+}
+
+/// Null-terminated UTF-16 text
+fn read_text(bin: &[u8]) -> String {
+	let units = bin.chunks_exact(2)
+		.map(|c| u16::from_le_bytes([c[0], c[1]]))
+		.take_while(|c| *c != 0)
+		.collect::<Vec<_>>();
+	String::from_utf16_lossy(&units)
+}
+
+fn read_fixed_file_info(bin: &[u8]) -> Option<FixedFileInfo> {
+	if bin.len() < 52 { return None }
+	let u32_at = |i: usize| u32::from_le_bytes(bin[i * 4..i * 4 + 4].try_into().unwrap());
+	if u32_at(0) != FIXED_FILE_INFO_SIGNATURE { return None }
+	let version = |ms: u32, ls: u32| [(ms >> 16) as u16, ms as u16, (ls >> 16) as u16, ls as u16];
+	Some(FixedFileInfo {
+		file_version: version(u32_at(2), u32_at(3)),
+		product_version: version(u32_at(4), u32_at(5)),
+		file_flags: u32_at(7) & u32_at(6),
+		file_os: u32_at(8),
+		file_type: u32_at(9),
+		file_subtype: u32_at(10),
+	})
+}
@@ -0,0 +1,60 @@
+mod common;
+use common::*;
+use towermod_cstc::{self as cstc, BehaviorData, PlatformBehaviorData};
+use towermod_shared::app::selectors;
+use towermod_shared::app::state::{app_state::reducer, AppAction, DataAction, State};
+use towermod_shared::cstc_editing::CstcData;
+
+fn behavior(new_index: i32, mov_index: i32, data: Vec<u8>) -> cstc::Behavior {
+	cstc::Behavior { object_type_id: 1, new_index, mov_index, name: "Movement".into(), data, descriptors: None }
+}
+
+// plugin 0 is decodable, plugin 5 is not
+fn project(behavior: cstc::Behavior) -> State {
+	state(CstcData {
+		editor_plugins: [(0, plugin("Platform")), (5, plugin("8 Direction"))].into_iter().collect(),
+		object_types: [(1, object_type(1, 0))].into_iter().collect(),
+		behaviors: vec![behavior],
+		..Default::default()
+	})
+}
+
+/// Decodable as Platform data
+fn platform_bin() -> Vec<u8> {
+	PlatformBehaviorData { trailing: vec![0; 8], ..Default::default() }.to_bin()
+}
+
+fn update(state: State, data: BehaviorData) -> State {
+	let mov_index = state.data.behaviors[0].mov_index;
+	reducer(state, AppAction::Data(DataAction::UpdateBehaviorData { object_type_id: 1, mov_index, data }))
+}
+
+#[test]
+fn behavior_plugin_from_new_index() {
+	// mov_index is a position, not a plugin id, even when it happens to match a decodable plugin
+	let bin = platform_bin();
+	let s = project(behavior(5, 0, bin.clone()));
+	let b = &s.data.behaviors[0];
+	assert_eq!(s.data.behavior_plugin_name(b), Some("8 Direction"));
+	assert!(matches!(selectors::select_behavior_data(1, 0)(&s), Some(BehaviorData::Unknown(data)) if data == bin));
+
+	let s = project(behavior(0, 5, bin));
+	assert!(matches!(selectors::select_behavior_data(1, 5)(&s), Some(BehaviorData::Platform(_))));
+}
+
+#[test]
+fn update_behavior_data_rejects_other_plugins() {
+	let bin = platform_bin();
+	let platform = PlatformBehaviorData { jump_strength: 100.0, ..Default::default() };
+
+	// a Platform blob written into an 8 Direction behavior is ignored
+	let s = update(project(behavior(5, 0, vec![1, 2, 3])), BehaviorData::Platform(platform.clone()));
+	assert_eq!(s.data.behaviors[0].data, [1, 2, 3]);
+	let s = update(s, BehaviorData::Unknown(vec![4, 5]));
+	assert_eq!(s.data.behaviors[0].data, [4, 5]);
+
+	let s = update(project(behavior(0, 5, bin.clone())), BehaviorData::Unknown(vec![4, 5]));
+	assert_eq!(s.data.behaviors[0].data, bin);
+	let s = update(s, BehaviorData::Platform(platform.clone()));
+	assert_eq!(s.data.behaviors[0].data, platform.to_bin());
+}
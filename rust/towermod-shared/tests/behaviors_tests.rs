@@ -0,0 +1,128 @@
+mod common;
+use common::*;
+use towermod_cstc as cstc;
+use towermod_shared::cstc_editing::CstcData;
+
+fn behavior(object_type_id: i32, mov_index: i32, name: &str) -> cstc::Behavior {
+	cstc::Behavior { object_type_id, new_index: 0, mov_index, name: name.into(), data: name.as_bytes().to_vec(), descriptors: None }
+}
+
+fn condition(object_id: i32, movement_id: i32) -> cstc::EventCondition {
+	cstc::EventCondition { object_id, cond_id: 0, negated: false, movement_id, params: Vec::new() }
+}
+
+fn action(object_id: i32, movement_id: i32) -> cstc::EventAction {
+	cstc::EventAction { object_id, action_id: 0, movement_id, params: Vec::new() }
+}
+
+fn event(conditions: Vec<cstc::EventCondition>, actions: Vec<cstc::EventAction>, events: Vec<cstc::SomeEvent>) -> cstc::SomeEvent {
+	cstc::SomeEvent::Event(cstc::Event { line_number: 0, sheet_id: 0, conditions, actions, events })
+}
+
+/// Object type 1 has behaviors A, B, C; object type 2 has D.
+/// Object type 1's behaviors are referenced from a top-level event, a sub-event, a group and a second sheet.
+fn project(a_references: bool) -> CstcData {
+	let a = if a_references { 0 } else { -1 };
+	let sheet1 = vec![
+		event(vec![condition(1, 1), condition(2, 0)], vec![action(1, 2), action(-1, 1)], vec![
+			event(vec![condition(1, 2)], vec![action(1, a)], vec![]),
+		]),
+		cstc::SomeEvent::EventGroup(cstc::EventGroup { active: true, name: "Group".into(), events: vec![
+			event(vec![], vec![action(1, 1), action(1, -1)], vec![]),
+		]}),
+	];
+	let sheet2 = vec![event(vec![condition(1, 2)], vec![], vec![]), cstc::SomeEvent::EventInclude(0)];
+	CstcData {
+		editor_plugins: [(0, plugin("Platform"))].into_iter().collect(),
+		object_types: [(1, object_type(1, 0)), (2, object_type(2, 0))].into_iter().collect(),
+		behaviors: vec![behavior(1, 0, "A"), behavior(2, 0, "D"), behavior(1, 1, "B"), behavior(1, 2, "C")],
+		event_block: Some(cstc::EventBlock { sheet_names: vec!["Level 1".into(), "Common".into()], layout_sheets: vec![sheet1, sheet2] }),
+		..Default::default()
+	}
+}
+
+fn behavior_names(data: &CstcData, object_type_id: i32) -> Vec<(i32, &str)> {
+	data.object_type_behaviors(object_type_id).into_iter().map(|b| (b.mov_index, b.name.as_str())).collect()
+}
+
+/// `(object_id, movement_id)` of every condition and action, in sheet order
+fn movement_ids(data: &CstcData) -> Vec<(i32, i32)> {
+	fn visit(events: &[cstc::SomeEvent], ids: &mut Vec<(i32, i32)>) {
+		for event in events {
+			match event {
+				cstc::SomeEvent::Event(e) => {
+					ids.extend(e.conditions.iter().map(|c| (c.object_id, c.movement_id)));
+					ids.extend(e.actions.iter().map(|a| (a.object_id, a.movement_id)));
+					visit(&e.events, ids);
+				},
+				cstc::SomeEvent::EventGroup(g) => visit(&g.events, ids),
+				cstc::SomeEvent::EventInclude(_) => {},
+			}
+		}
+	}
+	let mut ids = Vec::new();
+	for sheet in &data.event_block.as_ref().unwrap().layout_sheets {
+		visit(sheet, &mut ids);
+	}
+	ids
+}
+
+#[test]
+fn add_behavior() {
+	let mut data = project(true);
+	data.editor_plugins.insert(1, plugin("Sine"));
+	// the data is copied from the first behavior of the same plugin
+	assert_eq!(data.add_behavior(2, 0, "Platform".into()), Some(1));
+	assert_eq!(behavior_names(&data, 2), [(0, "D"), (1, "Platform")]);
+	assert_eq!(data.behaviors.last().unwrap().data, b"A");
+	// nothing to copy for plugins without behaviors, or object types that don't exist
+	assert_eq!(data.add_behavior(2, 1, "Sine".into()), None);
+	assert_eq!(data.add_behavior(9, 0, "Platform".into()), None);
+	assert_eq!(data.behaviors.len(), 5);
+}
+
+#[test]
+fn remove_behavior() {
+	let mut data = project(true);
+	assert_eq!(data.behavior_event_references(1, 0), 1);
+	assert_eq!(data.behavior_event_references(1, 2), 3);
+	// referenced behaviors can't be removed
+	assert!(!data.remove_behavior(1, 0));
+	assert!(!data.remove_behavior(1, 3));
+	assert_eq!(behavior_names(&data, 1).len(), 3);
+
+	let mut data = project(false);
+	assert!(data.remove_behavior(1, 0));
+	assert_eq!(behavior_names(&data, 1), [(0, "B"), (1, "C")]);
+	assert_eq!(behavior_names(&data, 2), [(0, "D")]);
+	assert_eq!(movement_ids(&data), [
+		(1, 0), (2, 0), (1, 1), (-1, 1),
+		(1, 1), (1, -1),
+		(1, 0), (1, -1),
+		(1, 1),
+	]);
+	assert_eq!(data.new_behavior_index(1), 2);
+}
+
+#[test]
+fn move_behavior() {
+	let mut data = project(true);
+	// C to the front
+	assert!(data.move_behavior(1, 2, 0));
+	assert_eq!(behavior_names(&data, 1), [(0, "C"), (1, "A"), (2, "B")]);
+	assert_eq!(behavior_names(&data, 2), [(0, "D")]);
+	assert_eq!(movement_ids(&data), [
+		(1, 2), (2, 0), (1, 0), (-1, 1),
+		(1, 0), (1, 1),
+		(1, 2), (1, -1),
+		(1, 0),
+	]);
+	// behaviors of other object types keep their position in the list
+	assert_eq!(data.behaviors.iter().map(|b| b.name.as_str()).collect::<Vec<_>>(), ["C", "D", "A", "B"]);
+
+	// out of range positions move to the end, and moving back restores the original references
+	assert!(data.move_behavior(1, 0, 99));
+	assert_eq!(behavior_names(&data, 1), [(0, "A"), (1, "B"), (2, "C")]);
+	assert_eq!(movement_ids(&data), movement_ids(&project(true)));
+	assert!(!data.move_behavior(1, 3, 0));
+}
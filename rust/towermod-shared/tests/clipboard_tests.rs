@@ -0,0 +1,65 @@
+mod common;
+use common::*;
+use towermod_cstc::ObjectData;
+use towermod_shared::cstc_editing::{CstcData, InstanceClipboard, VariableType, VariableValue};
+
+fn project(sprite_animation: i32) -> CstcData {
+	let mut player = object_type(1, 1);
+	player.name = "Player".into();
+	player.private_variables.insert("hp".into(), VariableType::Number);
+	let mut label = object_type(2, 2);
+	label.name = "Label".into();
+	let mut player_instance = sprite(10, 1, sprite_animation);
+	player_instance.private_variables.insert("hp".into(), VariableValue::Number(3.0));
+	CstcData {
+		editor_plugins: [(1, plugin("Sprite")), (2, plugin("Text"))].into(),
+		object_types: [(1, player), (2, label)].into_iter().collect(),
+		animations: [(sprite_animation, animation(sprite_animation, "", &[], vec![]))].into_iter().collect(),
+		layouts: vec![layout("Level 1", vec![layer(1, vec![player_instance, instance(11, 2, 100, 50)])])],
+		..Default::default()
+	}
+}
+
+#[test]
+fn paste_in_same_project() {
+	let data = project(5);
+	let clipboard = InstanceClipboard::copy(&data, &[10, 11]).unwrap();
+	let (instances, missing) = clipboard.paste(&data, 12, 16, -8);
+	assert!(missing.is_empty());
+	assert_eq!(instances.iter().map(|o| (o.id, o.object_type_id, o.x, o.y)).collect::<Vec<_>>(), [(12, 1, 16, -8), (13, 2, 116, 42)]);
+	let ObjectData::Sprite(sprite) = &instances[0].data else { panic!() };
+	assert_eq!(sprite.animation, 5);
+	assert!(matches!(instances[0].private_variables["hp"], VariableValue::Number(hp) if hp == 3.0));
+}
+
+#[test]
+fn paste_across_projects() {
+	let source = project(5);
+	let clipboard = InstanceClipboard::copy(&source, &[10, 11]).unwrap();
+
+	// object types are matched by name, the animation is taken from the target project
+	let mut target = project(40);
+	target.object_types.get_mut(&1).unwrap().private_variables.insert("speed".into(), VariableType::String);
+	target.object_types.shift_remove(&2);
+	let (instances, missing) = clipboard.clone().paste(&target, 100, 0, 0);
+	assert_eq!(missing, ["Label"]);
+	assert_eq!(instances.len(), 1);
+	let ObjectData::Sprite(sprite) = &instances[0].data else { panic!() };
+	assert_eq!(sprite.animation, 40);
+	assert!(matches!(&instances[0].private_variables["speed"], VariableValue::String(s) if s.is_empty()));
+
+	// the target project has the object type, but no instance to find its animations through
+	let mut target = project(40);
+	target.layouts[0].layers[0].objects.retain(|o| o.id != 10);
+	let (instances, missing) = clipboard.paste(&target, 100, 0, 0);
+	assert_eq!(missing, ["Player"]);
+	assert_eq!(instances.iter().map(|o| (o.id, o.object_type_id)).collect::<Vec<_>>(), [(100, 2)]);
+}
+
+#[test]
+fn copy_unknown_plugin() {
+	let mut data = project(5);
+	data.editor_plugins.remove(&2);
+	assert!(InstanceClipboard::copy(&data, &[10, 11]).is_err());
+	assert_eq!(InstanceClipboard::copy(&data, &[10]).unwrap().instances.len(), 1);
+}
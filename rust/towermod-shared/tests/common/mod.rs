@@ -0,0 +1,70 @@
+//! Builders for small synthetic projects shared by the editing tests
+#![allow(dead_code)]
+use towermod_cstc as cstc;
+use towermod_shared::app::state::State;
+use towermod_shared::cstc_editing::{CstcData, EdLayout, EdLayoutLayer, EdObjectInstance, EdObjectType};
+
+pub fn plugin(name: &str) -> cstc::plugin::PluginData {
+	serde_json::from_value(serde_json::json!({
+		"conditions": {}, "actions": {}, "expressions": {},
+		"cndCategories": {}, "actCategories": {}, "expCategories": {},
+		"properties": [],
+		"stringTable": { "name": name, "author": "", "version": "", "desc": "", "category": "", "web": "" },
+	})).unwrap()
+}
+
+pub fn layout(name: &str, layers: Vec<EdLayoutLayer>) -> EdLayout {
+	EdLayout {
+		name: name.to_string(),
+		width: 640,
+		height: 480,
+		color: 0xffffff,
+		unbounded_scrolling: false,
+		application_background: false,
+		data_keys: Default::default(),
+		layers,
+		image_ids: Vec::new(),
+		texture_loading_mode: cstc::TextureLoadingMode::LoadOnAppStart,
+	}
+}
+
+pub fn layer(id: i32, objects: Vec<EdObjectInstance>) -> EdLayoutLayer {
+	let mut layer = EdLayoutLayer::new(id, format!("Layer {id}"));
+	layer.objects = objects;
+	layer
+}
+
+pub fn object_type(id: i32, plugin_id: i32) -> EdObjectType {
+	EdObjectType { id, plugin_id, name: format!("obj{id}"), ..Default::default() }
+}
+
+pub fn instance(id: i32, object_type_id: i32, x: i32, y: i32) -> EdObjectInstance {
+	EdObjectInstance { id, object_type_id, x, y, width: 32, height: 32, ..Default::default() }
+}
+
+pub fn sprite(id: i32, object_type_id: i32, animation: i32) -> EdObjectInstance {
+	let data = cstc::SpriteObjectData { animation, ..Default::default() };
+	EdObjectInstance { data: cstc::ObjectData::Sprite(data), ..instance(id, object_type_id, 0, 0) }
+}
+
+pub fn animation(id: i32, name: &str, image_ids: &[i32], sub_animations: Vec<cstc::Animation>) -> cstc::Animation {
+	let frames = image_ids.iter().map(|&image_id| cstc::AnimationFrame { duration: 1.0, image_id }).collect();
+	cstc::Animation { id, name: name.to_string(), frames, sub_animations, ..Default::default() }
+}
+
+pub fn image(id: i32, width: u32, height: u32) -> cstc::ImageMetadata {
+	cstc::ImageMetadata {
+		id,
+		hotspot_x: 0,
+		hotspot_y: 0,
+		apoints: Vec::new(),
+		collision_width: width,
+		collision_height: height,
+		collision_pitch: 0,
+		collision_mask: Vec::new(),
+	}
+}
+
+pub fn state(data: CstcData) -> State {
+	State { data, ..Default::default() }
+}
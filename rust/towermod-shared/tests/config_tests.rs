@@ -0,0 +1,94 @@
+use std::path::Path;
+use towermod_shared::app::state::{GameProfile, TowermodConfig};
+use towermod_shared::{GameLauncher, GameType};
+
+#[test]
+fn migrate_unversioned_config() {
+	let v0 = r#"
+		gamePath = "/games/TowerClimb/TowerClimb_V1_Steam4.exe"
+
+		[launcher]
+		workingDir = "/games"
+
+		[launcher.launcher]
+		type = "wine"
+		winePath = "wine64"
+	"#;
+	let (config, migrated) = TowermodConfig::from_toml(v0).unwrap();
+	assert!(migrated);
+	assert_eq!(config.version, TowermodConfig::VERSION);
+	let profile = config.current_profile().unwrap();
+	assert_eq!(profile.name, "TowerClimb_V1_Steam4");
+	assert_eq!(profile.launcher.launcher, GameLauncher::Wine { wine_path: "wine64".into(), prefix: None });
+	assert_eq!(profile.launcher.working_dir.as_deref(), Some(Path::new("/games")));
+	assert_eq!(profile.appdata_dir, None);
+	assert_eq!(config.profile(Path::new("/games/Other.exe")), None);
+
+	// oldest configs only had a game path
+	let (config, migrated) = TowermodConfig::from_toml("gamePath = 'C:/TowerClimb.exe'").unwrap();
+	assert!(migrated);
+	assert!(config.profiles.is_empty());
+	assert_eq!(config.path_overrides().towerclimb_appdata_dir, None);
+}
+
+#[test]
+fn migrate_per_game_settings() {
+	let v1 = r#"
+		version = 1
+		gamePath = "/games/Modded/TowerClimb.exe"
+
+		[games."/games/TowerClimb/TowerClimb_V1_Steam4.exe"]
+		appdataDir = "/home/user/TowerClimbSaves"
+
+		[games."/games/Modded/TowerClimb.exe".launcher.launcher]
+		type = "native"
+	"#;
+	let (config, migrated) = TowermodConfig::from_toml(v1).unwrap();
+	assert!(migrated);
+	assert_eq!(config.profiles.len(), 2);
+	let steam = config.profile(Path::new("/games/TowerClimb/TowerClimb_V1_Steam4.exe")).unwrap();
+	assert_eq!(steam.appdata_dir.as_deref(), Some(Path::new("/home/user/TowerClimbSaves")));
+	let modded = config.current_profile().unwrap();
+	assert_eq!(modded.name, "TowerClimb");
+	assert_eq!(modded.launcher.launcher, GameLauncher::Native);
+	assert!(modded.recent_projects.is_empty());
+	assert_eq!(config.path_overrides().towerclimb_appdata_dir, None);
+}
+
+#[test]
+fn current_config_round_trip() {
+	let mut config = TowermodConfig::default();
+	let game_path = Path::new("/games/TowerClimb/TowerClimb_V1_Steam4.exe");
+	config.game_path = Some(game_path.to_path_buf());
+	config.cache_dir = Some("/mnt/cache/towermod".into());
+	let profile = config.profile_mut(game_path);
+	profile.game_type = GameType::Towerclimb;
+	profile.data_hash = Some("abc123".into());
+	profile.appdata_dir = Some("/home/user/TowerClimbSaves".into());
+	profile.add_recent_project("/projects/a/manifest.toml".into());
+	config.profile_mut(Path::new("/games/Other.exe"));
+
+	let (parsed, migrated) = TowermodConfig::from_toml(&toml::to_string_pretty(&config).unwrap()).unwrap();
+	assert!(!migrated);
+	assert_eq!(parsed.profiles, config.profiles);
+	assert_eq!(parsed.profile(Path::new("/games/Other.exe")).unwrap().name, "Other");
+	let overrides = parsed.path_overrides();
+	assert_eq!(overrides.cache_dir.as_deref(), Some(Path::new("/mnt/cache/towermod")));
+	assert_eq!(overrides.towerclimb_appdata_dir.as_deref(), Some(Path::new("/home/user/TowerClimbSaves")));
+
+	assert!(TowermodConfig::from_toml(&format!("version = {}", TowermodConfig::VERSION + 1)).is_err());
+	assert!(TowermodConfig::from_toml("version = 'one'").is_err());
+}
+
+#[test]
+fn recent_projects_are_most_recent_first() {
+	let mut profile = GameProfile::new("/games/TowerClimb.exe".into());
+	for i in 0..GameProfile::MAX_RECENT_PROJECTS + 2 {
+		profile.add_recent_project(format!("/projects/{i}/manifest.toml").into());
+	}
+	profile.add_recent_project("/projects/5/manifest.toml".into());
+	assert_eq!(profile.recent_projects.len(), GameProfile::MAX_RECENT_PROJECTS);
+	assert_eq!(profile.recent_projects[0], Path::new("/projects/5/manifest.toml"));
+	assert_eq!(profile.recent_projects[1], Path::new("/projects/11/manifest.toml"));
+	assert_eq!(profile.recent_projects.iter().filter(|p| p.ends_with("5/manifest.toml")).count(), 1);
+}
@@ -0,0 +1,85 @@
+use std::path::Path;
+use towermod_shared::{desktop_entry, get_stable_exe_path, install_desktop_integration, link_to_stable_path, set_default_applications, uninstall_desktop_integration, DesktopIntegrationPaths};
+
+#[test]
+fn default_applications() {
+	let mimeapps_list = "[Added Associations]\ntext/plain=gedit.desktop\n\n[Default Applications]\ntext/html=firefox.desktop\nx-scheme-handler/towermod=old.desktop\n\n[Removed Associations]\n";
+	let updated = set_default_applications(mimeapps_list, &[
+		("x-scheme-handler/towermod", Some("towermod.desktop")),
+		("application/x-towermod", Some("towermod.desktop")),
+	]);
+	assert_eq!(updated, "[Added Associations]\ntext/plain=gedit.desktop\n\n[Default Applications]\ntext/html=firefox.desktop\nx-scheme-handler/towermod=towermod.desktop\napplication/x-towermod=towermod.desktop\n\n[Removed Associations]\n");
+	let removed = set_default_applications(&updated, &[("x-scheme-handler/towermod", None), ("application/x-towermod", None)]);
+	assert_eq!(removed, "[Added Associations]\ntext/plain=gedit.desktop\n\n[Default Applications]\ntext/html=firefox.desktop\n\n[Removed Associations]\n");
+
+	assert_eq!(set_default_applications("", &[("application/x-towermod", Some("towermod.desktop"))]), "[Default Applications]\napplication/x-towermod=towermod.desktop\n");
+	assert_eq!(set_default_applications("[Added Associations]\n", &[("application/x-towermod", None)]), "[Added Associations]\n");
+}
+
+#[test]
+fn desktop_entry_exec() {
+	let entry = desktop_entry(Path::new("/home/user/.local/share/towermod/towermod"));
+	assert!(entry.lines().any(|l| l == "Exec=/home/user/.local/share/towermod/towermod %u"));
+	let entry = desktop_entry(Path::new("/home/user/My Games/100%/tower$mod"));
+	assert!(entry.lines().any(|l| l == r#"Exec="/home/user/My Games/100%%/tower\\$mod" %u"#));
+}
+
+#[test]
+fn install_and_uninstall() {
+	let dir = tempfile::tempdir().unwrap();
+	let paths = DesktopIntegrationPaths { data_dir: dir.path().join("data"), config_dir: dir.path().join("config") };
+	std::fs::create_dir_all(&paths.config_dir).unwrap();
+	std::fs::write(paths.mimeapps_list(), "[Default Applications]\ntext/html=firefox.desktop\n").unwrap();
+
+	let exe_path = Path::new("/opt/towermod/towermod");
+	install_desktop_integration(exe_path, &paths).unwrap();
+	let read = |path: &Path| std::fs::read_to_string(path).unwrap();
+	let installed = [read(&paths.desktop_entry()), read(&paths.mime_package()), read(&paths.mimeapps_list())];
+	assert!(installed[1].contains(r#"<glob pattern="*.towermod"/>"#));
+	assert_eq!(installed[2], "[Default Applications]\ntext/html=firefox.desktop\nx-scheme-handler/towermod=towermod.desktop\napplication/x-towermod=towermod.desktop\n");
+
+	// installing again changes nothing
+	install_desktop_integration(exe_path, &paths).unwrap();
+	assert_eq!([read(&paths.desktop_entry()), read(&paths.mime_package()), read(&paths.mimeapps_list())], installed);
+
+	uninstall_desktop_integration(&paths).unwrap();
+	assert!(!paths.desktop_entry().exists());
+	assert!(!paths.mime_package().exists());
+	assert_eq!(read(&paths.mimeapps_list()), "[Default Applications]\ntext/html=firefox.desktop\n");
+	uninstall_desktop_integration(&paths).unwrap();
+}
+
+#[tokio::test]
+async fn link_and_install() {
+	let dir = tempfile::tempdir().unwrap();
+	let exe_path = dir.path().join("build/towermod");
+	std::fs::create_dir_all(exe_path.parent().unwrap()).unwrap();
+	std::fs::write(&exe_path, "v1").unwrap();
+
+	// the stable directory doesn't exist yet
+	let stable_path = dir.path().join("data/towermod/towermod");
+	link_to_stable_path(&exe_path, &stable_path).await.unwrap();
+	assert_eq!(std::fs::read_to_string(&stable_path).unwrap(), "v1");
+
+	// linking again replaces the previous version
+	std::fs::remove_file(&exe_path).unwrap();
+	std::fs::write(&exe_path, "v2").unwrap();
+	link_to_stable_path(&exe_path, &stable_path).await.unwrap();
+	assert_eq!(std::fs::read_to_string(&stable_path).unwrap(), "v2");
+
+	// the entry runs the linked executable
+	let paths = DesktopIntegrationPaths { data_dir: dir.path().join("data"), config_dir: dir.path().join("config") };
+	install_desktop_integration(&stable_path, &paths).unwrap();
+	let entry = std::fs::read_to_string(paths.desktop_entry()).unwrap();
+	let exec = entry.lines().find_map(|l| l.strip_prefix("Exec=")).unwrap();
+	assert_eq!(exec.strip_suffix(" %u"), stable_path.to_str());
+	assert!(stable_path.is_file());
+
+	assert!(link_to_stable_path(&dir.path().join("missing"), &stable_path).await.is_err());
+}
+
+#[test]
+fn stable_exe_path() {
+	let file_name = get_stable_exe_path().file_name().unwrap().to_owned();
+	assert_eq!(file_name, if cfg!(windows) { "towermod.exe" } else { "towermod" });
+}
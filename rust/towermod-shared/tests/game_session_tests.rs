@@ -0,0 +1,60 @@
+#![cfg(unix)]
+use std::os::unix::fs::PermissionsExt;
+use std::path::Path;
+use towermod_shared::{spawn_game, wait_until_process_exits, GameLauncher, LauncherConfig};
+
+fn write_script(dir: &Path, script: &str) -> std::path::PathBuf {
+	let path = dir.join("game.sh");
+	std::fs::write(&path, format!("#!/bin/sh\n{script}\n")).unwrap();
+	std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+	path
+}
+
+#[tokio::test]
+async fn game_session_output_and_exit() {
+	let dir = tempfile::tempdir().unwrap();
+	let game_path = write_script(dir.path(), "echo out; echo err >&2; pwd; sleep 0.2; exit 3");
+	let launcher = LauncherConfig { launcher: GameLauncher::Native, ..Default::default() };
+	let log_dir = dir.path().join("logs");
+
+	let mut session = spawn_game(&game_path, &launcher, &log_dir).await.unwrap();
+	assert!(session.log_path.starts_with(&log_dir));
+	let pid = session.pid;
+	let (waited, exit) = tokio::join!(wait_until_process_exits(pid), session.wait());
+	let exit = exit.unwrap();
+	assert_eq!(exit.pid, session.pid);
+	assert_eq!(exit.exit_code, Some(3));
+	assert!(!exit.success);
+	assert!(exit.duration_secs >= 0.2);
+	assert_eq!(waited.unwrap(), Some(exit.clone()));
+
+	let log = std::fs::read_to_string(&exit.log_path).unwrap();
+	let cwd = dir.path().canonicalize().unwrap();
+	assert_eq!(log.lines().collect::<Vec<_>>(), ["out", "err", &*cwd.to_string_lossy()]);
+
+	// no longer tracked once exited
+	assert_eq!(wait_until_process_exits(exit.pid).await.unwrap(), None);
+}
+
+#[tokio::test]
+async fn game_sessions_get_separate_logs() {
+	let dir = tempfile::tempdir().unwrap();
+	let game_path = write_script(dir.path(), "echo $$");
+	let launcher = LauncherConfig { launcher: GameLauncher::Native, ..Default::default() };
+	let log_dir = dir.path().join("logs");
+
+	let mut sessions = Vec::new();
+	for _ in 0..3 {
+		sessions.push(spawn_game(&game_path, &launcher, &log_dir).await.unwrap());
+	}
+	let mut log_paths = Vec::new();
+	for session in &mut sessions {
+		let exit = session.wait().await.unwrap();
+		let log = std::fs::read_to_string(&exit.log_path).unwrap();
+		assert_eq!(log.trim(), session.pid.to_string());
+		log_paths.push(exit.log_path);
+	}
+	log_paths.sort();
+	log_paths.dedup();
+	assert_eq!(log_paths.len(), 3);
+}
@@ -0,0 +1,37 @@
+#![cfg(unix)]
+use std::sync::mpsc;
+use std::time::Duration;
+use towermod_shared::ipc::unix::{listen_socket, socket_exists, write_to_socket};
+
+#[test]
+fn forward_messages_over_socket() {
+	let dir = tempfile::tempdir().unwrap();
+	let path = dir.path().join("towermod.sock");
+	assert!(!socket_exists(&path));
+	assert!(write_to_socket(&path, b"towermod://install_mod/1").is_err());
+
+	// stale socket files are replaced
+	drop(std::os::unix::net::UnixListener::bind(&path).unwrap());
+	assert!(path.exists() && !socket_exists(&path));
+
+	let (sender, receiver) = mpsc::channel();
+	std::thread::spawn({
+		let path = path.clone();
+		move || listen_socket(&path, move |msg| sender.send(msg.to_string()).unwrap())
+	});
+	let timeout = Duration::from_secs(5);
+	let start = std::time::Instant::now();
+	while !socket_exists(&path) {
+		assert!(start.elapsed() < timeout, "socket was not created");
+		std::thread::sleep(Duration::from_millis(10));
+	}
+
+	write_to_socket(&path, b"towermod://install_mod/1").unwrap();
+	write_to_socket(&path, "/home/user/Mods/Mod ü.zip".as_bytes()).unwrap();
+	let mut messages = vec![receiver.recv_timeout(timeout).unwrap(), receiver.recv_timeout(timeout).unwrap()];
+	messages.sort();
+	assert_eq!(messages, ["/home/user/Mods/Mod ü.zip", "towermod://install_mod/1"]);
+
+	// a second listener does not take over the socket
+	assert!(listen_socket(&path, |_| {}).is_err());
+}
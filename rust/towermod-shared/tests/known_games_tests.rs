@@ -0,0 +1,89 @@
+use towermod_shared::{Game, GameType, KnownGames};
+
+fn game(file_hash: &str, data_hash: &str) -> Game {
+	Game {
+		file_hash: file_hash.into(),
+		file_name: "TowerClimb.exe".into(),
+		file_size: 1234,
+		data_hash: Some(data_hash.into()),
+		..Default::default()
+	}
+}
+
+#[test]
+fn find_known_games() {
+	let mut known_games = KnownGames::bundled();
+	known_games.extend(KnownGames::from_toml(r#"
+		[[game]]
+		name = "TowerClimb"
+		version = "v1.0 (Steam)"
+		gameType = "Towerclimb"
+		fileHash = "aaaa"
+		dataHash = "1111"
+
+		[[game]]
+		name = "TowerClimb"
+		version = "v1.0 (Steam, patched)"
+		gameType = "Towerclimb"
+		fileHash = "bbbb"
+		dataHash = "1111"
+
+		[[game]]
+		name = "Other Game"
+		version = "demo"
+		dataHash = "2222"
+	"#).unwrap());
+
+	let steam = known_games.find_game(&game("aaaa", "1111")).unwrap();
+	assert_eq!(steam.game_type, GameType::Towerclimb);
+	assert_eq!(steam.display_name(), "TowerClimb v1.0 (Steam)");
+	// the executable's hash is preferred over the data hash
+	assert_eq!(known_games.describe(&game("bbbb", "1111")), "TowerClimb v1.0 (Steam, patched)");
+	// unknown executables with known data
+	assert_eq!(known_games.describe(&game("cccc", "1111")), "TowerClimb v1.0 (Steam)");
+	assert_eq!(known_games.find("dddd", Some("2222")).unwrap().game_type, GameType::Other);
+	assert_eq!(known_games.find("dddd", None), None);
+	assert_eq!(known_games.describe(&game("eeee", "3333")), "an unrecognized version of TowerClimb.exe (1234 bytes)");
+
+	// later additions take precedence
+	known_games.extend(KnownGames::from_toml(r#"
+		[[game]]
+		name = "TowerClimb"
+		version = "v1.0 (GOG)"
+		fileHash = "aaaa"
+	"#).unwrap());
+	assert_eq!(known_games.describe(&game("aaaa", "1111")), "TowerClimb v1.0 (GOG)");
+
+	assert!(KnownGames::from_toml("[[game]]\nversion = 'v1'").is_err());
+}
+
+#[test]
+fn bundled_known_games() {
+	// the bundled file has no entries until hashes of real releases are added, so this only checks the ones it has
+	let known_games = KnownGames::bundled();
+	let is_md5 = |hash: &Option<String>| hash.as_ref().map_or(true, |h| h.len() == 32 && h.chars().all(|c| matches!(c, '0'..='9' | 'a'..='f')));
+	for known in &known_games.games {
+		let name = known.display_name();
+		assert!(known.file_hash.is_some() || known.data_hash.is_some(), "{name} has no hashes");
+		assert!(is_md5(&known.file_hash) && is_md5(&known.data_hash), "{name} has a malformed hash");
+		// each entry is reachable, and not shadowed by an earlier one with the same hashes
+		let file_hash = known.file_hash.as_deref().unwrap_or_default();
+		assert_eq!(known_games.find(file_hash, known.data_hash.as_deref()), Some(known), "{name}");
+	}
+}
+
+#[tokio::test]
+async fn bundled_known_games_recognize_game() {
+	// needs a real release: `TOWERMOD_GAME_PATH=path/to/TowerClimb_V1_Steam4.exe cargo test -p towermod-shared --test known_games_tests`
+	let Some(path) = std::env::var_os("TOWERMOD_GAME_PATH") else {
+		eprintln!("TOWERMOD_GAME_PATH not set, skipping");
+		return
+	};
+	let game = Game::from_path(path.into()).await.unwrap();
+	let known = KnownGames::bundled().find_game(&game).cloned();
+	let Some(known) = known else {
+		panic!("{} is not in known_games.toml: fileHash = {:?}, dataHash = {:?}", game.file_name, game.file_hash, game.data_hash.unwrap_or_default())
+	};
+	assert_eq!(known.file_hash.as_ref(), Some(&game.file_hash), "{}", known.display_name());
+	assert_eq!(known.data_hash, game.data_hash, "{}", known.display_name());
+}
@@ -0,0 +1,123 @@
+use std::ffi::OsStr;
+use std::path::{Path, PathBuf};
+use towermod_shared::{proton_from_config_info, proton_version, GameLauncher, LauncherConfig};
+
+fn env_var<'a>(command: &'a std::process::Command, key: &str) -> Option<&'a OsStr> {
+	command.get_envs().find(|(k, _)| *k == key).and_then(|(_, v)| v)
+}
+
+#[test]
+fn launcher_commands() {
+	let game_path = Path::new("/games/TowerClimb/TowerClimb_V1_Steam4.exe");
+
+	let native = LauncherConfig { launcher: GameLauncher::Native, ..Default::default() };
+	let command = native.command(game_path).unwrap();
+	assert_eq!(command.get_program(), game_path);
+	assert_eq!(command.get_args().count(), 0);
+	assert_eq!(command.get_current_dir(), Some(Path::new("/games/TowerClimb")));
+
+	let mut wine = LauncherConfig {
+		launcher: GameLauncher::Wine { wine_path: "wine64".into(), prefix: Some("/prefixes/towerclimb".into()) },
+		working_dir: Some("/tmp".into()),
+		..Default::default()
+	};
+	wine.env.insert("WINEDEBUG".into(), "-all".into());
+	let command = wine.command(game_path).unwrap();
+	assert_eq!(command.get_program(), "wine64");
+	assert_eq!(command.get_args().collect::<Vec<_>>(), [game_path]);
+	assert_eq!(env_var(&command, "WINEPREFIX"), Some(OsStr::new("/prefixes/towerclimb")));
+	assert_eq!(env_var(&command, "WINEDEBUG"), Some(OsStr::new("-all")));
+	assert_eq!(command.get_current_dir(), Some(Path::new("/tmp")));
+
+	let proton = LauncherConfig {
+		launcher: GameLauncher::Proton {
+			proton_path: "/steam/steamapps/common/Proton 9.0/proton".into(),
+			compat_data_path: "/steam/steamapps/compatdata/396640".into(),
+			steam_path: Some("/steam".into()),
+			app_id: None,
+		},
+		..Default::default()
+	};
+	let command = proton.command(game_path).unwrap();
+	assert_eq!(command.get_args().collect::<Vec<_>>(), [OsStr::new("run"), game_path.as_os_str()]);
+	assert_eq!(env_var(&command, "STEAM_COMPAT_DATA_PATH"), Some(OsStr::new("/steam/steamapps/compatdata/396640")));
+	assert_eq!(env_var(&command, "STEAM_COMPAT_CLIENT_INSTALL_PATH"), Some(OsStr::new("/steam")));
+	assert_eq!(proton.launcher.wine_prefix(), Some(PathBuf::from("/steam/steamapps/compatdata/396640/pfx")));
+	// the app id defaults to the prefix's directory name
+	assert_eq!(env_var(&command, "SteamAppId"), Some(OsStr::new("396640")));
+	assert_eq!(env_var(&command, "SteamGameId"), Some(OsStr::new("396640")));
+
+	let other_game = LauncherConfig {
+		launcher: GameLauncher::Proton {
+			proton_path: "/steam/steamapps/common/Proton 9.0/proton".into(),
+			compat_data_path: "/prefixes/other".into(),
+			steam_path: None,
+			app_id: Some(123450),
+		},
+		..Default::default()
+	};
+	let command = other_game.command(game_path).unwrap();
+	assert_eq!(env_var(&command, "SteamAppId"), Some(OsStr::new("123450")));
+	assert_eq!(env_var(&command, "SteamGameId"), Some(OsStr::new("123450")));
+
+	// configs saved before the app id was stored
+	let launcher: GameLauncher = serde_json::from_str(r#"{"type": "proton", "protonPath": "/proton", "compatDataPath": "/prefixes/other"}"#).unwrap();
+	let command = LauncherConfig { launcher, ..Default::default() }.command(game_path).unwrap();
+	assert_eq!(env_var(&command, "SteamAppId"), None);
+}
+
+#[test]
+fn detect_proton_prefix() {
+	let steam = tempfile::tempdir().unwrap();
+	let steam_path = steam.path().to_path_buf();
+	let empty = tempfile::tempdir().unwrap();
+	assert!(GameLauncher::detect_proton(&[empty.path().to_path_buf()], 396640).is_err());
+
+	let compat_data_path = steam_path.join("steamapps/compatdata/396640");
+	std::fs::create_dir_all(compat_data_path.join("pfx")).unwrap();
+	// a prefix without any Proton installation
+	let other_steam = tempfile::tempdir().unwrap();
+	std::fs::create_dir_all(other_steam.path().join("steamapps/compatdata/396640/pfx")).unwrap();
+	let error = GameLauncher::detect_proton(&[other_steam.path().to_path_buf()], 396640).unwrap_err();
+	assert!(error.to_string().contains("no Proton installation"));
+
+	for proton in ["Proton 8.0", "Proton 9.0", "Proton 10.0", "Proton - Experimental"] {
+		let dir = steam_path.join("steamapps/common").join(proton);
+		std::fs::create_dir_all(&dir).unwrap();
+		std::fs::write(dir.join("proton"), "").unwrap();
+	}
+
+	// without config_info, the newest Proton is used, and Steam installations without Proton are skipped
+	let launcher = GameLauncher::detect_proton(&[empty.path().to_path_buf(), other_steam.path().to_path_buf(), steam_path.clone()], 396640).unwrap();
+	assert_eq!(launcher, GameLauncher::Proton {
+		proton_path: steam_path.join("steamapps/common/Proton 10.0/proton"),
+		compat_data_path: compat_data_path.clone(),
+		steam_path: Some(steam_path.clone()),
+		app_id: Some(396640),
+	});
+
+	// otherwise, the Proton that created the prefix
+	let proton_8 = steam_path.join("steamapps/common/Proton 8.0");
+	let config_info = format!("8.0-5\n{}/files/share/fonts/\n", proton_8.display());
+	std::fs::write(compat_data_path.join("config_info"), config_info).unwrap();
+	let launcher = GameLauncher::detect_proton(&[steam_path], 396640).unwrap();
+	assert!(matches!(launcher, GameLauncher::Proton { proton_path, .. } if proton_path == proton_8.join("proton")));
+}
+
+#[test]
+fn config_info_proton_path() {
+	assert_eq!(
+		proton_from_config_info("9.0-200\n/home/user/.steam/steam/steamapps/common/Proton 9.0/files/share/fonts/\n"),
+		Some(PathBuf::from("/home/user/.steam/steam/steamapps/common/Proton 9.0/proton")),
+	);
+	assert_eq!(proton_from_config_info("3.7-8\n/steam/steamapps/common/Proton 3.7/dist/share/fonts/"), Some(PathBuf::from("/steam/steamapps/common/Proton 3.7/proton")));
+	assert_eq!(proton_from_config_info(""), None);
+}
+
+#[test]
+fn proton_versions() {
+	assert_eq!(proton_version("Proton 9.0"), [9, 0]);
+	assert_eq!(proton_version("Proton 7.0-6"), [7, 0, 6]);
+	assert!(proton_version("Proton 10.0") > proton_version("Proton 9.0"));
+	assert!(proton_version("Proton 9.0") > proton_version("Proton - Experimental"));
+}
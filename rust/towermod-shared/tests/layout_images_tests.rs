@@ -0,0 +1,69 @@
+mod common;
+use common::*;
+use towermod_cstc::{CanvasObjectData, ObjectData, PanelObjectData, TiledBackgroundObjectData};
+use towermod_shared::app::state::{app_state::reducer, AppAction, DataAction};
+use towermod_shared::cstc_editing::{CstcData, EdObjectInstance, LayoutImageIdsChange};
+
+fn with_data(id: i32, data: ObjectData) -> EdObjectInstance {
+	EdObjectInstance { data, ..instance(id, id, 0, 0) }
+}
+
+fn data() -> CstcData {
+	let mut level1 = layout("Level 1", vec![layer(1, vec![
+		sprite(1, 1, 10),
+		with_data(2, ObjectData::TiledBackground(TiledBackgroundObjectData { texture_id: 4, ..Default::default() })),
+		with_data(3, ObjectData::Panel(PanelObjectData { image_id: 5, ..Default::default() })),
+		with_data(4, ObjectData::Canvas(CanvasObjectData { initial_image_id: 6, ..Default::default() })),
+		// no such image
+		with_data(5, ObjectData::Canvas(CanvasObjectData { initial_image_id: -1, ..Default::default() })),
+	])]);
+	// 3 is an unused animation frame, 7 is not referenced by anything decoded
+	level1.image_ids = vec![1, 3, 7];
+	let mut level2 = layout("Level 2", vec![layer(2, vec![
+		with_data(6, ObjectData::Panel(PanelObjectData { image_id: 5, ..Default::default() })),
+	])]);
+	// 4 is only used as a texture on another layout
+	level2.image_ids = vec![4, 5, 7];
+	CstcData {
+		animations: [
+			(10, animation(10, "", &[], vec![animation(11, "Default", &[1, 2], vec![])])),
+			(20, animation(20, "", &[], vec![animation(21, "Default", &[3], vec![])])),
+		].into_iter().collect(),
+		image_block: (1..=7).map(|id| image(id, 8, 8)).collect(),
+		layouts: vec![level1, level2],
+		..Default::default()
+	}
+}
+
+#[test]
+fn layout_image_ids_include_textures() {
+	let changes = data().compute_layout_image_ids();
+	assert_eq!(changes.len(), 1);
+	assert_eq!(changes[0].layout_name, "Level 1");
+	assert_eq!(changes[0].added, [2, 4, 5, 6]);
+	assert!(changes[0].removed.is_empty());
+}
+
+#[test]
+fn unused_layout_image_ids() {
+	// images of objects that aren't placed on the layout are only candidates, and are never removed automatically
+	let changes = data().compute_unused_layout_image_ids();
+	assert_eq!(changes.len(), 2);
+	assert_eq!((changes[0].layout_name.as_str(), &changes[0].removed), ("Level 1", &vec![3]));
+	assert_eq!((changes[1].layout_name.as_str(), &changes[1].removed), ("Level 2", &vec![4]));
+	assert!(changes.iter().all(|c| c.added.is_empty()));
+}
+
+#[test]
+fn update_layout_image_ids() {
+	let data = data();
+	let changes = data.compute_layout_image_ids();
+	let s = reducer(state(data), AppAction::Data(DataAction::UpdateLayoutImageIds(changes)));
+	assert!(s.data.compute_layout_image_ids().is_empty());
+	assert_eq!(s.data.layouts[0].image_ids, [1, 3, 7, 2, 4, 5, 6]);
+	assert_eq!(s.data.layouts[1].image_ids, [4, 5, 7]);
+
+	let removed = LayoutImageIdsChange { layout_name: "Level 1".into(), added: vec![], removed: vec![3, 4] };
+	let s = reducer(s, AppAction::Data(DataAction::UpdateLayoutImageIds(vec![removed])));
+	assert_eq!(s.data.layouts[0].image_ids, [1, 7, 2, 5, 6]);
+}
@@ -0,0 +1,74 @@
+mod common;
+use common::*;
+use towermod_shared::app::selectors;
+use towermod_shared::app::state::{app_state::reducer, AppAction, DataAction, State};
+use towermod_shared::cstc_editing::CstcData;
+
+fn dispatch(state: State, action: DataAction) -> State {
+	reducer(state, AppAction::Data(action))
+}
+
+fn layer_ids(state: &State, layout_name: &str) -> Vec<i32> {
+	selectors::select_layout_layers(layout_name.to_string())(state)
+}
+
+fn project() -> State {
+	state(CstcData {
+		layouts: vec![
+			layout("Level 1", vec![layer(1, vec![instance(10, 1, 0, 0)]), layer(2, vec![])]),
+			layout("Level 2", vec![layer(5, vec![])]),
+		],
+		..Default::default()
+	})
+}
+
+#[test]
+fn create_layout_layer() {
+	let s = project();
+	// ids are unique across all layouts
+	let id = selectors::select_new_layout_layer_id()(&s);
+	assert_eq!(id, 6);
+
+	let s = dispatch(s, DataAction::CreateLayoutLayer { id, layout_name: "Level 1".into(), name: "Background".into(), index: Some(0) });
+	assert_eq!(layer_ids(&s, "Level 1"), [6, 1, 2]);
+	assert_eq!(selectors::select_layout_layer(6)(&s).unwrap().name, "Background");
+	assert_eq!(selectors::select_new_layout_layer_id()(&s), 7);
+
+	// out of range indices append the layer, duplicate ids are rejected
+	let s = dispatch(s, DataAction::CreateLayoutLayer { id: 7, layout_name: "Level 1".into(), name: "Top".into(), index: Some(99) });
+	let s = dispatch(s, DataAction::CreateLayoutLayer { id: 5, layout_name: "Level 1".into(), name: "Duplicate".into(), index: None });
+	assert_eq!(layer_ids(&s, "Level 1"), [6, 1, 2, 7]);
+	assert_eq!(selectors::select_new_layout_layer_id()(&State::default()), 1);
+}
+
+#[test]
+fn delete_layout_layer() {
+	let s = dispatch(project(), DataAction::DeleteLayoutLayer(2));
+	assert_eq!(layer_ids(&s, "Level 1"), [1]);
+
+	// layers with instances are kept
+	let s = dispatch(s, DataAction::DeleteLayoutLayer(1));
+	assert_eq!(layer_ids(&s, "Level 1"), [1]);
+	assert!(selectors::select_object_instance(10)(&s).is_some());
+
+	let s = dispatch(s, DataAction::DeleteObjectInstance(10));
+	let s = dispatch(s, DataAction::DeleteLayoutLayer(1));
+	assert!(layer_ids(&s, "Level 1").is_empty());
+	assert_eq!(layer_ids(&s, "Level 2"), [5]);
+}
+
+#[test]
+fn move_layout_layer_and_instances() {
+	let s = dispatch(project(), DataAction::CreateLayoutLayer { id: 3, layout_name: "Level 1".into(), name: "Top".into(), index: None });
+	let s = dispatch(s, DataAction::MoveLayoutLayer { id: 3, index: 0 });
+	assert_eq!(layer_ids(&s, "Level 1"), [3, 1, 2]);
+	let s = dispatch(s, DataAction::MoveLayoutLayer { id: 3, index: 99 });
+	assert_eq!(layer_ids(&s, "Level 1"), [1, 2, 3]);
+
+	let s = dispatch(s, DataAction::MoveObjectInstances { ids: vec![10], layout_layer_id: 5 });
+	assert!(selectors::select_layout_layer(1)(&s).unwrap().objects.is_empty());
+	assert_eq!(selectors::select_layout_layer(5)(&s).unwrap().objects[0].id, 10);
+	// moving to a layer that doesn't exist does nothing
+	let s = dispatch(s, DataAction::MoveObjectInstances { ids: vec![10], layout_layer_id: 99 });
+	assert_eq!(selectors::select_layout_layer(5)(&s).unwrap().objects.len(), 1);
+}
@@ -0,0 +1,144 @@
+mod common;
+use std::collections::HashMap;
+use common::*;
+use towermod_shared::cstc_editing::{CstcData, EdObjectInstance};
+use towermod_shared::layout_renderer::{render_layout, RenderOptions, RgbaImage};
+
+const RED: [u8; 4] = [255, 0, 0, 255];
+const BLUE: [u8; 4] = [0, 0, 255, 255];
+const GREEN: [u8; 4] = [0, 255, 0, 255];
+const WHITE: [u8; 4] = [255, 255, 255, 255];
+
+fn solid(width: u32, height: u32, color: [u8; 4]) -> RgbaImage {
+	RgbaImage { width, height, pixels: color.repeat((width * height) as usize) }
+}
+
+fn pixel(image: &RgbaImage, x: u32, y: u32) -> [u8; 4] {
+	let i = (y * image.width + x) as usize * 4;
+	image.pixels[i..i + 4].try_into().unwrap()
+}
+
+/// White opaque filter, positioned at (x, y) with the given size and angle
+fn placed(mut obj: EdObjectInstance, x: i32, y: i32, size: i32, angle: f32) -> EdObjectInstance {
+	obj.filter = -1;
+	(obj.x, obj.y, obj.width, obj.height, obj.angle) = (x, y, size, size, angle);
+	obj
+}
+
+/// Image 1 is red, 2 is blue (hotspot in its center), 3 is green.
+/// Animation 10 has a 0° angle showing red and a 90° angle showing blue, animation 20 has frames green, red.
+fn project() -> (CstcData, HashMap<i32, RgbaImage>) {
+	let mut angle0 = animation(11, "Default", &[1], vec![]);
+	let mut angle90 = animation(12, "", &[2], vec![]);
+	angle90.is_angle = true;
+	angle90.angle = 90.0;
+	angle0.sub_animations.push(angle90);
+	let mut blue = image(2, 8, 8);
+	(blue.hotspot_x, blue.hotspot_y) = (4, 4);
+
+	let mut second_frame = sprite(4, 2, 20);
+	if let towermod_cstc::ObjectData::Sprite(sprite) = &mut second_frame.data { sprite.start_frame = 2 }
+	let mut zoomed = layer(2, vec![placed(sprite(5, 3, 30), 40, 36, 4, 0.0)]);
+	(zoomed.zoom_x, zoomed.zoom_y) = (2.0, 2.0);
+	let mut rotated = layer(3, vec![placed(sprite(6, 3, 30), 32, 4, 8, 0.0)]);
+	rotated.angle = 180.0;
+
+	let data = CstcData {
+		animations: [
+			(10, animation(10, "", &[], vec![angle0])),
+			(20, animation(20, "", &[], vec![animation(21, "Default", &[3, 1], vec![])])),
+			(30, animation(30, "", &[], vec![animation(31, "Default", &[3], vec![])])),
+		].into_iter().collect(),
+		image_block: vec![image(1, 8, 8), blue, image(3, 8, 8)],
+		layouts: vec![{
+			let mut l = layout("Level 1", vec![
+				layer(1, vec![
+					placed(sprite(1, 1, 10), 0, 0, 8, 0.0),
+					// closest angle is 90°, drawn with the remaining 10° of rotation
+					placed(sprite(2, 1, 10), 20, 20, 8, 80.0),
+					placed(second_frame, 0, 40, 8, 0.0),
+				]),
+				zoomed,
+				rotated,
+			]);
+			(l.width, l.height, l.color) = (64, 64, 0xffffff);
+			l
+		}],
+		..Default::default()
+	};
+	let images = [(1, solid(8, 8, RED)), (2, solid(8, 8, BLUE)), (3, solid(8, 8, GREEN))].into();
+	(data, images)
+}
+
+#[test]
+fn render_layout_golden() {
+	let (data, images) = project();
+	let rendered = render_layout(&data, &data.layouts[0], &images, &RenderOptions::default());
+	assert_eq!((rendered.width, rendered.height), (64, 64));
+
+	assert_eq!(pixel(&rendered, 4, 4), RED);
+	assert_eq!(pixel(&rendered, 10, 10), WHITE);
+	// angle animation, centered on its hotspot
+	assert_eq!(pixel(&rendered, 20, 20), BLUE);
+	// starting frame 2
+	assert_eq!(pixel(&rendered, 4, 44), RED);
+	// zoomed 2x around the center of the view: (40..44, 36..40) -> (48..56, 40..48)
+	assert_eq!(pixel(&rendered, 52, 44), GREEN);
+	assert_eq!(pixel(&rendered, 42, 38), WHITE);
+	// rotated 180° around the center of the view: (32..40, 4..12) -> (24..32, 52..60)
+	assert_eq!(pixel(&rendered, 28, 56), GREEN);
+	assert_eq!(pixel(&rendered, 36, 8), WHITE);
+
+	let golden_path = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/layout_render.png");
+	if std::env::var_os("UPDATE_GOLDEN").is_some() {
+		std::fs::write(&golden_path, rendered.to_png().unwrap()).unwrap();
+	}
+	let golden = RgbaImage::from_png(&std::fs::read(&golden_path).unwrap()).unwrap();
+	assert_eq!((golden.width, golden.height), (rendered.width, rendered.height));
+	assert!(golden.pixels == rendered.pixels, "Rendered layout differs from {}, rerun with UPDATE_GOLDEN=1 if the change is intended", golden_path.display());
+}
+
+#[test]
+fn render_layout_scaled() {
+	let (data, images) = project();
+	let options = RenderOptions { x: 0.0, y: 0.0, width: Some(32), height: Some(32), scale: 0.5 };
+	let rendered = render_layout(&data, &data.layouts[0], &images, &options);
+	assert_eq!((rendered.width, rendered.height), (16, 16));
+	assert_eq!(pixel(&rendered, 1, 1), RED);
+	assert_eq!(pixel(&rendered, 10, 10), BLUE);
+}
+
+#[test]
+fn render_layout_textures() {
+	use towermod_cstc::{ObjectData, PanelObjectData, TiledBackgroundObjectData};
+	// image 1 is red on the left and blue on the right, image 2 is green
+	let striped = RgbaImage { width: 2, height: 1, pixels: [RED, BLUE].concat() };
+	let images = [(1, striped), (2, solid(4, 4, GREEN))].into();
+	let with_data = |id: i32, data: ObjectData, x: i32, y: i32, width: i32, height: i32| {
+		let mut obj = placed(instance(id, id, 0, 0), x, y, 0, 0.0);
+		(obj.data, obj.width, obj.height) = (data, width, height);
+		obj
+	};
+	let data = CstcData {
+		image_block: vec![image(1, 2, 1), image(2, 4, 4)],
+		layouts: vec![{
+			let mut l = layout("Level 1", vec![layer(1, vec![
+				with_data(1, ObjectData::TiledBackground(TiledBackgroundObjectData { texture_id: 1, ..Default::default() }), 0, 0, 6, 2),
+				with_data(2, ObjectData::Panel(PanelObjectData { image_id: 2, ..Default::default() }), 8, 8, 12, 6),
+			])]);
+			(l.width, l.height, l.color) = (32, 32, 0xffffff);
+			l
+		}],
+		..Default::default()
+	};
+	let layout = &data.layouts[0];
+	assert_eq!(towermod_shared::layout_renderer::required_image_ids(&data, layout), [1, 2].into());
+	let rendered = render_layout(&data, layout, &images, &RenderOptions::default());
+	// the texture repeats over the whole instance
+	assert_eq!([0, 1, 4, 5].map(|x| pixel(&rendered, x, 1)), [RED, BLUE, RED, BLUE]);
+	assert_eq!(pixel(&rendered, 6, 0), WHITE);
+	// panels are stretched from their top left corner
+	assert_eq!(pixel(&rendered, 8, 8), GREEN);
+	assert_eq!(pixel(&rendered, 19, 13), GREEN);
+	assert_eq!(pixel(&rendered, 20, 14), WHITE);
+}
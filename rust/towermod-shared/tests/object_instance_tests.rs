@@ -0,0 +1,42 @@
+mod common;
+use common::*;
+use towermod_cstc::{ObjectData, PanelObjectData};
+use towermod_shared::app::state::{app_state::reducer, AppAction, DataAction, State};
+use towermod_shared::cstc_editing::{CstcData, EdObjectInstance};
+
+fn dispatch(state: State, action: DataAction) -> State {
+	reducer(state, AppAction::Data(action))
+}
+
+fn instance_data(state: &State, id: i32) -> Option<&ObjectData> {
+	state.data.layouts.iter().flat_map(|l| l.layers.iter().flat_map(|l| l.objects.iter())).find(|o| o.id == id).map(|o| &o.data)
+}
+
+#[test]
+fn create_partially_decoded_instance() {
+	// a panel from the game, with fields that aren't decoded
+	let panel = PanelObjectData { version: 1, image_id: 5, trailing: vec![1, 2, 3, 4, 5, 6, 7, 8] };
+	let data = CstcData {
+		editor_plugins: [(1, plugin("Panel")), (2, plugin("Sprite"))].into(),
+		object_types: [(1, object_type(1, 1)), (2, object_type(2, 1)), (3, object_type(3, 2))].into_iter().collect(),
+		animations: [(10, animation(10, "", &[], vec![]))].into_iter().collect(),
+		layouts: vec![layout("Level 1", vec![layer(1, vec![sprite(1, 3, 10)])])],
+		..Default::default()
+	};
+	assert!(data.new_object_data(1).is_none());
+
+	// refused while there is no panel to copy from
+	let s = dispatch(state(data), DataAction::CreateObjectInstance { id: 2, object_type_id: 1, layout_layer_id: 1 });
+	assert!(instance_data(&s, 2).is_none());
+
+	// sprites get the animation of their object type
+	let s = dispatch(s, DataAction::CreateObjectInstance { id: 2, object_type_id: 3, layout_layer_id: 1 });
+	assert!(matches!(instance_data(&s, 2), Some(ObjectData::Sprite(sprite)) if sprite.animation == 10));
+
+	// copied from a panel of another object type
+	let existing = EdObjectInstance { data: ObjectData::Panel(panel.clone()), ..instance(3, 2, 0, 0) };
+	let s = dispatch(s, DataAction::InsertObjectInstances { instances: vec![existing], layout_layer_id: 1 });
+	let s = dispatch(s, DataAction::CreateObjectInstance { id: 4, object_type_id: 1, layout_layer_id: 1 });
+	let Some(ObjectData::Panel(created)) = instance_data(&s, 4) else { panic!() };
+	assert_eq!(created.to_bin(), panel.to_bin());
+}
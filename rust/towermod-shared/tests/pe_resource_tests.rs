@@ -0,0 +1,226 @@
+use std::collections::BTreeMap;
+use towermod_shared::{list_pe_resources, read_all_pe_resources, read_pe_resource, stage_debug_build_resources, string_table_block, transplant_pe_resources, PeResourceSet, ResourceId};
+
+type Resource<'a> = (ResourceId, ResourceId, u16, &'a [u8]);
+
+/// Sort order of resource directory entries: named entries first, then IDs in ascending order
+fn entry_key(id: &ResourceId) -> (u8, String, u16) {
+	match id {
+		ResourceId::String(s) => (0, s.to_uppercase(), 0),
+		ResourceId::Int(i) => (1, String::new(), *i),
+	}
+}
+
+/// Build a `.rsrc` section for the given resources, to be loaded at `rva`
+fn rsrc_section(resources: &[Resource], rva: u32) -> Vec<u8> {
+	let mut tree: BTreeMap<_, (&ResourceId, BTreeMap<_, (&ResourceId, BTreeMap<u16, &[u8]>)>)> = BTreeMap::new();
+	for (res_type, name, language, bin) in resources {
+		let (_, names) = tree.entry(entry_key(res_type)).or_insert((res_type, BTreeMap::new()));
+		let (_, languages) = names.entry(entry_key(name)).or_insert((name, BTreeMap::new()));
+		languages.insert(*language, *bin);
+	}
+
+	// lay out directories, then strings, then data entries, then data
+	let dir_size = |entries: usize| (16 + 8 * entries) as u32;
+	let mut offset = dir_size(tree.len());
+	let mut type_dirs = Vec::new();
+	let mut name_dirs = Vec::new();
+	for (_, names) in tree.values() {
+		type_dirs.push(offset);
+		offset += dir_size(names.len());
+	}
+	for (_, names) in tree.values() {
+		for (_, languages) in names.values() {
+			name_dirs.push(offset);
+			offset += dir_size(languages.len());
+		}
+	}
+	let mut strings = BTreeMap::new();
+	let ids = tree.values().flat_map(|(res_type, names)| std::iter::once(*res_type).chain(names.values().map(|(name, _)| *name)));
+	for id in ids {
+		if let ResourceId::String(s) = id {
+			if !strings.contains_key(s) {
+				strings.insert(s.clone(), offset);
+				offset += 2 + 2 * s.encode_utf16().count() as u32;
+			}
+		}
+	}
+	offset = offset.next_multiple_of(4);
+	let leaves = tree.values().flat_map(|(_, names)| names.values().flat_map(|(_, languages)| languages.iter())).collect::<Vec<_>>();
+	let data_entries = offset;
+	offset += 16 * leaves.len() as u32;
+	let mut data_offsets = Vec::new();
+	for (_, bin) in &leaves {
+		offset = offset.next_multiple_of(8);
+		data_offsets.push(offset);
+		offset += bin.len() as u32;
+	}
+
+	let mut section = vec![0u8; offset as usize];
+	let mut put = |at: u32, bytes: &[u8]| section[at as usize..at as usize + bytes.len()].copy_from_slice(bytes);
+	let put_directory = |put: &mut dyn FnMut(u32, &[u8]), at: u32, ids: &[&ResourceId], targets: &[u32]| {
+		let named = ids.iter().filter(|id| matches!(id, ResourceId::String(_))).count() as u16;
+		put(at + 12, &named.to_le_bytes());
+		put(at + 14, &(ids.len() as u16 - named).to_le_bytes());
+		for (i, (id, target)) in ids.iter().zip(targets).enumerate() {
+			let name = match id {
+				ResourceId::Int(i) => *i as u32,
+				ResourceId::String(s) => 0x8000_0000 | strings[s],
+			};
+			put(at + 16 + 8 * i as u32, &name.to_le_bytes());
+			put(at + 20 + 8 * i as u32, &target.to_le_bytes());
+		}
+	};
+	let subdir = |offset: &u32| 0x8000_0000 | offset;
+	put_directory(&mut put, 0, &tree.values().map(|(id, _)| *id).collect::<Vec<_>>(), &type_dirs.iter().map(subdir).collect::<Vec<_>>());
+	let mut name_dir_iter = name_dirs.iter();
+	let mut leaf = 0;
+	for ((_, names), type_dir) in tree.values().zip(&type_dirs) {
+		let dirs = name_dir_iter.by_ref().take(names.len()).copied().collect::<Vec<_>>();
+		put_directory(&mut put, *type_dir, &names.values().map(|(id, _)| *id).collect::<Vec<_>>(), &dirs.iter().map(subdir).collect::<Vec<_>>());
+		for ((_, languages), name_dir) in names.values().zip(&dirs) {
+			let language_ids = languages.keys().map(|l| ResourceId::Int(*l)).collect::<Vec<_>>();
+			let entries = (leaf..leaf + languages.len()).map(|i| data_entries + 16 * i as u32).collect::<Vec<_>>();
+			put_directory(&mut put, *name_dir, &language_ids.iter().collect::<Vec<_>>(), &entries);
+			leaf += languages.len();
+		}
+	}
+	for (s, at) in &strings {
+		let units = s.encode_utf16().collect::<Vec<_>>();
+		put(*at, &(units.len() as u16).to_le_bytes());
+		for (i, unit) in units.iter().enumerate() {
+			put(at + 2 + 2 * i as u32, &unit.to_le_bytes());
+		}
+	}
+	for (i, ((_, bin), data)) in leaves.iter().zip(&data_offsets).enumerate() {
+		let entry = data_entries + 16 * i as u32;
+		put(entry, &(rva + data).to_le_bytes());
+		put(entry + 4, &(bin.len() as u32).to_le_bytes());
+		put(*data, bin);
+	}
+	section
+}
+
+/// Minimal 32-bit executable whose only section holds the given resources
+fn synthetic_pe(resources: &[Resource]) -> Vec<u8> {
+	const FILE_ALIGNMENT: u32 = 0x200;
+	const SECTION_ALIGNMENT: u32 = 0x1000;
+	let rsrc = rsrc_section(resources, SECTION_ALIGNMENT);
+	let raw_size = (rsrc.len() as u32).next_multiple_of(FILE_ALIGNMENT);
+
+	let mut pe = vec![0u8; FILE_ALIGNMENT as usize];
+	let mut put = |at: usize, bytes: &[u8]| pe[at..at + bytes.len()].copy_from_slice(bytes);
+	put(0, b"MZ");
+	put(0x3c, &0x40u32.to_le_bytes());
+	put(0x40, b"PE\0\0");
+	// file header: i386, 1 section, executable 32-bit image
+	put(0x44, &0x14cu16.to_le_bytes());
+	put(0x46, &1u16.to_le_bytes());
+	put(0x54, &224u16.to_le_bytes());
+	put(0x56, &0x0102u16.to_le_bytes());
+	// optional header
+	let opt = 0x58;
+	put(opt, &0x10bu16.to_le_bytes());
+	put(opt + 8, &raw_size.to_le_bytes());
+	put(opt + 28, &0x400000u32.to_le_bytes());
+	put(opt + 32, &SECTION_ALIGNMENT.to_le_bytes());
+	put(opt + 36, &FILE_ALIGNMENT.to_le_bytes());
+	put(opt + 40, &4u16.to_le_bytes());
+	put(opt + 48, &4u16.to_le_bytes());
+	put(opt + 56, &(SECTION_ALIGNMENT + (rsrc.len() as u32).next_multiple_of(SECTION_ALIGNMENT)).to_le_bytes());
+	put(opt + 60, &FILE_ALIGNMENT.to_le_bytes());
+	put(opt + 68, &2u16.to_le_bytes());
+	put(opt + 72, &0x100000u32.to_le_bytes());
+	put(opt + 76, &0x1000u32.to_le_bytes());
+	put(opt + 80, &0x100000u32.to_le_bytes());
+	put(opt + 84, &0x1000u32.to_le_bytes());
+	put(opt + 92, &16u32.to_le_bytes());
+	// resource data directory
+	put(opt + 96 + 2 * 8, &SECTION_ALIGNMENT.to_le_bytes());
+	put(opt + 96 + 2 * 8 + 4, &(rsrc.len() as u32).to_le_bytes());
+	// section header
+	let section = opt + 224;
+	put(section, b".rsrc\0\0\0");
+	put(section + 8, &(rsrc.len() as u32).to_le_bytes());
+	put(section + 12, &SECTION_ALIGNMENT.to_le_bytes());
+	put(section + 16, &raw_size.to_le_bytes());
+	put(section + 20, &FILE_ALIGNMENT.to_le_bytes());
+	put(section + 36, &0x40000040u32.to_le_bytes());
+
+	pe.extend(&rsrc);
+	pe.resize((FILE_ALIGNMENT + raw_size) as usize, 0);
+	pe
+}
+
+#[test]
+fn transplant_resources_between_synthetic_executables() {
+	let src_resources: Vec<Resource> = vec![
+		(ResourceId::from("APPBLOCK"), ResourceId::from(997), 0, b"app block data"),
+		(ResourceId::from("DLLBLOCK"), ResourceId::from(1000), 0, &[0x4d, 0x5a, 0, 0, 1, 2, 3]),
+		(ResourceId::from(10), ResourceId::from("SETTINGS"), 1033, b"key=value"),
+		(ResourceId::from(6), ResourceId::from(63), 1033, &[0, 0, 3, 0, b'a', 0, b'b', 0, b'c', 0]),
+	];
+	let dest_resources: Vec<Resource> = vec![
+		(ResourceId::from(24), ResourceId::from(1), 1033, b"<assembly/>"),
+		(ResourceId::from("APPBLOCK"), ResourceId::from(997), 0, b"old"),
+	];
+	let dir = tempfile::tempdir().unwrap();
+	let src = dir.path().join("game.exe");
+	let dest = dir.path().join("runtime.exe");
+	std::fs::write(&src, synthetic_pe(&src_resources)).unwrap();
+	std::fs::write(&dest, synthetic_pe(&dest_resources)).unwrap();
+
+	let listed = list_pe_resources(&src).unwrap();
+	assert_eq!(listed.len(), src_resources.len());
+	for (res_type, name, language, bin) in &src_resources {
+		assert!(listed.iter().any(|r| &r.res_type == res_type && &r.name == name && r.language == *language && r.size == bin.len()), "{res_type}/{name} not listed");
+	}
+
+	transplant_pe_resources(&src, &dest).unwrap();
+
+	let listed = list_pe_resources(&dest).unwrap();
+	assert_eq!(listed.len(), 5);
+	for (res_type, name, language, bin) in src_resources.iter().chain(&dest_resources[..1]) {
+		assert_eq!(read_pe_resource(&dest, res_type, name, Some(*language)).unwrap(), *bin, "{res_type}/{name}");
+	}
+	assert_eq!(listed.iter().find(|r| r.res_type == ResourceId::from(10)).unwrap().type_name.as_deref(), Some("RCDATA"));
+}
+
+#[test]
+fn string_table_blocks() {
+	assert_eq!(string_table_block(&["", "ab"]), [0, 0, 2, 0, b'a', 0, b'b', 0]);
+	assert_eq!(string_table_block(&["é"]), [1, 0, 0xe9, 0]);
+}
+
+#[test]
+fn debug_build_resources() {
+	let src_resources: Vec<Resource> = vec![
+		(ResourceId::from("APPBLOCK"), ResourceId::from(997), 0, b"app block data"),
+		(ResourceId::from("DLLBLOCK"), ResourceId::from(1000), 0, b"first plugin"),
+		(ResourceId::from("DLLBLOCK"), ResourceId::from(1001), 0, b"second plugin"),
+		(ResourceId::from(24), ResourceId::from(1), 1033, b"<game/>"),
+	];
+	let dir = tempfile::tempdir().unwrap();
+	let src = dir.path().join("game.exe");
+	let dest = dir.path().join("preview.exe");
+	std::fs::write(&src, synthetic_pe(&src_resources)).unwrap();
+	std::fs::write(&dest, synthetic_pe(&[(ResourceId::from(24), ResourceId::from(1), 1033, b"<assembly/>")])).unwrap();
+
+	let mut set = PeResourceSet::new();
+	let plugins = stage_debug_build_resources(&mut set, read_all_pe_resources(&src).unwrap());
+	assert_eq!(plugins, [("plugin0.csx".to_string(), b"first plugin".to_vec()), ("plugin1.csx".to_string(), b"second plugin".to_vec())]);
+	set.commit(&dest).unwrap();
+
+	let listed = list_pe_resources(&dest).unwrap();
+	assert!(listed.iter().all(|r| r.res_type != ResourceId::from("DLLBLOCK")));
+	assert_eq!(read_pe_resource(&dest, &ResourceId::from("APPBLOCK"), &ResourceId::from(997), None).unwrap(), b"app block data");
+	// resources keep their language, replacing the runtime's own instead of being added next to it as language neutral
+	let manifests = listed.iter().filter(|r| r.res_type == ResourceId::from(24)).collect::<Vec<_>>();
+	assert_eq!(manifests.len(), 1);
+	assert_eq!(manifests[0].language, 1033);
+	assert_eq!(read_pe_resource(&dest, &ResourceId::from(24), &ResourceId::from(1), Some(1033)).unwrap(), b"<game/>");
+	for (i, (file_name, _)) in plugins.iter().enumerate() {
+		let entry = read_pe_resource(&dest, &ResourceId::from(6), &ResourceId::from(1000 + i as u16), Some(0)).unwrap();
+		assert_eq!(entry, string_table_block(&["", file_name]));
+	}
+}
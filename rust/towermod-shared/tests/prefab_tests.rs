@@ -0,0 +1,121 @@
+mod common;
+use common::*;
+use towermod_cstc::{Behavior, ObjectData, ObjectTrait};
+use towermod_shared::cstc_editing::{CstcData, EdContainer, EdFamily, Prefab, PrefabIds};
+
+fn behavior(object_type_id: i32, plugin_id: i32, mov_index: i32) -> Behavior {
+	Behavior { object_type_id, new_index: plugin_id, mov_index, name: format!("Behavior{mov_index}"), data: vec![1, 2, 3], descriptors: None }
+}
+
+fn source() -> CstcData {
+	let mut player = object_type(1, 1);
+	player.name = "Player".into();
+	let mut gun = object_type(2, 1);
+	gun.name = "Gun".into();
+	CstcData {
+		editor_plugins: [(1, plugin("Sprite")), (2, plugin("Platform")), (3, plugin("Sine"))].into(),
+		object_types: [(1, player), (2, gun), (3, object_type(3, 1))].into_iter().collect(),
+		animations: [
+			(10, animation(10, "", &[], vec![animation(11, "Default", &[100, 101], vec![])])),
+			(20, animation(20, "", &[], vec![animation(21, "Default", &[101], vec![])])),
+		].into_iter().collect(),
+		behaviors: vec![behavior(1, 2, 0), behavior(1, 3, 1), behavior(3, 2, 0)],
+		families: vec![EdFamily { name: "Enemies".into(), object_type_ids: vec![1, 3], private_variables: Default::default() }],
+		traits: vec![ObjectTrait { name: "Solid".into(), object_type_ids: vec![1] }],
+		containers: [(1, EdContainer { id: 1, object_ids: vec![1, 2, 3] })].into_iter().collect(),
+		image_block: vec![image(100, 16, 16), image(101, 8, 8)],
+		layouts: vec![layout("Level 1", vec![layer(1, vec![sprite(50, 1, 10), sprite(51, 2, 20)])])],
+		..Default::default()
+	}
+}
+
+/// Same plugins under different ids, and one existing object type named "Player"
+fn target() -> CstcData {
+	let mut existing = object_type(1, 7);
+	existing.name = "Player".into();
+	CstcData {
+		editor_plugins: [(7, plugin("Sprite")), (8, plugin("Sine")), (9, plugin("Platform"))].into(),
+		object_types: [(1, existing)].into_iter().collect(),
+		animations: [(1, animation(1, "", &[], vec![]))].into_iter().collect(),
+		traits: vec![ObjectTrait { name: "Solid".into(), object_type_ids: vec![1] }],
+		image_block: vec![image(1, 4, 4)],
+		layouts: vec![layout("Level 1", vec![layer(1, vec![sprite(1, 1, 1)])])],
+		..Default::default()
+	}
+}
+
+const IDS: PrefabIds = PrefabIds { object_type_id: 2, object_instance_id: 2, animation_id: 2, image_id: 2 };
+
+#[test]
+fn remap_prefab() {
+	let prefab = Prefab::export(&source(), &[1, 2]).unwrap();
+	assert_eq!(prefab.images.iter().map(|i| i.id).collect::<Vec<_>>(), [100, 101]);
+	assert_eq!(prefab.containers[0].object_ids, [1, 2]);
+
+	let (prefab, import) = prefab.remap(&target(), IDS).unwrap();
+	assert_eq!(import.object_type_ids, [2, 3]);
+	assert_eq!(import.image_ids, [(100, 2), (101, 3)].into());
+
+	let player = &prefab.object_types[0];
+	assert_eq!((player.object_type.name.as_str(), player.object_type.plugin_id), ("Player2", 7));
+	// behaviors keep their position and point at the target project's movement plugins
+	assert_eq!(player.behaviors.iter().map(|b| (b.behavior.object_type_id, b.behavior.new_index, b.behavior.mov_index)).collect::<Vec<_>>(), [(2, 9, 0), (2, 8, 1)]);
+	let animation = player.animation.as_ref().unwrap();
+	assert_eq!((animation.id, animation.sub_animations[0].id), (2, 3));
+	assert_eq!(animation.sub_animations[0].frames.iter().map(|f| f.image_id).collect::<Vec<_>>(), [2, 3]);
+	let instance = player.instance.as_ref().unwrap();
+	assert_eq!((instance.id, instance.object_type_id), (2, 2));
+	let ObjectData::Sprite(sprite) = &instance.data else { panic!() };
+	assert_eq!(sprite.animation, 2);
+
+	let gun = &prefab.object_types[1];
+	assert_eq!(gun.object_type.name, "Gun");
+	assert_eq!(gun.animation.as_ref().unwrap().id, 4);
+	assert_eq!(gun.instance.as_ref().unwrap().id, 3);
+	assert_eq!((prefab.containers[0].id, &prefab.containers[0].object_ids), (2, &vec![2, 3]));
+	assert_eq!(prefab.images.iter().map(|i| i.id).collect::<Vec<_>>(), [2, 3]);
+}
+
+#[test]
+fn remap_prefab_errors() {
+	// a frame whose image isn't part of the prefab
+	let mut prefab = Prefab::export(&source(), &[1]).unwrap();
+	prefab.images.retain(|i| i.id != 101);
+	assert!(prefab.remap(&target(), IDS).is_err());
+
+	// a behavior without a plugin name would keep the source project's plugin index
+	let mut prefab = Prefab::export(&source(), &[1]).unwrap();
+	prefab.object_types[0].behaviors[0].plugin_name = None;
+	assert!(prefab.remap(&target(), IDS).is_err());
+
+	let mut target = target();
+	target.editor_plugins.remove(&9);
+	assert!(Prefab::export(&source(), &[1]).unwrap().remap(&target, IDS).is_err());
+}
+
+#[test]
+fn export_prefab_unknown_behavior_plugin() {
+	let mut data = source();
+	data.editor_plugins.remove(&3);
+	assert!(Prefab::export(&data, &[1]).is_err());
+	assert!(Prefab::export(&data, &[2, 3]).is_ok());
+}
+
+#[test]
+fn merge_prefab() {
+	let mut data = target();
+	let (prefab, _) = Prefab::export(&source(), &[1, 2]).unwrap().remap(&data, IDS).unwrap();
+	prefab.merge_into(&mut data, 1);
+
+	assert_eq!(data.object_types.keys().copied().collect::<Vec<_>>(), [1, 2, 3]);
+	assert_eq!(data.animations.keys().copied().collect::<Vec<_>>(), [1, 2, 4]);
+	assert_eq!(data.layouts[0].layers[0].objects.iter().map(|o| (o.id, o.object_type_id)).collect::<Vec<_>>(), [(1, 1), (2, 2), (3, 3)]);
+	assert_eq!(data.behaviors.len(), 2);
+	assert_eq!(data.image_block.iter().map(|i| i.id).collect::<Vec<_>>(), [1, 2, 3]);
+	assert_eq!(data.containers[&2].object_ids, [2, 3]);
+	// families are created if needed, traits with the same name are shared
+	assert_eq!(data.families.len(), 1);
+	assert_eq!(data.families[0].object_type_ids, [2]);
+	assert_eq!(data.traits.len(), 1);
+	assert_eq!(data.traits[0].object_type_ids, [1, 2]);
+}
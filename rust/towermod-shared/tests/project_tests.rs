@@ -0,0 +1,55 @@
+use std::collections::HashMap;
+use towermod_shared::{read_editor_plugins_cache, write_editor_plugins_cache, Game, Project, ResourceId, ResourceReplacement};
+
+fn resource(file: &str) -> ResourceReplacement {
+	ResourceReplacement { res_type: ResourceId::String("WAVE".into()), name: ResourceId::Int(105), language: None, file: file.into() }
+}
+
+#[test]
+fn resource_files_stay_in_project() {
+	for file in ["resources/jump.wav", "jump.wav", "sounds\\jump.wav", "a..b/jump.wav"] {
+		assert!(resource(file).validate().is_ok(), "{file}");
+	}
+	for file in ["/etc/passwd", "\\Windows\\win.ini", "C:\\Windows\\win.ini", "c:jump.wav", "../jump.wav", "resources/../../jump.wav", "resources\\..\\..\\jump.wav"] {
+		assert!(resource(file).validate().is_err(), "{file}");
+	}
+}
+
+#[tokio::test]
+async fn save_and_load_resource_outside_project() {
+	let dir = tempfile::tempdir().unwrap();
+	let mut project = Project::new("author".into(), "name".into(), "Name".into(), "0.0.1".into(), Game::default());
+	project.dir_path = Some(dir.path().to_owned());
+	project.resources.push(resource("../secret.wav"));
+	assert!(project.save().await.is_err());
+	assert!(!dir.path().join("manifest.toml").exists());
+
+	project.resources = vec![resource("resources/jump.wav")];
+	project.save().await.unwrap();
+	let manifest = dir.path().join("manifest.toml");
+	assert_eq!(Project::from_path(&manifest).await.unwrap().resources[0].file, std::path::Path::new("resources/jump.wav"));
+
+	// edited by hand
+	let s = std::fs::read_to_string(&manifest).unwrap().replace("resources/jump.wav", "../secret.wav");
+	std::fs::write(&manifest, s).unwrap();
+	assert!(Project::from_path(&manifest).await.is_err());
+}
+
+#[tokio::test]
+async fn editor_plugins_cache_from_older_version_is_ignored() {
+	let dir = tempfile::tempdir().unwrap();
+	let path = dir.path().join("plugindata.msgpack");
+	let names = HashMap::from([(0, String::from("Sprite")), (1, String::from("Text"))]);
+
+	assert_eq!(read_editor_plugins_cache::<HashMap<i32, String>>(&path).await, None);
+
+	// unversioned, as written before the cache had a version
+	std::fs::write(&path, rmp_serde::to_vec(&names).unwrap()).unwrap();
+	assert_eq!(read_editor_plugins_cache::<HashMap<i32, String>>(&path).await, None);
+
+	std::fs::write(&path, rmp_serde::to_vec(&(towermod_shared::EDITOR_PLUGINS_CACHE_VERSION - 1, &names)).unwrap()).unwrap();
+	assert_eq!(read_editor_plugins_cache::<HashMap<i32, String>>(&path).await, None);
+
+	write_editor_plugins_cache(&path, &names).await.unwrap();
+	assert_eq!(read_editor_plugins_cache::<HashMap<i32, String>>(&path).await, Some(names));
+}
@@ -0,0 +1,67 @@
+mod common;
+use common::*;
+use towermod_shared::app::selectors;
+use towermod_shared::app::state::{app_state::reducer, AppAction, DataAction, State};
+use towermod_shared::cstc_editing::{CstcData, EdObjectInstance, SpatialIndex};
+
+fn build_index(objects: Vec<EdObjectInstance>) -> SpatialIndex {
+	let data = CstcData { layouts: vec![layout("Level 1", vec![layer(1, objects)])], ..Default::default() };
+	let mut index = SpatialIndex::default();
+	index.rebuild(&data);
+	index
+}
+
+fn point(id: i32, x: i32, y: i32) -> EdObjectInstance {
+	EdObjectInstance { width: 4, height: 4, ..instance(id, 1, x, y) }
+}
+
+#[test]
+fn nearest_across_cell_boundary() {
+	// cells are 128 units wide, the query point sits right at the edge of cell (0, 0)
+	let index = build_index(vec![point(1, 0, 0), point(2, 129, 0)]);
+	assert_eq!(index.query_nearest(1, 127.9, 0.0), Some(2));
+	assert_eq!(index.query_nearest(1, 0.1, 0.0), Some(1));
+	// negative coordinates round down to the previous cell
+	let index = build_index(vec![point(1, 0, 0), point(2, -129, 0)]);
+	assert_eq!(index.query_nearest(1, -127.9, 0.0), Some(2));
+}
+
+#[test]
+fn nearest_across_rings() {
+	// the first ring with any instance (2 cells away) doesn't hold the closest one
+	let index = build_index(vec![point(1, 300, 300), point(2, -260, 64)]);
+	assert_eq!(index.query_nearest(1, 10.0, 64.0), Some(2));
+	// only instances far away
+	let index = build_index(vec![point(1, 2000, 2000), point(2, -1500, 40)]);
+	assert_eq!(index.query_nearest(1, 0.0, 0.0), Some(2));
+	assert_eq!(index.query_nearest(2, 0.0, 0.0), None);
+	assert_eq!(build_index(vec![]).query_nearest(1, 0.0, 0.0), None);
+}
+
+#[test]
+fn nearest_uses_position_not_bounds() {
+	// a large instance whose bounds cover the query point, but whose position is further away than a small instance in the next cell
+	let large = EdObjectInstance { width: 1000, height: 1000, ..instance(1, 1, 0, 0) };
+	let index = build_index(vec![large, point(2, 700, 700)]);
+	assert_eq!(index.query_nearest(1, 600.0, 600.0), Some(2));
+	assert_eq!(index.query_point(1, 600.0, 600.0), [1]);
+}
+
+#[test]
+fn index_follows_actions() {
+	let dispatch = |state: State, action: DataAction| reducer(state, AppAction::Data(action));
+	let data = CstcData { layouts: vec![layout("Level 1", vec![layer(1, vec![point(1, 0, 0)])])], ..Default::default() };
+	let mut s = dispatch(state(CstcData::default()), DataAction::SetData(data));
+	assert_eq!(selectors::select_nearest_object_instance(1, 500.0, 0.0)(&s), Some(1));
+
+	s = dispatch(s, DataAction::CreateLayoutLayer { id: 2, layout_name: "Level 1".into(), name: "Layer 2".into(), index: None });
+	s = dispatch(s, DataAction::InsertObjectInstances { instances: vec![point(2, 400, 0)], layout_layer_id: 1 });
+	assert_eq!(selectors::select_nearest_object_instance(1, 500.0, 0.0)(&s), Some(2));
+
+	s = dispatch(s, DataAction::MoveObjectInstances { ids: vec![2], layout_layer_id: 2 });
+	assert_eq!(selectors::select_nearest_object_instance(1, 500.0, 0.0)(&s), Some(1));
+	assert_eq!(selectors::select_nearest_object_instance(2, 500.0, 0.0)(&s), Some(2));
+
+	s = dispatch(s, DataAction::DeleteObjectInstance(2));
+	assert_eq!(selectors::select_nearest_object_instance(2, 500.0, 0.0)(&s), None);
+}
@@ -0,0 +1,76 @@
+use std::path::Path;
+use towermod_shared::{find_steam_app, steam_library_folders, GameLauncher, SteamApp, Vdf};
+
+fn write(path: &Path, contents: &str) {
+	std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+	std::fs::write(path, contents).unwrap();
+}
+
+#[test]
+fn parse_vdf() {
+	let (key, root) = Vdf::parse(r#"
+		// comment
+		"AppState"
+		{
+			"appid"		"396640"
+			"name"		"Tower \"Climb\""
+			"InstallDir"		"TowerClimb"
+			"UserConfig" { "language" "english" }
+			unquoted value [$WIN32]
+		}
+	"#).unwrap();
+	assert_eq!(key, "AppState");
+	assert_eq!(root.get_str("appid"), Some("396640"));
+	assert_eq!(root.get_str("name"), Some(r#"Tower "Climb""#));
+	assert_eq!(root.get_str("installdir"), Some("TowerClimb"));
+	assert_eq!(root.get("userconfig").and_then(|c| c.get_str("language")), Some("english"));
+	assert_eq!(root.get_str("unquoted"), Some("value"));
+	assert_eq!(root.entries().len(), 5);
+
+	assert!(Vdf::parse(r#""AppState" { "appid" "1" "#).is_err());
+	assert!(Vdf::parse(r#""AppState" { "appid" "#).is_err());
+	assert!(Vdf::parse("").is_err());
+}
+
+#[test]
+fn find_app_in_library_folders() {
+	let dir = tempfile::tempdir().unwrap();
+	let steam_path = dir.path().join("Steam");
+	let library = dir.path().join("SteamLibrary");
+	let old_library = dir.path().join("OldLibrary");
+	write(&steam_path.join("steamapps/libraryfolders.vdf"), &format!(r#"
+		"libraryfolders"
+		{{
+			"0" {{ "path" "{}" "apps" {{ "228980" "123" }} }}
+			"1" {{ "path" "{}" "apps" {{ "396640" "456" }} }}
+		}}
+	"#, steam_path.display(), library.display()));
+	assert_eq!(steam_library_folders(&steam_path), [steam_path.clone(), library.clone()]);
+	let steam_dirs = [steam_path.clone()];
+	assert_eq!(find_steam_app(&steam_dirs, 396640), None);
+
+	write(&library.join("steamapps/appmanifest_396640.acf"), r#""AppState" { "appid" "396640" "name" "TowerClimb" "installdir" "TowerClimb" }"#);
+	std::fs::create_dir_all(library.join("steamapps/common/TowerClimb")).unwrap();
+	let app = find_steam_app(&steam_dirs, 396640).unwrap();
+	assert_eq!(app, SteamApp {
+		app_id: 396640,
+		name: "TowerClimb".to_string(),
+		library_path: library.clone(),
+		install_path: library.join("steamapps/common/TowerClimb"),
+		steam_path: steam_path.clone(),
+	});
+	assert_eq!(app.compat_data_path(), None);
+	std::fs::create_dir_all(library.join("steamapps/compatdata/396640/pfx")).unwrap();
+	assert_eq!(app.compat_data_path(), Some(library.join("steamapps/compatdata/396640")));
+
+	// Proton itself may be installed in another library
+	write(&steam_path.join("steamapps/common/Proton 9.0/proton"), "");
+	let launcher = GameLauncher::detect_proton(&steam_dirs, 396640).unwrap();
+	assert_eq!(launcher.wine_prefix(), Some(library.join("steamapps/compatdata/396640/pfx")));
+	assert!(matches!(launcher, GameLauncher::Proton { proton_path, .. } if proton_path == steam_path.join("steamapps/common/Proton 9.0/proton")));
+
+	// older format
+	write(&steam_path.join("steamapps/libraryfolders.vdf"), &format!(r#""LibraryFolders" {{ "TimeNextStatsReport" "1" "ContentStatsID" "2" "1" "{}" }}"#, old_library.display()));
+	assert_eq!(steam_library_folders(&steam_path), [steam_path.clone(), old_library]);
+	assert_eq!(steam_library_folders(&dir.path().join("missing")), [dir.path().join("missing")]);
+}
@@ -0,0 +1,87 @@
+mod common;
+use common::*;
+use towermod_shared::app::state::{app_state::reducer, AppAction, DataAction};
+use towermod_shared::cstc_editing::{CstcData, EdObjectInstance, VariableType, VariableValue};
+use towermod_shared::tiled::{self, TiledMap};
+
+fn project() -> CstcData {
+	let mut player = object_type(1, 1);
+	player.name = "Player".into();
+	player.private_variables = [("hp".to_string(), VariableType::Number), ("tag".to_string(), VariableType::String)].into_iter().collect();
+	let mut wall = object_type(2, 2);
+	wall.name = "Wall".into();
+
+	let mut hero = EdObjectInstance { x: 100, y: 80, width: 32, height: 48, angle: 30.0, filter: -1, ..sprite(1, 1, 10) };
+	hero.private_variables = [("hp".to_string(), VariableValue::Number(3.0)), ("tag".to_string(), VariableValue::String("a \"quoted\" <tag>".into()))].into();
+	let mut hotspot = image(100, 16, 24);
+	(hotspot.hotspot_x, hotspot.hotspot_y) = (8, 24);
+	CstcData {
+		editor_plugins: [(1, plugin("Sprite")), (2, plugin("Tiled Background"))].into(),
+		object_types: [(1, player), (2, wall)].into_iter().collect(),
+		animations: [(10, animation(10, "", &[], vec![animation(11, "Default", &[100], vec![])]))].into_iter().collect(),
+		image_block: vec![hotspot],
+		layouts: vec![
+			layout("Level 1", vec![
+				layer(1, vec![hero, EdObjectInstance { filter: -1, ..instance(2, 2, 0, 400) }]),
+				layer(2, vec![EdObjectInstance { filter: -1, ..instance(3, 2, 320, 16) }]),
+			]),
+		],
+		..Default::default()
+	}
+}
+
+fn import(data: &CstcData, map: &TiledMap) -> (CstcData, tiled::TiledImport) {
+	let changes = tiled::import_map(data, &data.layouts[0], map, 4);
+	let report = changes.report.clone();
+	let state = reducer(state(data.clone()), AppAction::Data(DataAction::ImportTiledMap(changes)));
+	(state.data, report)
+}
+
+fn summary(data: &CstcData) -> Vec<(i32, Vec<(i32, i32, i32, i32, i32, i32)>)> {
+	data.layouts[0].layers.iter().map(|l| {
+		(l.id, l.objects.iter().map(|o| (o.id, o.object_type_id, o.x, o.y, o.width, o.height)).collect())
+	}).collect()
+}
+
+#[test]
+fn tiled_round_trip() {
+	let data = project();
+	let (map, image_ids) = tiled::export_layout(&data, &data.layouts[0]);
+	assert_eq!(image_ids, [100]);
+	// tile objects are anchored at the bottom-left of their image
+	let hero = &map.layers[0].objects[0];
+	assert!(hero.gid.is_some());
+	assert!((hero.x - 100.0).abs() > 1.0);
+
+	for map in [TiledMap::from_tmx(&map.to_tmx()).unwrap(), TiledMap::from_json(&map.to_json().unwrap()).unwrap()] {
+		let (imported, report) = import(&data, &map);
+		assert_eq!(report.updated, [1, 2, 3]);
+		assert!(report.created.is_empty() && report.unknown_layers.is_empty() && report.unknown_object_types.is_empty());
+		assert_eq!(summary(&imported), summary(&data));
+		let hero = &imported.layouts[0].layers[0].objects[0];
+		assert_eq!(hero.angle, 30.0);
+		assert!(matches!(hero.private_variables["hp"], VariableValue::Number(n) if n == 3.0));
+		assert!(matches!(&hero.private_variables["tag"], VariableValue::String(s) if s == "a \"quoted\" <tag>"));
+	}
+}
+
+#[test]
+fn tiled_import_moves_and_creates() {
+	let data = project();
+	let (mut map, _) = tiled::export_layout(&data, &data.layouts[0]);
+	// move the wall from layer 1 to layer 2 in Tiled, and add a new wall to layer 1
+	let wall = map.layers[0].objects.remove(1);
+	map.layers[1].objects.push(wall.clone());
+	map.layers[0].objects.push(tiled::TiledObject { id: 99, x: 64.0, y: 64.0, ..wall.clone() });
+	map.layers[0].objects.push(tiled::TiledObject { id: 100, object_type: "Missing".into(), ..wall });
+	let map = TiledMap::from_tmx(&map.to_tmx()).unwrap();
+
+	let (imported, report) = import(&data, &map);
+	assert_eq!(report.updated, [1, 3, 2]);
+	assert_eq!(report.created, [4]);
+	assert_eq!(report.unknown_object_types, ["Missing"]);
+	let ids = imported.layouts[0].layers.iter().map(|l| l.objects.iter().map(|o| o.id).collect::<Vec<_>>()).collect::<Vec<_>>();
+	assert_eq!(ids, [vec![1, 4], vec![3, 2]]);
+	let new_wall = &imported.layouts[0].layers[0].objects[1];
+	assert_eq!((new_wall.object_type_id, new_wall.x, new_wall.y), (2, 64, 64));
+}
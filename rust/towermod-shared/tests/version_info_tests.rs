@@ -0,0 +1,105 @@
+use towermod_shared::VersionInfo;
+
+enum Value<'a> {
+	None,
+	Text(&'a str),
+	Binary(&'a [u8]),
+}
+
+fn pad(bin: &mut Vec<u8>) {
+	bin.resize(bin.len().next_multiple_of(4), 0);
+}
+
+/// Version information block, with text value lengths given in UTF-16 code units (or bytes, if `length_in_bytes`)
+fn block(key: &str, value: Value, children: &[Vec<u8>], length_in_bytes: bool) -> Vec<u8> {
+	let mut bin = vec![0; 6];
+	for unit in key.encode_utf16().chain([0]) {
+		bin.extend(unit.to_le_bytes());
+	}
+	pad(&mut bin);
+	let (value_length, is_text) = match value {
+		Value::None => (0, 1),
+		Value::Text(text) => {
+			let units = text.encode_utf16().chain([0]).collect::<Vec<_>>();
+			for unit in &units {
+				bin.extend(unit.to_le_bytes());
+			}
+			(if length_in_bytes { units.len() * 2 } else { units.len() }, 1)
+		},
+		Value::Binary(data) => {
+			bin.extend(data);
+			(data.len(), 0)
+		},
+	};
+	for child in children {
+		pad(&mut bin);
+		bin.extend(child);
+	}
+	let length = bin.len() as u16;
+	bin[0..2].copy_from_slice(&length.to_le_bytes());
+	bin[2..4].copy_from_slice(&(value_length as u16).to_le_bytes());
+	bin[4..6].copy_from_slice(&(is_text as u16).to_le_bytes());
+	bin
+}
+
+fn string_table(lang_code_page: &str, strings: &[(&str, &str)], length_in_bytes: bool) -> Vec<u8> {
+	let strings = strings.iter().map(|(k, v)| block(k, Value::Text(v), &[], length_in_bytes)).collect::<Vec<_>>();
+	block(lang_code_page, Value::None, &strings, false)
+}
+
+fn fixed_file_info() -> Vec<u8> {
+	let fields: [u32; 13] = [0xfeef04bd, 0x10000, 0x10002, 0x30004, 0x10002, 0, 0x3f, 0, 0x40004, 2, 0, 0, 0];
+	fields.iter().flat_map(|f| f.to_le_bytes()).collect()
+}
+
+fn version_info(tables: &[Vec<u8>], translations: &[(u16, u16)]) -> Vec<u8> {
+	let translation = translations.iter().flat_map(|(l, c)| [l.to_le_bytes(), c.to_le_bytes()].concat()).collect::<Vec<_>>();
+	let children = [
+		block("StringFileInfo", Value::None, tables, false),
+		block("VarFileInfo", Value::None, &[block("Translation", Value::Binary(&translation), &[], false)], false),
+	];
+	block("VS_VERSION_INFO", Value::Binary(&fixed_file_info()), &children, false)
+}
+
+#[test]
+fn version_info_strings() {
+	let german = string_table("040704b0", &[("OriginalFilename", "Gras.csx"), ("ProductName", "Gras")], false);
+	let english = string_table("040904b0", &[("OriginalFilename", "Grass.csx"), ("FileVersion", "1, 2, 3, 4"), ("ProductName", "")], false);
+	let info = VersionInfo::from_bin(&version_info(&[german.clone(), english.clone()], &[(0x0409, 1200)])).unwrap();
+	assert_eq!(info.string_tables.len(), 2);
+	assert_eq!((info.string_tables[0].language, info.string_tables[0].code_page), (0x0407, 1200));
+	assert_eq!(info.translations, [(0x0409, 1200)]);
+	assert_eq!(info.original_filename(), Some("Grass.csx"));
+	assert_eq!(info.file_version(), Some("1, 2, 3, 4"));
+	// empty strings fall back to other tables
+	assert_eq!(info.product_name(), Some("Gras"));
+	assert_eq!(info.company_name(), None);
+
+	let fixed = info.fixed.unwrap();
+	assert_eq!(fixed.file_version, [1, 2, 3, 4]);
+	assert_eq!(fixed.product_version, [1, 2, 0, 0]);
+	assert_eq!(fixed.file_type, 2);
+
+	// without a matching translation, US English is preferred
+	let info = VersionInfo::from_bin(&version_info(&[german.clone(), english], &[])).unwrap();
+	assert_eq!(info.original_filename(), Some("Grass.csx"));
+	let info = VersionInfo::from_bin(&version_info(&[german], &[])).unwrap();
+	assert_eq!(info.original_filename(), Some("Gras.csx"));
+}
+
+#[test]
+fn text_lengths_in_bytes() {
+	let table = string_table("041104b0", &[("OriginalFilename", "Plugin.csx"), ("InternalName", "Plugin")], true);
+	let info = VersionInfo::from_bin(&version_info(&[table], &[(0x0411, 1200)])).unwrap();
+	assert_eq!(info.original_filename(), Some("Plugin.csx"));
+	assert_eq!(info.internal_name(), Some("Plugin"));
+}
+
+#[test]
+fn invalid_version_info() {
+	assert!(VersionInfo::from_bin(&[]).is_err());
+	assert!(VersionInfo::from_bin(&block("StringFileInfo", Value::None, &[], false)).is_err());
+	let mut truncated = version_info(&[], &[]);
+	truncated.truncate(40);
+	assert!(VersionInfo::from_bin(&truncated).is_err());
+}
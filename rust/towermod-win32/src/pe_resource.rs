@@ -9,14 +9,18 @@ use std::path::Path;
 use log::warn;
 use tokio::task::JoinSet;
 use tracing::instrument;
-use windows::Win32::System::LibraryLoader::{BeginUpdateResourceW, EndUpdateResourceW, EnumResourceNamesW, EnumResourceTypesExW, FindResourceExW, LoadLibraryExW, LoadResource, LockResource, SizeofResource, UpdateResourceW, LOAD_LIBRARY_AS_DATAFILE};
+use windows::Win32::System::LibraryLoader::{BeginUpdateResourceW, EndUpdateResourceW, EnumResourceLanguagesW, EnumResourceNamesW, EnumResourceTypesExW, FindResourceExW, LoadLibraryExW, LoadResource, LockResource, SizeofResource, UpdateResourceW, LOAD_LIBRARY_AS_DATAFILE};
 use anyhow::Result;
 use windows::Win32::Foundation::{HANDLE, HMODULE, BOOL, FreeLibrary};
 use windows::core::{PCWSTR, HSTRING};
 
 pub unsafe fn read_hmodule_resource(hmodule: HMODULE, res_type: &ResId, res_name: &ResId) -> Result<Vec<u8>> {
+	unsafe { read_hmodule_resource_language(hmodule, res_type, res_name, 0x00) }
+}
+
+pub unsafe fn read_hmodule_resource_language(hmodule: HMODULE, res_type: &ResId, res_name: &ResId, language: u16) -> Result<Vec<u8>> {
 	unsafe {
-		let hres = FindResourceExW(hmodule, res_type, res_name, 0x00);
+		let hres = FindResourceExW(hmodule, res_type, res_name, language);
 		let hresdata = LoadResource(hmodule, hres)?;
 		let pdata = LockResource(hresdata);
 		let dw_size = SizeofResource(hmodule, hres);
@@ -26,26 +30,54 @@ pub unsafe fn read_hmodule_resource(hmodule: HMODULE, res_type: &ResId, res_name
 }
 
 pub fn read_pe_file_resource(exe_path: &Path, res_type: &ResId, res_name: &ResId) -> Result<Vec<u8>> {
+	read_pe_file_resource_language(exe_path, res_type, res_name, 0x00)
+}
+
+pub fn read_pe_file_resource_language(exe_path: &Path, res_type: &ResId, res_name: &ResId, language: u16) -> Result<Vec<u8>> {
 	unsafe {
 		let hmodule = LoadLibraryExW(&HSTRING::from(exe_path), HANDLE::default(), LOAD_LIBRARY_AS_DATAFILE)?;
 		scopeguard::defer! { if let Err(e) = FreeLibrary(hmodule) { log::error!("{}", e) }; }
-		read_hmodule_resource(hmodule, res_type, res_name)
+		read_hmodule_resource_language(hmodule, res_type, res_name, language)
 	}
 }
 
 #[instrument(skip(buffer))]
 pub fn replace_pe_file_resource(exe_path: &Path, res_type: &ResId, res_name: &ResId, buffer: &[u8]) -> Result<()> {
+	replace_pe_file_resource_language(exe_path, res_type, res_name, 0x00, buffer)
+}
+
+#[instrument(skip(buffer))]
+pub fn replace_pe_file_resource_language(exe_path: &Path, res_type: &ResId, res_name: &ResId, language: u16, buffer: &[u8]) -> Result<()> {
 	unsafe {
 		let buffer_ptr = buffer as *const _ as *const c_void;
 		let hupdate = BeginUpdateResourceW(&HSTRING::from(exe_path.as_os_str()), BOOL(0))?;
 		// Integer resource ID names (u16 only) are passed by casting them to a PCWSTR pointer
-		UpdateResourceW(hupdate, res_type, res_name, 0x00, Some(buffer_ptr), buffer.len() as u32)?;
+		UpdateResourceW(hupdate, res_type, res_name, language, Some(buffer_ptr), buffer.len() as u32)?;
 		EndUpdateResourceW(hupdate, BOOL(0))?;
 		log::info!("Updated {:?} for {}", res_type, exe_path.to_string_lossy());
 	}
 	Ok(())
 }
 
+/// Replace several resources (type, name, language, data) with a single resource update
+#[instrument(skip(resources))]
+pub fn replace_pe_file_resources(exe_path: &Path, resources: &[(ResId, ResId, u16, &[u8])]) -> Result<()> {
+	unsafe {
+		let hupdate = BeginUpdateResourceW(&HSTRING::from(exe_path.as_os_str()), BOOL(0))?;
+		for (res_type, res_name, language, buffer) in resources {
+			let buffer_ptr = buffer.as_ptr() as *const c_void;
+			if let Err(e) = UpdateResourceW(hupdate, res_type, res_name, *language, Some(buffer_ptr), buffer.len() as u32) {
+				// discard the staged updates
+				EndUpdateResourceW(hupdate, BOOL(1))?;
+				return Err(e.into());
+			}
+		}
+		EndUpdateResourceW(hupdate, BOOL(0))?;
+		log::info!("Updated {} resources for {}", resources.len(), exe_path.to_string_lossy());
+	}
+	Ok(())
+}
+
 /// Throws an error if the resource does not already exist
 #[instrument]
 pub fn delete_pe_file_resource(exe_path: &Path, res_type: &ResId, res_name: &ResId) -> Result<()> {
@@ -89,6 +121,39 @@ pub unsafe fn list_hmodule_resource_types(hmodule: HMODULE) -> Result<Vec<ResId>
 	}
 }
 
+thread_local!(static RESOURCE_LANGUAGES_ACC: RefCell<Vec<u16>> = RefCell::new(vec![]));
+pub unsafe fn list_hmodule_resource_languages(hmodule: HMODULE, res_type: &ResId, res_name: &ResId) -> Result<Vec<u16>> {
+	RESOURCE_LANGUAGES_ACC.with_borrow_mut(|v| *v = vec![]);
+	extern "system" fn cb(_hmodule: HMODULE, _res_type: PCWSTR, _res_name: PCWSTR, language: u16, _lparam: isize) -> BOOL {
+		RESOURCE_LANGUAGES_ACC.with_borrow_mut(|v| v.push(language));
+		BOOL(1)
+	}
+	unsafe {
+		EnumResourceLanguagesW(hmodule, res_type, res_name, Some(cb), 0);
+		Ok(RESOURCE_LANGUAGES_ACC.with_borrow_mut(|v| v.clone()))
+	}
+}
+
+/// Every resource in an executable as (type, name, language, size)
+#[instrument]
+pub fn list_pe_file_resources(exe_path: &Path) -> Result<Vec<(ResId, ResId, u16, usize)>> {
+	unsafe {
+		let hmodule = LoadLibraryExW(&HSTRING::from(exe_path), HANDLE::default(), LOAD_LIBRARY_AS_DATAFILE)?;
+		scopeguard::defer! { if let Err(e) = FreeLibrary(hmodule) { log::error!("{}", e) }; }
+		let mut resources = Vec::new();
+		for res_type in list_hmodule_resource_types(hmodule)? {
+			for res_name in list_hmodule_resource_names(hmodule, &res_type)? {
+				for language in list_hmodule_resource_languages(hmodule, &res_type, &res_name)? {
+					let hres = FindResourceExW(hmodule, &res_type, &res_name, language);
+					let size = SizeofResource(hmodule, hres) as usize;
+					resources.push((res_type.clone(), res_name.clone(), language, size));
+				}
+			}
+		}
+		Ok(resources)
+	}
+}
+
 /// Type for representing PE resource IDs using strings or integers (in lieu of the MAKEINTRESOURCE() win32 macro)
 #[derive(Debug, Clone)]
 pub enum ResId {
@@ -1,7 +1,7 @@
 use windows::core::PCWSTR;
 use windows::Win32::System::Registry::{
-	RegCreateKeyExW, RegSetValueExW, HKEY_CURRENT_USER as HKCU, HKEY, REG_OPTION_NON_VOLATILE,
-	REG_SZ, KEY_WRITE
+	RegCreateKeyExW, RegGetValueW, RegSetValueExW, HKEY_CURRENT_USER as HKCU, HKEY, REG_OPTION_NON_VOLATILE,
+	REG_SZ, KEY_WRITE, RRF_RT_REG_SZ
 };
 use anyhow::Result;
 
@@ -53,3 +53,16 @@ fn reg_sz(base: HKEY, path: &str, key: &str, value: &str) -> Result<()> {
 	}
 	Ok(())
 }
+
+pub fn read_reg_sz(base: HKEY, path: &str, key: &str) -> Result<String> {
+	unsafe {
+		let path = str_to_pwstr(path);
+		let key = str_to_pwstr(key);
+		let mut size = 0u32;
+		RegGetValueW(base, PCWSTR(&path[0]), PCWSTR(&key[0]), RRF_RT_REG_SZ, None, None, Some(&mut size))?;
+		let mut buffer = vec![0u16; size as usize / 2];
+		RegGetValueW(base, PCWSTR(&path[0]), PCWSTR(&key[0]), RRF_RT_REG_SZ, None, Some(buffer.as_mut_ptr() as *mut _), Some(&mut size))?;
+		let len = buffer.iter().position(|c| *c == 0).unwrap_or(buffer.len());
+		Ok(String::from_utf16(&buffer[..len])?)
+	}
+}
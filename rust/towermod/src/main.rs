@@ -21,13 +21,12 @@ fn main() {
 
 	let _ = tracing_log::LogTracer::init();
 
-	// TODO: implement pipe communication on linux
-	#[cfg(windows)]
-	if towermod_win32::pipe::pipe_exists() {
+	// Forward command-line arguments to the running instance, if any
+	if towermod_shared::ipc::instance_exists() {
 		let mut command_line = false;
 		for arg in std::env::args().skip(1) {
 			command_line = true;
-			towermod_util::log_on_error(towermod_win32::pipe::write_to_pipe(arg.as_bytes()));
+			towermod_util::log_on_error(towermod_shared::ipc::send_to_instance(&arg));
 		}
 		if command_line {
 			std::process::exit(0);
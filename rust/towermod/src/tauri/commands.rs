@@ -48,6 +48,12 @@ pub async fn get_image(
 	}
 }
 
+#[command]
+pub async fn render_layout_png(layout_name: String, options: Option<towermod_shared::layout_renderer::RenderOptions>) -> Result<tauri::ipc::Response> {
+	let data = thunks::render_layout_png(layout_name, options.unwrap_or_default()).await?;
+	Ok(tauri::ipc::Response::new(data))
+}
+
 #[command]
 pub async fn get_file(_window: tauri::Window, request: tauri::ipc::Request<'_>) -> Result<tauri::ipc::Response> {
 	if let tauri::ipc::InvokeBody::Raw(payload) = request.body() {
@@ -118,18 +124,22 @@ pub async fn init(app: AppHandle) -> Result<()> {
 			*initialized = true;
 		}
 
+		// Notify the frontend when a playtest finishes or crashes
+		let app_handle = app.clone();
+		towermod_shared::on_game_exit(move |exit| {
+			log_on_error(app_handle.emit("towermod/game-exit", exit));
+		});
+
 		// Install mods passed from the command-line
 		let args: Vec<String> = std::env::args().skip(1).collect();
 		for arg in args {
 			log_on_error(app.emit("towermod/request-install-mod", arg));
 		}
 
-		// Attempt to listen on pipe
-		// TODO: implement on linux
-		#[cfg(windows)]
+		// Attempt to listen for messages from other instances
 		std::thread::spawn(move || {
 			let app_handle = app;
-			log_on_error(towermod_win32::pipe::listen_pipe(move |msg| {
+			log_on_error(towermod_shared::ipc::listen(move |msg| {
 				log_on_error(app_handle.emit("towermod/request-install-mod", msg));
 			}));
 		});
@@ -144,6 +154,11 @@ pub async fn get_game() -> Option<towermod_shared::Game> { selectors::get_game()
 #[command]
 pub async fn get_config() -> towermod_shared::app::state::TowermodConfig { selectors::get_config().await }
 #[command]
+pub async fn set_config(config: towermod_shared::app::state::TowermodConfig) -> Result<()> {
+	thunks::set_config(config).await;
+	thunks::save_config().await
+}
+#[command]
 pub async fn get_project() -> Option<towermod_shared::Project> {
 	selectors::get_project().await
 }
@@ -199,12 +214,12 @@ pub async fn load_project(manifest_path: PathBuf) -> Result<()> {
 }
 
 #[command]
-pub async fn save_new_project(dir_path: PathBuf, author: String, name: String, display_name: String) -> Result<()> {
+pub async fn save_new_project(dir_path: PathBuf, author: String, name: String, display_name: String) -> Result<Vec<cstc_editing::LayoutImageIdsChange>> {
 	thunks::save_new_project(dir_path, author, name, display_name).await
 }
 
 #[command]
-pub async fn save_project(dir_path: PathBuf) -> Result<()> {
+pub async fn save_project(dir_path: PathBuf) -> Result<Vec<cstc_editing::LayoutImageIdsChange>> {
 	thunks::save_project(dir_path).await
 }
 
@@ -224,7 +239,7 @@ pub async fn clear_game_cache() -> Result<()> {
 }
 
 #[command]
-pub async fn export_mod(mod_type: ModType) -> Result<()> {
+pub async fn export_mod(mod_type: ModType) -> Result<Vec<cstc_editing::LayoutImageIdsChange>> {
 	thunks::export_mod(mod_type).await
 }
 
@@ -253,6 +268,31 @@ pub async fn play_project(debug: bool) -> Result<u32> {
 	thunks::play_project(debug).await
 }
 
+#[command]
+pub async fn add_plugin(file_name: String) -> Result<i32> {
+	thunks::add_plugin(file_name).await
+}
+
+#[command]
+pub async fn get_runtime_plugin_names() -> Result<Vec<String>> {
+	thunks::get_runtime_plugin_names().await
+}
+
+#[command]
+pub async fn list_game_resources() -> Result<Vec<towermod_shared::PeResourceInfo>> {
+	thunks::list_game_resources().await
+}
+
+#[command]
+pub async fn get_game_version_info() -> Result<towermod_shared::VersionInfo> {
+	thunks::get_game_version_info().await
+}
+
+#[command]
+pub async fn extract_game_resource(res_type: towermod_shared::ResourceId, name: towermod_shared::ResourceId, language: Option<u16>, dest_path: PathBuf) -> Result<()> {
+	thunks::extract_game_resource(res_type, name, language, dest_path).await
+}
+
 #[command]
 pub async fn play_vanilla() -> Result<u32> {
 	thunks::play_vanilla().await
@@ -283,6 +323,36 @@ pub async fn save_config() -> Result<()> {
 	thunks::save_config().await
 }
 
+#[command]
+pub async fn uninstall_desktop_integration() -> Result<()> {
+	thunks::uninstall_desktop_integration().await
+}
+
+#[command]
+pub async fn set_launcher_config(launcher: towermod_shared::LauncherConfig) -> Result<()> {
+	thunks::set_launcher_config(launcher).await
+}
+
+#[command]
+pub async fn detect_launcher() -> Result<towermod_shared::GameLauncher> {
+	thunks::detect_launcher().await
+}
+
+#[command]
+pub async fn select_game_profile(game_path: PathBuf) -> Result<()> { thunks::select_game_profile(game_path).await }
+
+#[command]
+pub async fn remove_game_profile(game_path: PathBuf) -> Result<()> { thunks::remove_game_profile(game_path).await }
+
+#[command]
+pub async fn rename_game_profile(game_path: PathBuf, name: String) -> Result<()> { thunks::rename_game_profile(game_path, name).await }
+
+#[command]
+pub async fn get_recent_projects() -> Result<Vec<Project>> { thunks::get_recent_projects().await }
+
+#[command]
+pub async fn get_known_game() -> Result<Option<towermod_shared::KnownGame>> { thunks::get_known_game().await }
+
 #[command]
 pub async fn dump_images() -> Result<()> {
 	thunks::dump_images().await
@@ -297,7 +367,7 @@ pub async fn mod_cache_exists(mod_info: ModInfo) -> bool {
 pub async fn clear_mod_cache(mod_info: ModInfo) -> Result<()> { thunks::clear_mod_cache(mod_info).await }
 
 #[command]
-pub async fn wait_until_process_exits(pid: u32) -> Result<()> {
+pub async fn wait_until_process_exits(pid: u32) -> Result<Option<towermod_shared::GameExit>> {
 	thunks::wait_until_process_exits(pid).await
 }
 
@@ -343,6 +413,15 @@ pub async fn wait_until_process_exits(pid: u32) -> Result<()> {
 #[command] pub async fn get_object_type_instances(object_type_id: i32) -> Vec<i32> {
 	select(selectors::select_object_type_instance_ids(object_type_id)).await
 }
+#[command] pub async fn get_object_instances_in_rect(layout_layer_id: i32, x: f32, y: f32, width: f32, height: f32) -> Vec<i32> {
+	select(selectors::select_object_instances_in_rect(layout_layer_id, x, y, width, height)).await
+}
+#[command] pub async fn get_object_instances_at_point(layout_layer_id: i32, x: f32, y: f32) -> Vec<i32> {
+	select(selectors::select_object_instances_at_point(layout_layer_id, x, y)).await
+}
+#[command] pub async fn get_nearest_object_instance(layout_layer_id: i32, x: f32, y: f32) -> Option<i32> {
+	select(selectors::select_nearest_object_instance(layout_layer_id, x, y)).await
+}
 #[command] pub async fn get_object_instance(id: i32) -> Option<serde_json::Value> {
 	select(move |s| {
 		selectors::select_object_instance(id)(s).map(|o| enhance_object_instance(s, o))
@@ -357,14 +436,15 @@ pub async fn wait_until_process_exits(pid: u32) -> Result<()> {
 #[command] pub async fn get_object_instance_image_id(id: i32) -> Option<i32> {
 	select(selectors::select_object_instance_image_id(id)).await
 }
-#[command] pub async fn create_object_instance(object_type_id: i32, layout_layer_id: i32) -> i32 {
-	let id = select(selectors::select_new_object_instance_id()).await;
-	dispatch(DataAction::CreateObjectInstance { id, object_type_id, layout_layer_id }).await;
-	id
+#[command] pub async fn create_object_instance(object_type_id: i32, layout_layer_id: i32) -> Result<i32> {
+	thunks::create_object_instance(object_type_id, layout_layer_id).await
 }
 #[command] pub async fn delete_object_instance(id: i32) {
 	dispatch(DataAction::DeleteObjectInstance(id)).await
 }
+#[command] pub async fn move_object_instances(ids: Vec<i32>, layout_layer_id: i32) {
+	dispatch(DataAction::MoveObjectInstances { ids, layout_layer_id }).await
+}
 
 #[command] pub async fn get_layouts() -> Vec<String> {
 	select(selectors::select_layouts()).await
@@ -394,6 +474,51 @@ pub async fn wait_until_process_exits(pid: u32) -> Result<()> {
 #[command] pub async fn update_layout_layer(layer: cstc_editing::EdLayoutLayer) {
 	dispatch(DataAction::UpdateLayoutLayer(layer)).await
 }
+#[command] pub async fn create_layout_layer(layout_name: String, name: Option<String>, index: Option<usize>) -> i32 {
+	let id = select(selectors::select_new_layout_layer_id()).await;
+	let name = name.unwrap_or_else(|| format!("Layer {id}"));
+	dispatch(DataAction::CreateLayoutLayer { id, layout_name, name, index }).await;
+	id
+}
+#[command] pub async fn delete_layout_layer(id: i32) -> Result<()> {
+	thunks::delete_layout_layer(id).await
+}
+#[command] pub async fn move_layout_layer(id: i32, index: usize) {
+	dispatch(DataAction::MoveLayoutLayer { id, index }).await
+}
+
+/// Returns the clipboard contents as JSON
+#[command] pub async fn copy_object_instances(ids: Vec<i32>) -> Result<String> {
+	Ok(serde_json::to_string(&thunks::copy_object_instances(ids).await?)?)
+}
+#[command] pub async fn paste_object_instances(clipboard: String, layout_layer_id: i32, offset_x: i32, offset_y: i32) -> Result<cstc_editing::PasteResult> {
+	let clipboard: cstc_editing::InstanceClipboard = serde_json::from_str(&clipboard).context("Invalid clipboard data")?;
+	thunks::paste_object_instances(clipboard, layout_layer_id, offset_x, offset_y).await
+}
+
+#[command] pub async fn update_layout_image_ids() -> Vec<cstc_editing::LayoutImageIdsChange> {
+	thunks::update_layout_image_ids().await
+}
+#[command] pub async fn get_unused_layout_image_ids() -> Vec<cstc_editing::LayoutImageIdsChange> {
+	thunks::get_unused_layout_image_ids().await
+}
+#[command] pub async fn remove_layout_image_ids(changes: Vec<cstc_editing::LayoutImageIdsChange>) {
+	thunks::remove_layout_image_ids(changes).await
+}
+
+#[command] pub async fn export_layout_tiled(layout_name: String, path: PathBuf) -> Result<()> {
+	thunks::export_layout_tiled(layout_name, path).await
+}
+#[command] pub async fn import_layout_tiled(layout_name: String, path: PathBuf) -> Result<towermod_shared::tiled::TiledImport> {
+	thunks::import_layout_tiled(layout_name, path).await
+}
+
+#[command] pub async fn export_prefab(object_type_ids: Vec<i32>, path: PathBuf) -> Result<()> {
+	thunks::export_prefab(object_type_ids, path).await
+}
+#[command] pub async fn import_prefab(path: PathBuf, layout_layer_id: i32) -> Result<cstc_editing::PrefabImport> {
+	thunks::import_prefab(path, layout_layer_id).await
+}
 
 #[command] pub async fn get_root_animations() -> Vec<i32> {
 	select(selectors::select_root_animations()).await
@@ -482,6 +607,21 @@ binary_command! {
 #[command] pub async fn update_behavior(behavior: towermod_cstc::Behavior) {
 	dispatch(DataAction::UpdateBehavior(behavior)).await
 }
+#[command] pub async fn create_behavior(object_type_id: i32, plugin_id: i32, name: String) -> Result<i32> {
+	thunks::create_behavior(object_type_id, plugin_id, name).await
+}
+#[command] pub async fn delete_behavior(object_type_id: i32, mov_index: i32) -> Result<()> {
+	thunks::delete_behavior(object_type_id, mov_index).await
+}
+#[command] pub async fn move_behavior(object_type_id: i32, mov_index: i32, index: usize) {
+	dispatch(DataAction::MoveBehavior { object_type_id, mov_index, index }).await
+}
+#[command] pub async fn get_behavior_data(object_type_id: i32, mov_index: i32) -> Option<towermod_cstc::BehaviorData> {
+	select(selectors::select_behavior_data(object_type_id, mov_index)).await
+}
+#[command] pub async fn update_behavior_data(object_type_id: i32, mov_index: i32, data: towermod_cstc::BehaviorData) -> Result<()> {
+	thunks::update_behavior_data(object_type_id, mov_index, data).await
+}
 
 #[command] pub async fn get_containers() -> Vec<i32> {
 	select(selectors::select_containers()).await
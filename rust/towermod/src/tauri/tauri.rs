@@ -44,12 +44,14 @@ pub fn run() {
 				commands::delete_file,
 				commands::get_version,
 				commands::get_image,
+				commands::render_layout_png,
 				commands::get_file,
 				commands::get_towermod_dir_path,
 				commands::mod_cache_dir_path,
 				commands::mod_runtime_dir_path,
 				commands::get_game,
 				commands::get_config,
+				commands::set_config,
 				commands::get_project,
 				commands::is_data_loaded,
 				commands::get_editor_plugin,
@@ -73,6 +75,11 @@ pub fn run() {
 				commands::load_manifest,
 				commands::play_mod,
 				commands::play_project,
+				commands::add_plugin,
+				commands::get_runtime_plugin_names,
+				commands::list_game_resources,
+				commands::extract_game_resource,
+				commands::get_game_version_info,
 				commands::play_vanilla,
 				commands::install_mod,
 				commands::init,
@@ -80,6 +87,14 @@ pub fn run() {
 				commands::get_installed_mods,
 				commands::load_config,
 				commands::save_config,
+				commands::set_launcher_config,
+				commands::uninstall_desktop_integration,
+				commands::detect_launcher,
+				commands::select_game_profile,
+				commands::remove_game_profile,
+				commands::rename_game_profile,
+				commands::get_recent_projects,
+				commands::get_known_game,
 				commands::dump_images,
 				commands::mod_cache_exists,
 				commands::clear_mod_cache,
@@ -96,6 +111,9 @@ pub fn run() {
 				commands::object_type_delete_variable,
 
 				commands::get_object_instances,
+				commands::get_object_instances_in_rect,
+				commands::get_object_instances_at_point,
+				commands::get_nearest_object_instance,
 				commands::get_object_type_instances,
 				commands::get_object_instance,
 				commands::search_object_instances,
@@ -103,6 +121,7 @@ pub fn run() {
 				commands::get_object_instance_image_id,
 				commands::delete_object_instance,
 				commands::create_object_instance,
+				commands::move_object_instances,
 
 				commands::get_layouts,
 				commands::get_layout,
@@ -112,6 +131,18 @@ pub fn run() {
 				commands::get_layout_layer,
 				commands::search_layout_layers,
 				commands::update_layout_layer,
+				commands::create_layout_layer,
+				commands::delete_layout_layer,
+				commands::move_layout_layer,
+				commands::copy_object_instances,
+				commands::paste_object_instances,
+				commands::update_layout_image_ids,
+				commands::get_unused_layout_image_ids,
+				commands::remove_layout_image_ids,
+				commands::export_layout_tiled,
+				commands::import_layout_tiled,
+				commands::export_prefab,
+				commands::import_prefab,
 
 				commands::get_root_animations,
 				commands::get_animation_children,
@@ -125,6 +156,11 @@ pub fn run() {
 				commands::get_behaviors,
 				commands::get_behavior,
 				commands::update_behavior,
+				commands::create_behavior,
+				commands::delete_behavior,
+				commands::move_behavior,
+				commands::get_behavior_data,
+				commands::update_behavior_data,
 
 				commands::get_container,
 				commands::get_containers,
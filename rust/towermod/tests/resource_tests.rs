@@ -64,6 +64,10 @@ fn level_block() {
 				match deserialized_object_data {
 					ObjectData::Sprite(o_data) => { assert_eq!(object.data, o_data.to_bin()); },
 					ObjectData::Text(o_data) => { assert_eq!(object.data, o_data.to_bin()); },
+					ObjectData::TiledBackground(o_data) => { assert_eq!(object.data, o_data.to_bin()); },
+					ObjectData::Panel(o_data) => { assert_eq!(object.data, o_data.to_bin()); },
+					ObjectData::Gradient(o_data) => { assert_eq!(object.data, o_data.to_bin()); },
+					ObjectData::Canvas(o_data) => { assert_eq!(object.data, o_data.to_bin()); },
 					_ => {}
 				}
 			}
@@ -1,6 +1,6 @@
 import { binaryInvoke, enhanceModInfo, svgToDataUri } from "@/util"
 import { invoke } from "@tauri-apps/api/core"
-import { Game, ModInfo, ModType, Project, ProjectType, TowermodConfig } from '@towermod'
+import { Game, LayoutImageIdsChange, ModInfo, ModType, Project, ProjectType, TowermodConfig } from '@towermod'
 import { createMutation, createQuery, invalidate } from "./helpers"
 
 export const getFile = createQuery({
@@ -66,7 +66,7 @@ export const newProject = createMutation({
 })
 export const exportMod = createMutation({
 	mutationFn: async (modType: ModType) => {
-		await invoke('export_mod', { modType })
+		return await invoke<LayoutImageIdsChange[]>('export_mod', { modType })
 	},
 	onSuccess: () => invalidate('ModInfo', 'new'),
 })
@@ -169,13 +169,13 @@ export const editProjectInfo = createMutation({
 
 export const saveProject = createMutation({
 	mutationFn: async (dirPath: string) => {
-		return await invoke('save_project', { dirPath })
+		return await invoke<LayoutImageIdsChange[]>('save_project', { dirPath })
 	},
 	onSuccess: () => invalidate('Project', 'singleton'),
 })
 
 export const saveNewProject = createMutation({
-	mutationFn: async (args: { dirPath: string, author: string, name: string, displayName: string }) => await invoke('save_new_project', args),
+	mutationFn: async (args: { dirPath: string, author: string, name: string, displayName: string }) => await invoke<LayoutImageIdsChange[]>('save_new_project', args),
 	onSuccess: () => invalidate('Project', 'singleton'),
 })
 
@@ -2,7 +2,7 @@ import api from "@/api"
 import { openModal } from "@/app/Modal"
 import { ProjectDetailsFormData, ProjectDetailsModal } from "@/app/ProjectDetailsModal"
 import { toast } from "@/app/Toast"
-import { saveProject } from "@/appUtil"
+import { layoutImageIdsMessage, saveProject } from "@/appUtil"
 import { Button } from "@/components/Button"
 import { showError } from "@/components/Error"
 import FilePathEdit from "@/components/FilePathEdit"
@@ -138,8 +138,8 @@ export const Config = () => {
 		if (!project) { return }
 		const confirmed = await updateProjectDetails(project, "Export")
 		if (!confirmed) { return }
-		await spin(api.exportMod('BinaryPatch'))
-		toast("Project exported")
+		const changes = await spin(api.exportMod('BinaryPatch'))
+		toast("Project exported" + layoutImageIdsMessage(changes))
 		dispatch(actions.setCurrentTab('Mods'))
 	}
 
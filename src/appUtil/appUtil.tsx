@@ -3,6 +3,7 @@ import { spin } from "@/app/GlobalSpinner"
 import { openModal } from "@/app/Modal"
 import { ProjectDetailsFormData, ProjectDetailsModal } from "@/app/ProjectDetailsModal"
 import { toast } from "@/app/Toast"
+import { LayoutImageIdsChange } from "@towermod"
 import { dispatch, store } from "@/redux"
 import { activateWindow, useMountEffect, useObjectUrl } from "@/util"
 import { ApiEndpointQuery, QueryDefinition, defaultSerializeQueryArgs } from "@reduxjs/toolkit/query"
@@ -14,16 +15,23 @@ export async function saveProject() {
 	const project = await api.getProject()
 
 	if (project && project.dirPath) {
-		await spin(saveProject(project.dirPath))
-		toast("Project saved")
+		const changes = await spin(saveProject(project.dirPath))
+		toast("Project saved" + layoutImageIdsMessage(changes))
 	} else {
 		openModal(<ProjectDetailsModal confirmText="Save" newProject onConfirm={async (form) => {
-			await spin(saveNewProject(form))
-			toast("Project saved")
+			const changes = await spin(saveNewProject(form))
+			toast("Project saved" + layoutImageIdsMessage(changes))
 		}} />)
 	}
 }
 
+/** Describes the images that saving or exporting added to the layouts' preloaded images */
+export function layoutImageIdsMessage(changes: LayoutImageIdsChange[] | undefined): string {
+	if (!changes?.length) { return "" }
+	const count = changes.reduce((n, c) => n + c.added.length, 0)
+	return ` (added ${count} missing preloaded images to ${changes.map(c => c.layoutName).join(", ")})`
+}
+
 
 export async function installMods(files: string[]) {
 	activateWindow()
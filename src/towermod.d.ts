@@ -284,12 +284,16 @@ export interface ModInfo {
 	filePath?: string
 	cover?: Array<number>
 	icon?: Array<number>
+	/** File names of plugins added to the game's DLLBLOCK */
+	addedPlugins?: string[]
+	/** Resource replacements bundled in the mod */
+	resources?: ResourceReplacement[]
 	error?: string
 }
 
 export type ModType = 'FilesOnly' | 'Legacy' | 'BinaryPatch';
 
-export type ObjectInstanceData = TextObjectData | SpriteObjectData | number[]
+export type ObjectInstanceData = TextObjectData | SpriteObjectData | TiledBackgroundObjectData | PanelObjectData | GradientObjectData | CanvasObjectData | number[]
 
 export interface ObjectInstance<T extends ObjectInstanceData = ObjectInstanceData> {
 	id: int
@@ -319,9 +323,17 @@ export interface TextObjectData {
 	horizAlign: number;
 	vertAlign: number;
 	hideAtStart: boolean;
+	legacy?: TextObjectLegacyData | null;
 	_type: 'TextObjectData';
 }
 
+export interface TextObjectLegacyData {
+	header: number[];
+	flags: number;
+	privateVariables: number[];
+	trailing: number[];
+}
+
 export interface SpriteObjectData {
 	version: number;
 	collMode: number;
@@ -339,6 +351,32 @@ export interface SpriteObjectData {
 	_type: 'SpriteObjectData';
 }
 
+export interface TiledBackgroundObjectData {
+	version: number;
+	textureId: number;
+	trailing: number[];
+}
+
+export interface PanelObjectData {
+	version: number;
+	imageId: number;
+	trailing: number[];
+}
+
+export interface GradientObjectData {
+	version: number;
+	color1: number;
+	color2: number;
+	direction: number;
+	trailing: number[];
+}
+
+export interface CanvasObjectData {
+	version: number;
+	initialImageId: number;
+	trailing: number[];
+}
+
 export interface ObjectTrait {
 	name: string
 	objectTypeIds: Array<number>
@@ -358,6 +396,16 @@ export interface ObjectType {
 	_type: 'ObjectType'
 }
 
+export interface PeResourceInfo {
+	resType: ResourceId
+	/** Name of predefined resource types */
+	typeName?: string
+	name: ResourceId
+	language: int
+	/** Size in bytes */
+	size: int
+}
+
 export interface Param {
 	paramType: number
 	name: string
@@ -397,6 +445,12 @@ export interface Project {
 	version: string
 	projectType: ProjectType
 	description: string
+	/** Convert version 1 Text objects to version 2 when saving */
+	upgradeLegacyText?: boolean
+	/** File names of plugins from the runtime plugins directory that are added to the game's DLLBLOCK */
+	addedPlugins?: string[]
+	/** Resources of the game executable replaced with files from the project directory */
+	resources?: ResourceReplacement[]
 	towermodVersion: string
 	/** Date that the project was last saved/exported */
 	date: Date
@@ -406,6 +460,18 @@ export interface Project {
 
 export type ProjectType = 'Towermod' | 'FilesOnly' | 'Legacy'
 
+/** Integer or string ID of a PE resource type or name */
+export type ResourceId = int | string
+
+export interface ResourceReplacement {
+	type: ResourceId
+	name: ResourceId
+	/** Defaults to the language of the existing resource */
+	language?: int
+	/** Path relative to the project directory */
+	file: string
+}
+
 export type ResizeMode = 'Disabled' | 'ShowMore' | 'Stretch'
 
 export type SamplerMode = 'Point' | 'Linear'
@@ -487,8 +553,51 @@ export declare const enum TokenKind {
 	Color = 53
 }
 
+/** A release of a game, identified by its `fileHash` or `dataHash` */
+export interface KnownGame {
+	name: string,
+	/** e.g. `v1.0 (Steam)` */
+	version: string,
+	gameType: GameType,
+	fileHash?: string,
+	dataHash?: string,
+}
+
 export interface TowermodConfig {
-	filePath: string,
+	/** Format version of the config file */
+	version: int,
+	gamePath?: string,
+	/** Overrides the directory where cached data is stored */
+	cacheDir?: string,
+	/** Every game that has been selected, in the order they were added */
+	profiles: GameProfile[],
+}
+
+export interface GameProfile {
+	path: string,
+	/** Shown when switching games, defaults to the file name */
+	name: string,
+	gameType: GameType,
+	/** `Game.dataHash` of the executable when it was last selected */
+	dataHash?: string,
+	/** Overrides the AppData directory where the game stores its savedata and settings */
+	appdataDir?: string,
+	launcher: LauncherConfig,
+	/** Manifests of the projects opened for this game, most recent first */
+	recentProjects: string[],
+}
+
+export type GameLauncher =
+	| { type: 'native' }
+	| { type: 'wine', winePath: string, prefix?: string }
+	| { type: 'proton', protonPath: string, compatDataPath: string, steamPath?: string, appId?: number }
+
+export interface LauncherConfig {
+	launcher: GameLauncher,
+	/** Additional environment variables set for the game process */
+	env: Record<string, string>,
+	/** Defaults to the directory containing the game executable */
+	workingDir?: string,
 }
 
 export interface FileDialogFilter {
@@ -503,7 +612,49 @@ export interface FileDialogOptions {
 	canCreateDirectories?: boolean,
 }
 
+/** Images added to (or removed from) the images a layout preloads */
+export interface LayoutImageIdsChange {
+	layoutName: string,
+	added: int[],
+	removed: int[],
+}
+
 export interface SearchOptions {
 	text: string,
 	caseSensitive?: boolean,
 }
+
+/** `VS_VERSIONINFO` resource of an executable */
+export interface VersionInfo {
+	fixed?: FixedFileInfo
+	stringTables: VersionStringTable[]
+	/** [language, code page] pairs listed in `VarFileInfo\Translation` */
+	translations: [int, int][]
+}
+
+export interface FixedFileInfo {
+	fileVersion: [int, int, int, int]
+	productVersion: [int, int, int, int]
+	fileFlags: int
+	fileOs: int
+	fileType: int
+	fileSubtype: int
+}
+
+export interface VersionStringTable {
+	language: int
+	codePage: int
+	strings: Record<string, string>
+}
+
+/** Emitted as `towermod/game-exit` once a game started by towermod has exited */
+export interface GameExit {
+	pid: int
+	gamePath: string
+	/** Captured stdout/stderr of the game */
+	logPath: string
+	/** Absent if the process was terminated by a signal */
+	exitCode?: int
+	success: boolean
+	durationSecs: number
+}
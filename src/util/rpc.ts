@@ -3,7 +3,7 @@ import { Decoder, Encoder } from '@msgpack/msgpack'
 import { InvokeOptions, invoke } from '@tauri-apps/api/core'
 import { listen } from '@tauri-apps/api/event'
 import { getCurrentWindow } from '@tauri-apps/api/window'
-import { FileDialogOptions } from '@towermod'
+import { FileDialogOptions, GameExit } from '@towermod'
 import { useEffect } from 'react'
 
 export async function openFolder(dir: string) {
@@ -18,8 +18,8 @@ export async function deleteFile(path: string) {
 	await invoke('delete_file', { path })
 }
 
-export async function waitUntilProcessExits(pid: number) {
-	await invoke('wait_until_process_exits', { pid })
+export async function waitUntilProcessExits(pid: number): Promise<GameExit | null> {
+	return await invoke('wait_until_process_exits', { pid })
 }
 
 export async function filePicker(options?: FileDialogOptions): Promise<string | null> {
@@ -65,6 +65,7 @@ interface EventTypeMap {
 	'towermod/request-install-mod': string,
 	'towermod/progress': string,
 	'towermod/toast': string,
+	'towermod/game-exit': GameExit,
 }
 
 /** Intended for use with React Compiler */